@@ -0,0 +1,139 @@
+use std::{
+    io::{self, Error, ErrorKind, IsTerminal, Write},
+    sync::mpsc::channel,
+};
+
+use tocker_core::{Output, RowFilter, TargetType, Tocker};
+
+/// A command run without the TUI, e.g. `tocker rm containers --filter status=exited --yes`.
+pub struct Batch {
+    pub verb: String,
+    /// the kind by its config name, plural or not, e.g. `containers`
+    pub kind: String,
+    pub filters: Vec<String>,
+    /// runs without asking, for cron jobs and CI
+    pub yes: bool,
+}
+
+/// Picks the targets from a fresh listing like the TUI would, then runs the command
+/// on them. Returns what the docker CLI would exit with.
+pub fn run(batch: &Batch) -> Result<i32, Error> {
+    let filters = batch
+        .filters
+        .iter()
+        .map(|filter| RowFilter::parse(filter))
+        .collect::<Result<Vec<RowFilter>, Error>>()?;
+    let tocker = Tocker::new()?;
+    let (first, second, _) =
+        tocker.named(&format!("{} {}", singular(&batch.kind), batch.verb), None)?;
+    let only_tui = || {
+        Error::new(
+            ErrorKind::Unsupported,
+            format!("{} runs only in the TUI", batch.verb),
+        )
+    };
+    if tocker.interactive_cmd(&second, "-").is_some()
+        || tocker.watch_cmd(&second, "-").is_some()
+        || tocker.edit_cmd(&second)
+        || tocker.pin_cmd(&second)
+        || tocker.logs_cmd(&first, &second)
+        || tocker.layers_cmd(&second)
+        || tocker.browse_cmd(&second)
+    {
+        return Err(only_tui());
+    }
+    let kind = tocker.kind_of(&first).ok_or_else(only_tui)?;
+    let targeted = match tocker.check_for_target(&first, &second)? {
+        TargetType::SELECT => true,
+        TargetType::CONFIRM | TargetType::EMPTY => false,
+        // what's typed after the targets is asked for in the TUI only
+        TargetType::INPUT | TargetType::SELECTINPUT => return Err(only_tui()),
+    };
+    // a `ls` prints the rows the filters pick, e.g. to check them before a `rm`
+    let listing = !targeted && tocker.listing_cmd(&first, &second).is_some();
+    if !targeted && !listing && !filters.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} takes no --filter", batch.verb),
+        ));
+    }
+
+    let mut target = String::new();
+    if targeted || listing {
+        let (sender, results) = channel();
+        let done = move |output: Result<Output, Error>| {
+            sender.send(output).ok();
+        };
+        // what the dangling command removes is listed apart, like in the TUI
+        match tocker.dangling_cmd(&second) {
+            true => tocker.reclaimable_cmd(kind, done)?,
+            false => tocker.list_cmd(kind, done)?,
+        }
+        let Output::Table(mut table) = results.recv().map_err(Error::other)?? else {
+            return Err(Error::other("The listing came back without rows"));
+        };
+        table
+            .rows
+            .retain(|row| filters.iter().all(|filter| filter.picks(row)));
+        if listing {
+            table.lines().iter().for_each(|line| println!("{line}"));
+            return Ok(0);
+        }
+        if table.rows.is_empty() {
+            eprintln!("Nothing matches, nothing to {}", batch.verb);
+            return Ok(0);
+        }
+        if !batch.yes {
+            table.lines().iter().for_each(|line| eprintln!("{line}"));
+        }
+        target = table
+            .rows
+            .iter()
+            .map(|row| row.id.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ");
+    }
+
+    let line = tocker.command_line(&first, &second, &target);
+    if !batch.yes && !confirm(&line)? {
+        return Ok(1);
+    }
+    eprintln!("$ {line}");
+    let (sender, results) = channel();
+    tocker.exec_cmd(
+        &first,
+        &second,
+        &target,
+        move |output| {
+            sender.send(output).ok();
+        },
+        |_| {},
+    )?;
+    let output = results.recv().map_err(Error::other)??;
+    let code = output.exit_code();
+    output.lines().iter().for_each(|line| println!("{line}"));
+    Ok(code)
+}
+
+/// Asks on the terminal, a run from a script has to pass `--yes` instead.
+fn confirm(line: &str) -> Result<bool, Error> {
+    if !io::stdin().is_terminal() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Not a terminal to confirm on, pass --yes to run anyway",
+        ));
+    }
+    eprint!("Run `{line}`? [y/N] ");
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// The config name of a kind typed as the CLI lists it, e.g. `registries` or `images`.
+fn singular(kind: &str) -> String {
+    match kind.strip_suffix("ies") {
+        Some(stem) => format!("{stem}y"),
+        None => String::from(kind.strip_suffix('s').unwrap_or(kind)),
+    }
+}
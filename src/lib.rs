@@ -0,0 +1,8 @@
+//! The docker side of tocker: the keybinding model, what every command targets and
+//! runs, and the worker executing them against the daemon, for the TUI or any other
+//! front end.
+#![allow(clippy::upper_case_acronyms)]
+
+mod tocker;
+
+pub use tocker::*;
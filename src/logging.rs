@@ -0,0 +1,42 @@
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    path::PathBuf,
+    str::FromStr,
+    sync::Mutex,
+};
+
+use tracing::level_filters::LevelFilter;
+
+/// Overrides what gets written, e.g. `TOCKER_LOG=debug` for every keypress too.
+const LEVEL_VAR: &str = "TOCKER_LOG";
+
+/// `~/.local/state/tocker/tocker.log`, what to attach when reporting weird behavior.
+fn log_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".local/state/tocker/tocker.log"))
+}
+
+/// Appends to the log file from now on, tocker runs on without it when it can't be opened.
+pub fn init() {
+    let Some(path) = log_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        if fs::create_dir_all(dir).is_err() {
+            return;
+        }
+    }
+    let Ok(file) = OpenOptions::new().create(true).append(true).open(&path) else {
+        return;
+    };
+    let level = env::var(LEVEL_VAR)
+        .ok()
+        .and_then(|level| LevelFilter::from_str(&level).ok())
+        .unwrap_or(LevelFilter::INFO);
+    tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_max_level(level)
+        .with_target(false)
+        .try_init()
+        .ok();
+}
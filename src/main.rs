@@ -1,14 +1,71 @@
-mod tocker;
+#![allow(clippy::upper_case_acronyms)]
+
+mod batch;
+mod logging;
 mod tui;
 
+use batch::Batch;
+use clap::Parser;
 use crossterm::terminal::enable_raw_mode;
-use std::io;
+use std::{env, io, process::exit};
 use tui::Tui;
 
+/// Turns plain mode on like `--plain`, unless empty or `0`.
+const PLAIN_VAR: &str = "TOCKER_PLAIN";
+
+/// A simple terminal user interface for Docker.
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    /// Daemon to connect to, e.g. tcp://host:2376 or ssh://user@host [default: DOCKER_HOST]
+    #[arg(short = 'H', long)]
+    host: Option<String>,
+    /// Screen reader friendly: no box drawing, labelled rows and a status line announcing
+    /// changes [env: TOCKER_PLAIN]
+    #[arg(long)]
+    plain: bool,
+    /// Runs a command without the TUI and exits, e.g. `rm containers --filter status=exited`
+    #[arg(requires = "kind")]
+    verb: Option<String>,
+    /// What the command runs on, e.g. containers, images or volumes
+    kind: Option<String>,
+    /// Picks the rows to run on, every one must match: id=, name=, status= or label=
+    #[arg(long, requires = "verb")]
+    filter: Vec<String>,
+    /// Runs without asking first, e.g. from cron or CI
+    #[arg(short, long, requires = "verb")]
+    yes: bool,
+}
+
 fn main() -> Result<(), io::Error> {
+    let args = Args::parse();
+    logging::init();
+    if let Some(host) = args.host {
+        // through the environment, so the docker CLI we run for exec/compose/build follows too
+        env::set_var("DOCKER_HOST", host);
+    }
+
+    if let Some(verb) = args.verb {
+        let batch = Batch {
+            verb,
+            kind: args.kind.unwrap_or_default(),
+            filters: args.filter,
+            yes: args.yes,
+        };
+        match batch::run(&batch) {
+            Ok(code) => exit(code),
+            Err(err) => {
+                eprintln!("{err}");
+                exit(1)
+            }
+        }
+    }
+
     enable_raw_mode().unwrap();
 
-    let mut tocker_tui = Tui::new()?;
+    let plain =
+        args.plain || env::var(PLAIN_VAR).is_ok_and(|value| !matches!(value.as_str(), "" | "0"));
+    let mut tocker_tui = Tui::new(plain)?;
     tocker_tui.draw_ui().unwrap();
 
     tocker_tui.start_loop();
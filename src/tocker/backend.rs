@@ -0,0 +1,73 @@
+use std::{future::Future, io::Error};
+
+use super::{
+    engine::Engine,
+    worker::{Events, Progress},
+    DockerPrompt, LogOptions, Output, Watch,
+};
+
+/// Whatever answers the docker commands, the daemon itself or a [`MockBackend`] in tests.
+///
+/// [`MockBackend`]: super::MockBackend
+pub trait DockerBackend: Clone + Send + Sync + 'static {
+    /// Where commands go, shown in the status bar.
+    fn host(&self) -> &str;
+
+    fn ping(&self) -> impl Future<Output = Result<(), Error>> + Send;
+
+    /// Runs `cmd`, `progress` gets what long running commands have done so far.
+    fn execute(
+        &self,
+        cmd: DockerPrompt,
+        progress: &mut Progress,
+    ) -> impl Future<Output = Result<Output, Error>> + Send;
+
+    /// One refresh of a watched view.
+    fn watch(&self, watch: Watch) -> impl Future<Output = Result<Output, Error>> + Send;
+
+    /// Follows the container events until the stream ends.
+    fn events(&self, each: &mut Events) -> impl Future<Output = Result<(), Error>> + Send;
+
+    /// Follows the logs of `container`, its last lines first.
+    fn logs(
+        &self,
+        container: &str,
+        options: LogOptions,
+        each: &mut Events,
+    ) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+impl DockerBackend for Engine {
+    fn host(&self) -> &str {
+        Engine::host(self)
+    }
+
+    fn ping(&self) -> impl Future<Output = Result<(), Error>> + Send {
+        Engine::ping(self)
+    }
+
+    fn execute(
+        &self,
+        cmd: DockerPrompt,
+        progress: &mut Progress,
+    ) -> impl Future<Output = Result<Output, Error>> + Send {
+        Engine::execute(self, cmd, progress)
+    }
+
+    fn watch(&self, watch: Watch) -> impl Future<Output = Result<Output, Error>> + Send {
+        Engine::watch(self, watch)
+    }
+
+    fn events(&self, each: &mut Events) -> impl Future<Output = Result<(), Error>> + Send {
+        Engine::events(self, each)
+    }
+
+    fn logs(
+        &self,
+        container: &str,
+        options: LogOptions,
+        each: &mut Events,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        Engine::logs(self, container, options, each)
+    }
+}
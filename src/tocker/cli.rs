@@ -0,0 +1,164 @@
+use std::{env, process::Command};
+
+use super::{compose::Project, volume, ComposeCommand};
+
+/// Small enough to pull quickly the first time a volume is browsed.
+const BROWSE_IMAGE: &str = "alpine";
+
+/// Operations that need the docker CLI itself, e.g. because they take over the terminal.
+pub fn exec(container: &str, shell: &str) -> Command {
+    let mut command = Command::new("docker");
+    command.args(["exec", "-it", container, shell]);
+    command
+}
+
+/// The platform's own opener, e.g. the default browser for an url.
+pub fn open(url: &str) -> Command {
+    let mut command = match env::consts::OS {
+        "macos" => Command::new("open"),
+        "windows" => {
+            let mut command = Command::new("cmd");
+            // the empty title, start takes the first quoted argument as one
+            command.args(["/C", "start", ""]);
+            command
+        }
+        _ => Command::new("xdg-open"),
+    };
+    command.arg(url);
+    command
+}
+
+pub fn push(reference: &str) -> Command {
+    let mut command = Command::new("docker");
+    command.args(["push", reference]);
+    command
+}
+
+/// The secret is read from stdin, so it isn't visible in the process list.
+pub fn login(server: &str, username: &str) -> Command {
+    let mut command = Command::new("docker");
+    command.args(["login", "--username", username, "--password-stdin", server]);
+    command
+}
+
+pub fn logout(server: &str) -> Command {
+    let mut command = Command::new("docker");
+    command.args(["logout", server]);
+    command
+}
+
+pub fn load(path: &str) -> Command {
+    let mut command = Command::new("docker");
+    command.args(["load", "--input", path]);
+    command
+}
+
+pub fn import(path: &str, reference: Option<&str>) -> Command {
+    let mut command = Command::new("docker");
+    command.args(["import", path]);
+    command.args(reference);
+    command
+}
+
+pub fn cp(source: &str, destination: &str) -> Command {
+    let mut command = Command::new("docker");
+    command.args(["cp", source, destination]);
+    command
+}
+
+/// Lists everything in the volume from a throwaway container it's mounted read-only in.
+pub fn browse(volume: &str) -> Command {
+    let mut command = Command::new("docker");
+    command.args(["run", "--rm", "--network", "none", "--volume"]);
+    command.arg(format!("{volume}:{}:ro", volume::MOUNT));
+    command.args([BROWSE_IMAGE, "find", volume::MOUNT, "-exec", "stat", "-c"]);
+    command.args([volume::FORMAT, "{}", "+"]);
+    command
+}
+
+pub fn build(context: &str, tag: Option<&str>) -> Command {
+    let mut command = Command::new("docker");
+    // plain progress prints one line per step instead of redrawing the terminal
+    command.args(["build", "--progress", "plain"]);
+    if let Some(tag) = tag {
+        command.args(["--tag", tag]);
+    }
+    command.arg(context);
+    command
+}
+
+/// A command line as typed, `docker` first, quotes group words like a shell does.
+pub fn line(line: &str) -> Option<Command> {
+    let words = words(line);
+    let (program, args) = words.split_first()?;
+    if program != "docker" {
+        return None;
+    }
+    let mut command = Command::new("docker");
+    command.args(args);
+    Some(command)
+}
+
+/// A custom command line from the config, any program, quoted like `line`.
+pub fn custom(line: &str) -> Option<Command> {
+    let words = words(line);
+    let (program, args) = words.split_first()?;
+    let mut command = Command::new(program);
+    command.args(args);
+    Some(command)
+}
+
+/// Splits on whitespace outside of single or double quotes, which are dropped.
+fn words(line: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+pub fn deploy(file: &str, stack: &str) -> Command {
+    let mut command = Command::new("docker");
+    command.args(["stack", "deploy", "--compose-file", file, stack]);
+    command
+}
+
+/// Removes the services, networks, secrets and configs of the stack.
+pub fn stack_rm(stack: &str) -> Command {
+    let mut command = Command::new("docker");
+    command.args(["stack", "rm", stack]);
+    command
+}
+
+pub fn compose(project: &Project, command: ComposeCommand) -> Command {
+    let mut cmd = Command::new("docker");
+    cmd.args(["compose", "--project-name", &project.name]);
+    if let ComposeCommand::UP = command {
+        // only up has to read the compose files again, the rest works from the labels
+        if let Some(working_dir) = &project.working_dir {
+            cmd.args(["--project-directory", working_dir]);
+        }
+        project.config_files.iter().for_each(|file| {
+            cmd.args(["--file", file]);
+        });
+    }
+    match command {
+        ComposeCommand::UP => cmd.args(["up", "--detach"]),
+        ComposeCommand::DOWN => cmd.arg("down"),
+        ComposeCommand::RESTART => cmd.arg("restart"),
+        ComposeCommand::LOGS => cmd.args(["logs", "--no-color", "--tail", "100"]),
+    };
+    cmd
+}
@@ -0,0 +1,82 @@
+use std::collections::{BTreeMap, HashMap};
+
+use bollard::models::ContainerSummary;
+
+pub const PROJECT_LABEL: &str = "com.docker.compose.project";
+pub const WORKING_DIR_LABEL: &str = "com.docker.compose.project.working_dir";
+pub const CONFIG_FILES_LABEL: &str = "com.docker.compose.project.config_files";
+const SERVICE_LABEL: &str = "com.docker.compose.service";
+
+/// A compose project, rebuilt from the labels compose puts on its containers.
+#[derive(Debug, Default)]
+pub struct Project {
+    pub name: String,
+    pub working_dir: Option<String>,
+    pub config_files: Vec<String>,
+    pub services: Vec<String>,
+    /// container count per state, e.g. running → 2
+    pub states: BTreeMap<String, usize>,
+}
+
+impl Project {
+    /// Same summary as `docker compose ls`, e.g. `running(2), exited(1)`.
+    pub fn status(&self) -> String {
+        self.states
+            .iter()
+            .map(|(state, count)| format!("{state}({count})"))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// The labels it was rebuilt from, where it lives and the files it's made of.
+    pub fn labels(&self) -> HashMap<String, String> {
+        let mut labels = HashMap::from([(String::from(PROJECT_LABEL), self.name.clone())]);
+        if let Some(working_dir) = &self.working_dir {
+            labels.insert(String::from(WORKING_DIR_LABEL), working_dir.clone());
+        }
+        if !self.config_files.is_empty() {
+            labels.insert(
+                String::from(CONFIG_FILES_LABEL),
+                self.config_files.join(","),
+            );
+        }
+        labels
+    }
+}
+
+/// Groups containers by compose project, containers outside of compose are left out.
+pub fn projects(containers: &[ContainerSummary]) -> Vec<Project> {
+    let mut projects: BTreeMap<String, Project> = BTreeMap::new();
+    containers.iter().for_each(|container| {
+        let Some(labels) = &container.labels else {
+            return;
+        };
+        let Some(name) = labels.get(PROJECT_LABEL) else {
+            return;
+        };
+        let project = projects.entry(name.clone()).or_insert_with(|| Project {
+            name: name.clone(),
+            ..Default::default()
+        });
+        if project.working_dir.is_none() {
+            project.working_dir = labels.get(WORKING_DIR_LABEL).cloned();
+        }
+        if project.config_files.is_empty() {
+            project.config_files = labels
+                .get(CONFIG_FILES_LABEL)
+                .map(|files| files.split(',').map(String::from).collect())
+                .unwrap_or_default();
+        }
+        if let Some(service) = labels.get(SERVICE_LABEL) {
+            if !project.services.contains(service) {
+                project.services.push(service.clone());
+            }
+        }
+        let state = container
+            .state
+            .map(|state| state.to_string())
+            .unwrap_or(String::from("unknown"));
+        *project.states.entry(state).or_default() += 1;
+    });
+    projects.into_values().collect()
+}
@@ -0,0 +1,600 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    fs::{self, OpenOptions},
+    io::{Error, ErrorKind, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use super::{Desktop, DockerCommand, DockerKind, GeneralCommand, Select, TargetType};
+
+/// Seconds between two refreshes of the listing on screen.
+const DEFAULT_REFRESH: u64 = 5;
+
+/// Replaced by the target in the command line of a custom command.
+const PLACEHOLDER: &str = "{{id}}";
+
+/// `preset = "vim"`, hjkl moves, `g`/`G` jump, `h`/`l` switch tabs, `dd` removes
+/// and `:` runs a command by name.
+const VIM_PRESET: &str = r#"
+[general]
+split = "ctrl+w"
+
+[select]
+next = ["n", "j"]
+prev = ["N", "k"]
+first = ["home", "g"]
+last = ["end", "shift+G"]
+pageup = ["pageup", "ctrl+u"]
+pagedown = ["pagedown", "ctrl+d"]
+prevtab = "h"
+nexttab = "l"
+command = ":"
+
+[repeat]
+rm = "d"
+"#;
+
+/// `preset = "emacs"`, C-n/C-p move, C-v/M-v page, C-s filters, C-g cancels and
+/// M-x runs a command by name. The palette moves to M-p, C-p is taken by moving up.
+const EMACS_PRESET: &str = r#"
+[general]
+cancel = ["esc", "ctrl+c", "ctrl+g"]
+palette = "alt+p"
+
+[select]
+next = ["n", "ctrl+n"]
+prev = ["N", "ctrl+p"]
+first = ["home", "alt+<"]
+last = ["end", "alt+>"]
+pageup = ["pageup", "alt+v"]
+pagedown = ["pagedown", "ctrl+v"]
+filter = ["/", "ctrl+s"]
+command = "alt+x"
+"#;
+
+/// Keys bound to an action, either one (`"ctrl+q"`) or several (`["esc", "ctrl+c"]`).
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// `~/.config/tocker/config.toml`, every section remaps actions by name, e.g.
+///
+/// ```toml
+/// refresh = 10
+/// preset = "vim"
+///
+/// [theme]
+/// name = "light"
+/// cursor = { fg = "white", bg = "#005f87" }
+///
+/// [command]
+/// ls = "L"
+///
+/// [general]
+/// cancel = ["esc", "ctrl+g"]
+///
+/// [hosts]
+/// local = "unix:///var/run/docker.sock"
+/// vps = "ssh://me@vps.example.com"
+///
+/// [tls]
+/// ca = "~/.docker/ca.pem"
+/// cert = "~/.docker/cert.pem"
+/// key = "~/.docker/key.pem"
+///
+/// [[custom]]
+/// name = "tail"
+/// key = "W"
+/// kind = "container"
+/// command = "docker logs --tail 100 {{id}}"
+///
+/// [[macro]]
+/// name = "clean-exited"
+/// key = "alt+1"
+/// keys = ["c", "l", "enter", "a", "enter"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// seconds between listing refreshes, `0` turns them off
+    pub refresh: Option<u64>,
+    /// seconds a docker command may run before it's canceled, `0` waits for it forever
+    pub timeout: Option<u64>,
+    /// a whole keymap, `vim` or `emacs`, laid under the remaps below
+    pub preset: Option<String>,
+    pub kind: HashMap<String, Keys>,
+    pub command: HashMap<String, Keys>,
+    pub general: HashMap<String, Keys>,
+    pub select: HashMap<String, Keys>,
+    /// commands run on the tab on screen by pressing their key twice, e.g. `rm = "d"`
+    pub repeat: HashMap<String, Keys>,
+    pub theme: ThemeConfig,
+    /// extra commands, added to the commands of their kind
+    pub custom: Vec<Custom>,
+    /// the daemons listed side by side in the fleet tab, endpoints by name
+    pub hosts: BTreeMap<String, String>,
+    /// client certificates for `tcp://` daemons behind TLS
+    pub tls: Option<TlsConfig>,
+    /// the columns listed for a kind by its name and in their order, e.g.
+    /// `container = ["names", "image", "status", "ports"]`
+    pub columns: HashMap<String, Vec<String>>,
+    /// keys recorded in tocker, replayed by a key of their own
+    #[serde(rename = "macro")]
+    pub macros: Vec<Macro>,
+    /// what's forwarded to the notifications of the desktop, e.g. `["died", "built"]`
+    pub desktop: Vec<String>,
+    pub logs: LogsConfig,
+}
+
+/// How much of a container's logs the log viewer starts out with.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogsConfig {
+    /// the newest lines printed before it opened
+    pub tail: Option<usize>,
+    /// how far back they go at most, e.g. `10m`, `2h` or `1d`
+    pub since: Option<String>,
+}
+
+/// Client certificates for daemons behind TLS, e.g. `tcp://host:2376`, like the
+/// `--tlscacert`, `--tlscert` and `--tlskey` flags of the docker CLI.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    pub ca: String,
+    pub cert: String,
+    pub key: String,
+}
+
+impl TlsConfig {
+    /// The ca, cert and key files, a leading `~/` taken as the home directory, once
+    /// each of them is found.
+    pub fn files(&self) -> Result<[PathBuf; 3], Error> {
+        let file = |name: &str, path: &str| {
+            let path = match path.strip_prefix("~/").zip(dirs::home_dir()) {
+                Some((rest, home)) => home.join(rest),
+                None => PathBuf::from(path),
+            };
+            match path.is_file() {
+                true => Ok(path),
+                false => Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("The tls {name} in config, {}, isn't a file", path.display()),
+                )),
+            }
+        };
+        Ok([
+            file("ca", &self.ca)?,
+            file("cert", &self.cert)?,
+            file("key", &self.key)?,
+        ])
+    }
+}
+
+/// A command of the user's, its `{{id}}` placeholders filled with the target.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Custom {
+    pub name: String,
+    pub key: String,
+    pub kind: String,
+    pub command: String,
+    /// `select` (default) runs it once per picked row, `input` with what's typed,
+    /// `empty` as it is
+    #[serde(default = "default_target")]
+    pub target: String,
+}
+
+/// Keypresses replayed as they were recorded, one at a time.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Macro {
+    pub name: String,
+    pub key: String,
+    pub keys: Vec<String>,
+}
+
+impl Macro {
+    pub fn keys(&self) -> Result<Vec<KeyEvent>, Error> {
+        self.keys.iter().map(|key| parse_key(key)).collect()
+    }
+
+    /// Appends the macro to the config file, whatever else is in there stays as it is.
+    pub fn save(&self) -> Result<(), Error> {
+        let path = path().ok_or(Error::new(ErrorKind::NotFound, "No config directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // a JSON string is a valid TOML one
+        let quote = |text: &str| serde_json::to_string(text).map_err(Error::other);
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| quote(key))
+            .collect::<Result<Vec<String>, Error>>()?
+            .join(", ");
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        write!(
+            file,
+            "\n[[macro]]\nname = {}\nkey = {}\nkeys = [{keys}]\n",
+            quote(&self.name)?,
+            quote(&self.key)?
+        )
+    }
+}
+
+fn default_target() -> String {
+    String::from("select")
+}
+
+impl Custom {
+    /// The command lines run for `target`, one per picked row when selected.
+    pub fn lines(&self, target: &str) -> Vec<String> {
+        if !self.command.contains(PLACEHOLDER) {
+            return vec![self.command.clone()];
+        }
+        match self.target.as_str() {
+            "select" => target
+                .split_whitespace()
+                .map(|id| self.command.replace(PLACEHOLDER, id))
+                .collect(),
+            _ => vec![self.command.replace(PLACEHOLDER, target)],
+        }
+    }
+}
+
+/// A built-in theme by `name`, `dark` by default, with single styles overridden.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub name: Option<String>,
+    pub cursor: Option<Colors>,
+    pub selected: Option<Colors>,
+    pub commands: Option<Colors>,
+    pub highlight: Option<Colors>,
+    pub tab: Option<Colors>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Colors {
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+}
+
+impl Config {
+    /// The user config if there is one, the built-in defaults otherwise.
+    pub fn load() -> Result<Config, Error> {
+        let Some(path) = path() else {
+            return Ok(Config::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content).map_err(|err| {
+                Error::new(ErrorKind::InvalidData, format!("{}: {err}", path.display()))
+            }),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Config::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// The keymap `preset` names, the defaults are remapped by it before this config.
+    pub fn preset(&self) -> Result<Config, Error> {
+        let content = match self.preset.as_deref() {
+            None => return Ok(Config::default()),
+            Some("vim") => VIM_PRESET,
+            Some("emacs") => EMACS_PRESET,
+            Some(name) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unknown preset in config: {name}"),
+                ))
+            }
+        };
+        toml::from_str(content).map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string()))
+    }
+
+    /// How often the listing on screen is fetched again, if at all.
+    pub fn refresh_every(&self) -> Option<Duration> {
+        match self.refresh.unwrap_or(DEFAULT_REFRESH) {
+            0 => None,
+            seconds => Some(Duration::from_secs(seconds)),
+        }
+    }
+
+    /// How long a docker command may run, commands like a pull of a huge image wait
+    /// for as long as it takes unless one is set.
+    pub fn timeout_after(&self) -> Option<Duration> {
+        match self.timeout.unwrap_or_default() {
+            0 => None,
+            seconds => Some(Duration::from_secs(seconds)),
+        }
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("tocker").join("config.toml"))
+}
+
+/// Replaces the default keys of every action named in `overrides`.
+pub fn rebind<T: Copy + PartialEq>(
+    bindings: &mut HashMap<KeyEvent, T>,
+    overrides: &HashMap<String, Keys>,
+    action: fn(&str) -> Option<T>,
+) -> Result<(), Error> {
+    for (name, keys) in overrides {
+        let value = action(name).ok_or(Error::new(
+            ErrorKind::InvalidData,
+            format!("Unknown action in config: {name}"),
+        ))?;
+        let keys = match keys {
+            Keys::One(key) => vec![parse_key(key)?],
+            Keys::Many(keys) => keys
+                .iter()
+                .map(|key| parse_key(key))
+                .collect::<Result<Vec<KeyEvent>, Error>>()?,
+        };
+        bindings.retain(|_, bound| *bound != value);
+        keys.into_iter().for_each(|key| {
+            bindings.insert(key, value);
+        });
+    }
+    Ok(())
+}
+
+/// The key shown in the legends for `value`, the same one every time.
+pub fn key_for<T: PartialEq>(bindings: &HashMap<KeyEvent, T>, value: &T) -> String {
+    let mut labels: Vec<String> = bindings
+        .iter()
+        .filter(|(_, bound)| *bound == value)
+        .map(|(key, _)| key_label(key))
+        .collect();
+    labels.sort();
+    labels.into_iter().next().unwrap_or(String::from("?"))
+}
+
+/// `(keys, action)` pairs, actions by their config name and every key bound to them.
+pub fn legend<T: Debug>(bindings: &HashMap<KeyEvent, T>) -> Vec<(String, String)> {
+    let mut actions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    bindings.iter().for_each(|(key, value)| {
+        actions
+            .entry(format!("{value:?}").to_lowercase())
+            .or_default()
+            .push(key_label(key));
+    });
+    actions
+        .into_iter()
+        .map(|(action, mut keys)| {
+            keys.sort();
+            (keys.join("/"), action)
+        })
+        .collect()
+}
+
+pub fn kind(name: &str) -> Option<DockerKind> {
+    match name {
+        "image" => Some(DockerKind::Image),
+        "container" => Some(DockerKind::Container),
+        "volume" => Some(DockerKind::Volume),
+        "project" => Some(DockerKind::Project),
+        "registry" => Some(DockerKind::Registry),
+        "system" => Some(DockerKind::System),
+        "service" => Some(DockerKind::Service),
+        "stack" => Some(DockerKind::Stack),
+        "secret" => Some(DockerKind::Secret),
+        "config" => Some(DockerKind::Config),
+        "fleet" => Some(DockerKind::Fleet),
+        _ => None,
+    }
+}
+
+pub fn command(name: &str) -> Option<DockerCommand> {
+    match name {
+        "ls" => Some(DockerCommand::LS),
+        "rm" => Some(DockerCommand::RM),
+        "tag" => Some(DockerCommand::TAG),
+        "stop" => Some(DockerCommand::STOP),
+        "exec" => Some(DockerCommand::EXEC),
+        "stats" => Some(DockerCommand::STATS),
+        "up" => Some(DockerCommand::UP),
+        "down" => Some(DockerCommand::DOWN),
+        "restart" => Some(DockerCommand::RESTART),
+        "logs" => Some(DockerCommand::LOGS),
+        "pull" => Some(DockerCommand::PULL),
+        "pause" => Some(DockerCommand::PAUSE),
+        "unpause" => Some(DockerCommand::UNPAUSE),
+        "open" => Some(DockerCommand::OPEN),
+        "cp" => Some(DockerCommand::CP),
+        "push" => Some(DockerCommand::PUSH),
+        "login" => Some(DockerCommand::LOGIN),
+        "search" => Some(DockerCommand::SEARCH),
+        "history" => Some(DockerCommand::HISTORY),
+        "layers" => Some(DockerCommand::LAYERS),
+        "top" => Some(DockerCommand::TOP),
+        "rename" => Some(DockerCommand::RENAME),
+        "kill" => Some(DockerCommand::KILL),
+        "update" => Some(DockerCommand::UPDATE),
+        "health" => Some(DockerCommand::HEALTH),
+        "browse" => Some(DockerCommand::BROWSE),
+        "create" => Some(DockerCommand::CREATE),
+        "save" => Some(DockerCommand::SAVE),
+        "load" => Some(DockerCommand::LOAD),
+        "export" => Some(DockerCommand::EXPORT),
+        "import" => Some(DockerCommand::IMPORT),
+        "dangling" => Some(DockerCommand::DANGLING),
+        "policy" => Some(DockerCommand::POLICY),
+        "env" => Some(DockerCommand::ENV),
+        "edit" => Some(DockerCommand::EDIT),
+        "pin" => Some(DockerCommand::PIN),
+        "ps" => Some(DockerCommand::PS),
+        "scale" => Some(DockerCommand::SCALE),
+        "deploy" => Some(DockerCommand::DEPLOY),
+        "logout" => Some(DockerCommand::LOGOUT),
+        "inspect" => Some(DockerCommand::INSPECT),
+        "prune" => Some(DockerCommand::PRUNE),
+        _ => None,
+    }
+}
+
+pub fn general(name: &str) -> Option<GeneralCommand> {
+    match name {
+        "quit" => Some(GeneralCommand::QUIT),
+        "cancel" => Some(GeneralCommand::CANCEL),
+        "help" => Some(GeneralCommand::HELP),
+        "clean" => Some(GeneralCommand::CLEAN),
+        "build" => Some(GeneralCommand::BUILD),
+        "split" => Some(GeneralCommand::SPLIT),
+        "tab" => Some(GeneralCommand::TAB),
+        "history" => Some(GeneralCommand::HISTORY),
+        "events" => Some(GeneralCommand::EVENTS),
+        "find" => Some(GeneralCommand::FIND),
+        "context" => Some(GeneralCommand::CONTEXT),
+        "record" => Some(GeneralCommand::RECORD),
+        "palette" => Some(GeneralCommand::PALETTE),
+        "pager" => Some(GeneralCommand::PAGER),
+        _ => None,
+    }
+}
+
+pub fn select(name: &str) -> Option<Select> {
+    match name {
+        "up" => Some(Select::UP),
+        "down" => Some(Select::DOWN),
+        "select" => Some(Select::SELECT),
+        "confirm" => Some(Select::CONFIRM),
+        "cancel" => Some(Select::CANCEL),
+        "filter" => Some(Select::FILTER),
+        "labels" => Some(Select::LABELS),
+        "edit" => Some(Select::EDIT),
+        "next" => Some(Select::NEXT),
+        "prev" => Some(Select::PREV),
+        "sort" => Some(Select::SORT),
+        "copy" => Some(Select::COPY),
+        "copyname" => Some(Select::COPYNAME),
+        "export" => Some(Select::EXPORT),
+        "all" => Some(Select::ALL),
+        "invert" => Some(Select::INVERT),
+        "clear" => Some(Select::CLEAR),
+        "pageup" => Some(Select::PAGEUP),
+        "pagedown" => Some(Select::PAGEDOWN),
+        "first" => Some(Select::FIRST),
+        "last" => Some(Select::LAST),
+        "prevtab" => Some(Select::PREVTAB),
+        "nexttab" => Some(Select::NEXTTAB),
+        "command" => Some(Select::COMMAND),
+        "truncate" => Some(Select::TRUNCATE),
+        "digests" => Some(Select::DIGESTS),
+        "favorite" => Some(Select::FAVORITE),
+        "watch" => Some(Select::WATCH),
+        _ => None,
+    }
+}
+
+pub fn desktop(name: &str) -> Option<Desktop> {
+    match name {
+        "died" => Some(Desktop::DIED),
+        "built" => Some(Desktop::BUILT),
+        "pulled" => Some(Desktop::PULLED),
+        _ => None,
+    }
+}
+
+/// How the target of a custom command is given.
+pub fn target(name: &str) -> Option<TargetType> {
+    match name {
+        "select" => Some(TargetType::SELECT),
+        "input" => Some(TargetType::INPUT),
+        "empty" => Some(TargetType::EMPTY),
+        _ => None,
+    }
+}
+
+/// Parses keys like `l`, `S`, `ctrl+b`, `esc`, `enter` or `space`.
+pub fn parse_key(key: &str) -> Result<KeyEvent, Error> {
+    let invalid = || {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid key in config: {key}"),
+        )
+    };
+    let (modifiers, code) = match key.rsplit_once('+') {
+        Some((modifier, code)) if !code.is_empty() => {
+            let modifiers = modifier
+                .split('+')
+                .map(|modifier| match modifier.to_lowercase().as_str() {
+                    "ctrl" => Ok(KeyModifiers::CONTROL),
+                    "alt" => Ok(KeyModifiers::ALT),
+                    "shift" => Ok(KeyModifiers::SHIFT),
+                    _ => Err(invalid()),
+                })
+                .collect::<Result<Vec<KeyModifiers>, Error>>()?
+                .into_iter()
+                .fold(KeyModifiers::NONE, |all, modifier| all | modifier);
+            (modifiers, code)
+        }
+        _ => (KeyModifiers::NONE, key),
+    };
+    let code = match code.to_lowercase().as_str() {
+        "esc" => KeyCode::Esc,
+        "enter" => KeyCode::Enter,
+        "space" => KeyCode::Char(' '),
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        _ => {
+            let mut chars = code.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return Err(invalid()),
+            }
+        }
+    };
+    Ok(KeyEvent::new(code, modifiers))
+}
+
+pub fn key_label(key: &KeyEvent) -> String {
+    key_name(key).unwrap_or(String::from("?"))
+}
+
+/// The key as [`parse_key`] takes it, none for keys it doesn't know, e.g. `F1`.
+pub fn key_name(key: &KeyEvent) -> Option<String> {
+    let code = match key.code {
+        KeyCode::Esc => String::from("esc"),
+        KeyCode::Enter => String::from("enter"),
+        KeyCode::Char(' ') => String::from("space"),
+        KeyCode::Tab => String::from("tab"),
+        KeyCode::Backspace => String::from("backspace"),
+        KeyCode::Up => String::from("up"),
+        KeyCode::Down => String::from("down"),
+        KeyCode::Left => String::from("left"),
+        KeyCode::Right => String::from("right"),
+        KeyCode::PageUp => String::from("pageup"),
+        KeyCode::PageDown => String::from("pagedown"),
+        KeyCode::Home => String::from("home"),
+        KeyCode::End => String::from("end"),
+        KeyCode::Char(c) => c.to_string(),
+        _ => return None,
+    };
+    let mut label = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        label.push_str("ctrl+");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        label.push_str("alt+");
+    }
+    Some(label + &code)
+}
@@ -0,0 +1,72 @@
+use std::{
+    env,
+    io::{Error, ErrorKind},
+    process::Command,
+};
+
+use serde::Deserialize;
+
+/// The context the docker CLI uses without `--context` or `DOCKER_HOST`.
+const DEFAULT_CONTEXT: &str = "default";
+
+/// A row of `docker context ls`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Context {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub docker_endpoint: String,
+    #[serde(default)]
+    pub current: bool,
+}
+
+impl Context {
+    pub fn line(&self) -> String {
+        let current = if self.current { "*" } else { " " };
+        match self.description.is_empty() {
+            true => format!("{current} {}  {}", self.name, self.docker_endpoint),
+            false => format!(
+                "{current} {}  {}  {}",
+                self.name, self.docker_endpoint, self.description
+            ),
+        }
+    }
+
+    /// Points the docker CLI run from now on at this context, custom commands
+    /// included. `DOCKER_HOST` goes, the CLI would pick it over the context.
+    pub fn activate(&self) {
+        match self.name.as_str() {
+            DEFAULT_CONTEXT => {
+                env::remove_var("DOCKER_CONTEXT");
+                env::set_var("DOCKER_HOST", &self.docker_endpoint);
+            }
+            name => {
+                env::remove_var("DOCKER_HOST");
+                env::set_var("DOCKER_CONTEXT", name);
+            }
+        }
+    }
+}
+
+/// Every context the docker CLI knows of, as `docker context ls` lists them.
+pub fn list() -> Result<Vec<Context>, Error> {
+    let output = Command::new("docker")
+        .args(["context", "ls", "--format", "{{json .}}"])
+        .output()?;
+    if !output.status.success() {
+        return Err(Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    let contexts: Vec<Context> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(Error::other))
+        .collect::<Result<_, _>>()?;
+    match contexts.is_empty() {
+        true => Err(Error::new(ErrorKind::NotFound, "No docker context")),
+        false => Ok(contexts),
+    }
+}
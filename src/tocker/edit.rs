@@ -0,0 +1,103 @@
+use std::{
+    collections::HashMap,
+    env,
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use super::compose::{CONFIG_FILES_LABEL, WORKING_DIR_LABEL};
+
+/// Looked for in that order, like `docker compose` does without `--file`.
+const COMPOSE_FILES: [&str; 4] = [
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yaml",
+    "docker-compose.yml",
+];
+const DOCKERFILE: &str = "Dockerfile";
+
+/// A file something is built from, to edit and build again.
+#[derive(Debug)]
+pub enum BuildFile {
+    Dockerfile(PathBuf),
+    Compose(PathBuf),
+}
+
+impl BuildFile {
+    /// The file a row says it was built from, or the one in the directory tocker runs in.
+    pub fn find(labels: Option<&HashMap<String, String>>) -> Result<BuildFile, Error> {
+        let labels = labels.cloned().unwrap_or_default();
+        if let Some(file) = labels
+            .get(CONFIG_FILES_LABEL)
+            .and_then(|files| files.split(',').next())
+        {
+            return Ok(BuildFile::Compose(PathBuf::from(file)));
+        }
+        let dir = match labels.get(WORKING_DIR_LABEL) {
+            Some(dir) => PathBuf::from(dir),
+            None => env::current_dir()?,
+        };
+        BuildFile::detect(&dir).ok_or(Error::new(
+            ErrorKind::NotFound,
+            format!("No Dockerfile or compose file in {}", dir.display()),
+        ))
+    }
+
+    /// A compose file first, a project is usually what's worked on when there's one.
+    fn detect(dir: &Path) -> Option<BuildFile> {
+        if let Some(file) = COMPOSE_FILES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+        {
+            return Some(BuildFile::Compose(file));
+        }
+        let dockerfile = dir.join(DOCKERFILE);
+        dockerfile
+            .is_file()
+            .then_some(BuildFile::Dockerfile(dockerfile))
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            BuildFile::Dockerfile(path) | BuildFile::Compose(path) => path,
+        }
+    }
+
+    /// `$VISUAL` or `$EDITOR` on the file, `vi` if neither is set.
+    pub fn editor(&self) -> Command {
+        let editor = env::var("VISUAL")
+            .or_else(|_| env::var("EDITOR"))
+            .unwrap_or(String::from("vi"));
+        // e.g. `code --wait`
+        let mut words = editor.split_whitespace();
+        let mut command = Command::new(words.next().unwrap_or("vi"));
+        command.args(words);
+        command.arg(self.path());
+        command
+    }
+
+    /// The docker command line building again from the file, offered once it's edited.
+    pub fn rebuild(&self) -> String {
+        let path = self.path();
+        match self {
+            BuildFile::Dockerfile(_) => {
+                let context = path.parent().unwrap_or(Path::new("."));
+                format!("docker build --file {} {}", quoted(path), quoted(context))
+            }
+            BuildFile::Compose(_) => {
+                format!("docker compose --file {} up --detach --build", quoted(path))
+            }
+        }
+    }
+}
+
+/// Quoted when it has spaces, the command line is split on them.
+fn quoted(path: &Path) -> String {
+    let path = path.display().to_string();
+    match path.contains(char::is_whitespace) {
+        true => format!("\"{path}\""),
+        false => path,
+    }
+}
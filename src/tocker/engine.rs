@@ -0,0 +1,2504 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    env,
+    fs::{self, File},
+    future::Future,
+    io::{Error, ErrorKind, Write},
+    path::{Path, PathBuf},
+    process::{self, ExitStatus, Stdio},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use bollard::{
+    models::{
+        ConfigSpec, ContainerSummary, ContainerUpdateBody, RestartPolicy, RestartPolicyNameEnum,
+        SecretSpec, Service, ServiceSpec, SystemDataUsageResponse, Volume, VolumeCreateRequest,
+        VolumeUsageData,
+    },
+    query_parameters::{
+        CreateImageOptionsBuilder, DataUsageOptions, DataUsageOptionsBuilder, EventsOptionsBuilder,
+        InspectContainerOptions, InspectServiceOptions, KillContainerOptionsBuilder,
+        ListConfigsOptions, ListContainersOptions, ListContainersOptionsBuilder, ListImagesOptions,
+        ListImagesOptionsBuilder, ListNodesOptions, ListSecretsOptions, ListServicesOptionsBuilder,
+        ListTasksOptionsBuilder, ListVolumesOptions, ListVolumesOptionsBuilder, LogsOptionsBuilder,
+        PruneBuildOptions, PruneContainersOptions, PruneImagesOptions, PruneNetworksOptions,
+        PruneVolumesOptions, RemoveContainerOptions, RemoveImageOptions, RemoveVolumeOptions,
+        RenameContainerOptionsBuilder, RestartContainerOptions, SearchImagesOptionsBuilder,
+        StartContainerOptions, StatsOptionsBuilder, StopContainerOptions, TagImageOptionsBuilder,
+        TopOptions, UpdateServiceOptionsBuilder,
+    },
+    Docker, API_DEFAULT_VERSION,
+};
+use futures_util::{future::join_all, Stream, StreamExt};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tracing::info;
+
+use super::{
+    cli,
+    compose::{self, PROJECT_LABEL},
+    config::TlsConfig,
+    layers::{self, Layer},
+    listing::{align, clock, human_size, short_id, Listing},
+    progress::{Checklist, LayerProgress, Step},
+    registry,
+    stack::{self, NAMESPACE_LABEL},
+    stats,
+    worker::{Events, Progress},
+    ComposeCommand, DockerCommand, DockerKind, DockerPrompt, LogOptions, Output, Report, Secret,
+    VolumeFile, Watch,
+};
+
+const DEFAULT_HOST: &str = "unix:///var/run/docker.sock";
+/// Seconds a request to the daemon may take, bollard's own default.
+const TIMEOUT: u64 = 120;
+/// How long a host of the fleet gets to list its containers.
+const FLEET_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long the daemon gets to answer a ping, a stalled one counts as gone.
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+/// The newest lines of a running command's output shown, all of them come with its reply.
+const PROGRESS_TAIL: usize = 200;
+/// How often a running command's output is shown again at most.
+const PROGRESS_RATE: Duration = Duration::from_millis(100);
+/// Same as the docker CLI.
+const SEARCH_LIMIT: i32 = 25;
+/// the engine keeps the last five probes, fewer of them are shown if it ever keeps more
+const HEALTH_PROBES: usize = 5;
+/// The container lifecycle, what an orchestrator does shows up as these.
+const EVENTS: [&str; 9] = [
+    "create",
+    "start",
+    "restart",
+    "pause",
+    "unpause",
+    "stop",
+    "die",
+    "destroy",
+    "health_status",
+];
+
+/// Talks to the docker daemon through its socket (honors `DOCKER_HOST`).
+/// Cheap to clone, so every spawned job gets its own handle.
+#[derive(Clone)]
+pub struct Engine {
+    docker: Docker,
+    host: String,
+    /// the daemons of the fleet tab by name, those that couldn't be reached with why
+    fleet: Arc<Vec<(String, Result<Docker, String>)>>,
+    /// container listings come with the size of each container, the daemon takes a
+    /// while to count them
+    sizes: bool,
+}
+
+fn to_json<T: serde::Serialize>(inspected: T) -> Result<serde_json::Value, Error> {
+    serde_json::to_value(inspected).map_err(Error::other)
+}
+
+/// With its causes, e.g. why a TLS handshake failed is only told by the innermost one.
+fn engine_err(err: bollard::errors::Error) -> Error {
+    let mut message = err.to_string();
+    let mut cause = std::error::Error::source(&err);
+    while let Some(inner) = cause {
+        let text = inner.to_string();
+        if !message.contains(&text) {
+            message.push_str(&format!(": {text}"));
+        }
+        cause = inner.source();
+    }
+    Error::other(message)
+}
+
+/// Runs a docker CLI command, handing over its output line by line as it's printed.
+async fn follow<F>(command: std::process::Command, mut each: F) -> Result<ExitStatus, Error>
+where
+    F: FnMut(String),
+{
+    info!(command = ?command, "running");
+    let mut child = tokio::process::Command::from(command)
+        .kill_on_drop(true)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let piped = || Error::other("The output is not piped");
+    let mut stdout = BufReader::new(child.stdout.take().ok_or_else(piped)?).lines();
+    let mut stderr = BufReader::new(child.stderr.take().ok_or_else(piped)?).lines();
+    let (mut stdout_open, mut stderr_open) = (true, true);
+    while stdout_open || stderr_open {
+        let line = tokio::select! {
+            line = stdout.next_line(), if stdout_open => line?.or_else(|| {
+                stdout_open = false;
+                None
+            }),
+            line = stderr.next_line(), if stderr_open => line?.or_else(|| {
+                stderr_open = false;
+                None
+            }),
+        };
+        if let Some(line) = line {
+            each(line);
+        }
+    }
+    let status = child.wait().await?;
+    info!(%status, "exited");
+    Ok(status)
+}
+
+/// Shows a running command's output, its newest lines and no more often than
+/// `PROGRESS_RATE`, rather than every line so far after each one.
+struct Tail<'a> {
+    progress: &'a mut Progress,
+    shown: Option<Instant>,
+}
+
+impl<'a> Tail<'a> {
+    fn new(progress: &'a mut Progress) -> Tail<'a> {
+        Tail {
+            progress,
+            shown: None,
+        }
+    }
+
+    fn show(&mut self, log: &[String]) {
+        if self
+            .shown
+            .is_some_and(|shown| shown.elapsed() < PROGRESS_RATE)
+        {
+            return;
+        }
+        self.shown = Some(Instant::now());
+        (self.progress)(log[log.len().saturating_sub(PROGRESS_TAIL)..].to_vec());
+    }
+}
+
+/// `log` ending with how the command went, a failure unless it exited with 0.
+fn summed(mut log: Vec<String>, status: ExitStatus, summary: String) -> Report {
+    log.push(summary.clone());
+    Report {
+        lines: log,
+        failures: match status.success() {
+            true => vec![],
+            false => vec![summary],
+        },
+    }
+}
+
+/// What `docker update` would change from `memory=`, `cpus=` and `restart=` settings.
+fn update_body(settings: &[&str]) -> Result<ContainerUpdateBody, Error> {
+    let invalid = |message: String| Error::new(ErrorKind::InvalidInput, message);
+    let mut body = ContainerUpdateBody::default();
+    for setting in settings {
+        let (name, value) = setting.split_once('=').unwrap_or((setting, ""));
+        match name {
+            "memory" => {
+                body.memory = Some(bytes(value).ok_or_else(|| {
+                    invalid(format!(
+                        "Memory should be like '512m' or '2g', not '{value}'"
+                    ))
+                })?)
+            }
+            "cpus" => {
+                let cpus: f64 = value
+                    .parse()
+                    .map_err(|_| invalid(format!("CPUs should be a number, not '{value}'")))?;
+                body.nano_cpus = Some((cpus * 1e9) as i64);
+            }
+            "restart" => {
+                let (policy, retries) = value.split_once(':').unwrap_or((value, ""));
+                body.restart_policy = Some(RestartPolicy {
+                    name: Some(
+                        policy
+                            .parse()
+                            .map_err(|_| invalid(format!("Unknown restart policy '{policy}'")))?,
+                    ),
+                    maximum_retry_count: retries.parse().ok(),
+                });
+            }
+            _ => return Err(invalid(format!("Unknown setting '{name}'"))),
+        }
+    }
+    if settings.is_empty() {
+        return Err(invalid(String::from("Nothing to update")));
+    }
+    Ok(body)
+}
+
+/// Reads like `docker update --restart` takes it, e.g. `on-failure:3`.
+fn restart_policy(policy: &RestartPolicy) -> String {
+    let name = match policy.name {
+        Some(RestartPolicyNameEnum::EMPTY) | None => String::from("no"),
+        Some(name) => name.to_string(),
+    };
+    match policy.maximum_retry_count {
+        Some(retries) if retries > 0 => format!("{name}:{retries}"),
+        _ => name,
+    }
+}
+
+/// Writes what the daemon streams into a file at `path`, reporting `doing` with the size
+/// written so far. Returns the size, half a file isn't left behind when it fails.
+async fn write_archive<S, T>(
+    stream: S,
+    path: &Path,
+    doing: &str,
+    progress: &mut Progress,
+) -> Result<usize, Error>
+where
+    S: Stream<Item = Result<T, bollard::errors::Error>>,
+    T: AsRef<[u8]>,
+{
+    let mut file = File::create(path)?;
+    futures_util::pin_mut!(stream);
+    let mut written = 0;
+    while let Some(chunk) = stream.next().await {
+        let written_chunk = chunk
+            .map_err(engine_err)
+            .and_then(|chunk| file.write_all(chunk.as_ref()).map(|_| chunk.as_ref().len()));
+        let length = match written_chunk {
+            Ok(length) => length,
+            Err(err) => {
+                fs::remove_file(path).ok();
+                return Err(err);
+            }
+        };
+        // once a megabyte, not for every chunk
+        if (written + length) >> 20 != written >> 20 {
+            let saved = human_size((written + length) as i64);
+            progress(vec![format!("{doing}… {saved}")]);
+        }
+        written += length;
+    }
+    Ok(written)
+}
+
+/// `path` with a leading `~/` taken as the home directory, like a shell would.
+fn local_path(path: &str) -> PathBuf {
+    match path.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// `key=value` pairs separated by commas. A part without `=` belongs to the value before
+/// it, e.g. `o=addr=10.0.0.1,rw` is the single option `o`.
+fn pairs(list: &str) -> Result<HashMap<String, String>, Error> {
+    let mut parts: Vec<String> = vec![];
+    for part in list.split(',').filter(|part| !part.is_empty()) {
+        match (part.contains('='), parts.last_mut()) {
+            (false, Some(last)) => {
+                last.push(',');
+                last.push_str(part);
+            }
+            _ => parts.push(String::from(part)),
+        }
+    }
+    parts
+        .into_iter()
+        .map(|part| match part.split_once('=') {
+            Some((key, value)) => Ok((String::from(key), String::from(value))),
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("'{part}' should be key=value"),
+            )),
+        })
+        .collect()
+}
+
+/// A size like the docker CLI takes them, e.g. `512m`, `1.5g` or plain bytes.
+fn bytes(size: &str) -> Option<i64> {
+    let size = size.to_lowercase();
+    let size = size.strip_suffix('b').unwrap_or(&size);
+    let (number, unit) = match size.char_indices().last()? {
+        (index, unit @ ('k' | 'm' | 'g' | 't')) => (&size[..index], unit),
+        _ => (size, 'b'),
+    };
+    let power = ['b', 'k', 'm', 'g', 't'].iter().position(|u| *u == unit)?;
+    let number: f64 = number.parse().ok()?;
+    Some((number * 1024f64.powi(power as i32)) as i64)
+}
+
+/// The unix time of `ago` before now, `0` for logs from their start.
+fn since(ago: Option<Duration>) -> i32 {
+    ago.and_then(|ago| SystemTime::now().checked_sub(ago))
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |time| time.as_secs() as i32)
+}
+
+/// `user@host[:port]` of an `ssh://` endpoint.
+fn check_ssh(destination: &str) -> Result<(), Error> {
+    let host = destination
+        .rsplit_once('@')
+        .map_or(destination, |(_, host)| host);
+    match host.is_empty() || host.starts_with(':') {
+        true => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("No host in ssh://{destination}, e.g. ssh://me@vps.example.com"),
+        )),
+        false => Ok(()),
+    }
+}
+
+/// `ssh://` endpoints are reached with the ssh client on the PATH, looked for on every
+/// ping so installing it is picked up on the next retry.
+fn ssh_client() -> Result<(), Error> {
+    let found = env::var_os("PATH").is_some_and(|paths| {
+        env::split_paths(&paths)
+            .any(|dir| dir.join("ssh").is_file() || dir.join("ssh.exe").is_file())
+    });
+    match found {
+        true => Ok(()),
+        false => Err(Error::new(
+            ErrorKind::NotFound,
+            "ssh:// daemons are reached through the ssh client, which isn't on the PATH",
+        )),
+    }
+}
+
+/// Where the ports a daemon publishes are reached, the remote host itself for `ssh://`
+/// and `tcp://` endpoints.
+fn published_host(endpoint: &str) -> &str {
+    let Some((_, address)) = endpoint.split_once("://") else {
+        return "localhost";
+    };
+    if endpoint.starts_with("unix://") || endpoint.starts_with("npipe://") {
+        return "localhost";
+    }
+    let address = address.rsplit_once('@').map_or(address, |(_, host)| host);
+    let address = address.split('/').next().unwrap_or(address);
+    match address.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => address,
+    }
+}
+
+/// The registry an image reference points to, Docker Hub unless it starts with a host.
+fn registry(reference: &str) -> &str {
+    match reference.split_once('/') {
+        Some((host, _)) if host.contains(['.', ':']) || host == "localhost" => host,
+        _ => "docker.io",
+    }
+}
+
+impl Engine {
+    /// Must be called from within the tokio runtime the engine will be used on.
+    /// TLS is used with the certs of `tls`, or when `DOCKER_TLS_VERIFY` is set with the
+    /// certs in `DOCKER_CERT_PATH`.
+    pub fn new(tls: Option<&TlsConfig>) -> Result<Engine, Error> {
+        let host = env::var("DOCKER_HOST").unwrap_or(String::from(DEFAULT_HOST));
+        Engine::connect(&host, tls)
+    }
+
+    /// Like `new`, against the daemon at `endpoint`, e.g. the one of a docker context.
+    /// `ssh://user@host` is tunneled through the ssh client like the docker CLI does, the
+    /// keys and options of `~/.ssh/config` apply.
+    pub fn connect(endpoint: &str, tls: Option<&TlsConfig>) -> Result<Engine, Error> {
+        if let Some(destination) = endpoint.strip_prefix("ssh://") {
+            check_ssh(destination)?;
+        }
+        let docker = match tls {
+            Some(tls) if endpoint.starts_with("tcp://") || endpoint.starts_with("https://") => {
+                let [ca, cert, key] = tls.files()?;
+                Docker::connect_with_ssl(endpoint, &key, &cert, &ca, TIMEOUT, API_DEFAULT_VERSION)
+            }
+            _ => Docker::connect_with_host(endpoint),
+        }
+        .map_err(engine_err)?;
+        Ok(Engine {
+            docker,
+            host: String::from(endpoint),
+            fleet: Arc::default(),
+            sizes: false,
+        })
+    }
+
+    /// Lists the containers of `hosts` in the fleet tab too, an endpoint that can't be
+    /// used shows up there instead of keeping tocker from starting.
+    pub fn with_fleet(self, hosts: &BTreeMap<String, String>, tls: Option<&TlsConfig>) -> Engine {
+        let fleet = hosts
+            .iter()
+            .map(|(name, endpoint)| {
+                let docker = Engine::connect(endpoint, tls)
+                    .and_then(|engine| match endpoint.starts_with("ssh://") {
+                        true => ssh_client().map(|_| engine),
+                        false => Ok(engine),
+                    })
+                    .map(|engine| engine.docker)
+                    .map_err(|err| err.to_string());
+                (name.clone(), docker)
+            })
+            .collect();
+        Engine {
+            fleet: Arc::new(fleet),
+            ..self
+        }
+    }
+
+    /// Lists the containers with their size, like `docker ps --size`, when `sizes`.
+    pub fn with_sizes(self, sizes: bool) -> Engine {
+        Engine { sizes, ..self }
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub async fn ping(&self) -> Result<(), Error> {
+        if self.host.starts_with("ssh://") {
+            ssh_client()?;
+        }
+        let pinged = self.docker.ping().await.map(|_| ());
+        match self.host.starts_with("ssh://") {
+            // the tunnel runs `docker system dial-stdio` on the remote side
+            true => pinged.map_err(|err| {
+                Error::other(format!(
+                    "{err}, the ssh user needs docker on its PATH and access to the daemon"
+                ))
+            }),
+            false => pinged.map_err(engine_err),
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        cmd: DockerPrompt,
+        progress: &mut Progress,
+    ) -> Result<Output, Error> {
+        match cmd.command {
+            DockerCommand::LS => Ok(Output::Table(self.list(&cmd.kind).await?.table())),
+            DockerCommand::RECLAIMABLE => {
+                Ok(Output::Table(self.reclaimable(&cmd.kind).await?.table()))
+            }
+            DockerCommand::SEARCH => Ok(Output::Table(self.search(&cmd.target).await?.table())),
+            DockerCommand::CREATE => {
+                let targets: Vec<&str> = cmd.target.split_whitespace().collect();
+                match cmd.kind {
+                    DockerKind::Secret => self.create_secret(&targets, cmd.secret.as_ref()).await?,
+                    DockerKind::Config => self.create_config(&targets).await?,
+                    _ => self.create_volume(&targets).await?,
+                };
+                Ok(Output::Table(self.list(&cmd.kind).await?.table()))
+            }
+            DockerCommand::BROWSE => match cmd.target.split_whitespace().collect::<Vec<&str>>()[..]
+            {
+                [volume] => Ok(Output::Files(self.browse(volume, progress).await?)),
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Volumes are browsed one at a time",
+                )),
+            },
+            DockerCommand::LAYERS => match cmd.target.split_whitespace().collect::<Vec<&str>>()[..]
+            {
+                [image] => Ok(Output::Layers(self.layers(image, progress).await?)),
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Layers are browsed one image at a time",
+                )),
+            },
+            _ => self.run(cmd, progress).await.map(Output::Ran),
+        }
+    }
+
+    async fn run(&self, cmd: DockerPrompt, progress: &mut Progress) -> Result<Report, Error> {
+        if cmd.kind == DockerKind::Fleet
+            && !matches!(cmd.command, DockerCommand::LS | DockerCommand::INSPECT)
+        {
+            return Ok(self.across_fleet(cmd).await);
+        }
+        let targets: Vec<&str> = cmd.target.split_whitespace().collect();
+        match cmd.command {
+            DockerCommand::LS => Ok(self.list(&cmd.kind).await?.table().lines().into()),
+            DockerCommand::RM => {
+                let kind = &cmd.kind;
+                let each = |target| async move { self.remove(kind, &[target]).await };
+                Ok(self.batch("Removing", &targets, progress, each).await)
+            }
+            DockerCommand::STOP => {
+                let each = |target| async move { self.stop(&[target]).await };
+                Ok(self.batch("Stopping", &targets, progress, each).await)
+            }
+            DockerCommand::TAG => match targets[..] {
+                [source, target] => self
+                    .tag(source, target)
+                    .await
+                    .map(|_| Report::from(vec![format!("Tagged {source} as {target}")])),
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Tag needs a source image and a target reference",
+                )),
+            },
+            DockerCommand::EXEC => Err(Error::new(
+                ErrorKind::Unsupported,
+                "Exec needs the terminal and runs in the foreground",
+            )),
+            DockerCommand::EDIT => Err(Error::new(
+                ErrorKind::Unsupported,
+                "The editor needs the terminal and runs in the foreground",
+            )),
+            DockerCommand::PIN => Err(Error::new(
+                ErrorKind::Unsupported,
+                "Pinned logs are followed in their own pane",
+            )),
+            DockerCommand::LOGS if cmd.kind == DockerKind::Container => Err(Error::new(
+                ErrorKind::Unsupported,
+                "Container logs are followed in the log viewer",
+            )),
+            DockerCommand::STATS => self.stats().await.map(Report::from),
+            DockerCommand::INSPECT => self.inspect(&cmd.kind, &targets).await.map(Report::from),
+            DockerCommand::DF => Ok(self.usage(&cmd.kind).await?.table().lines().into()),
+            DockerCommand::RECLAIMABLE => {
+                Ok(self.reclaimable(&cmd.kind).await?.table().lines().into())
+            }
+            DockerCommand::PRUNE => self.prune(&cmd.kind).await.map(Report::from),
+            DockerCommand::PAUSE => {
+                let each = |target| async move { self.pause(&[target]).await };
+                Ok(self.batch("Pausing", &targets, progress, each).await)
+            }
+            DockerCommand::OPEN => Ok(self.open(&targets).await),
+            DockerCommand::UNPAUSE => {
+                let each = |target| async move { self.unpause(&[target]).await };
+                Ok(self.batch("Unpausing", &targets, progress, each).await)
+            }
+            DockerCommand::UP if cmd.kind == DockerKind::Container => {
+                let each = |target| async move { self.start(&[target]).await };
+                Ok(self.batch("Starting", &targets, progress, each).await)
+            }
+            DockerCommand::RESTART if cmd.kind == DockerKind::Container => {
+                let each = |target| async move { self.restart(&[target]).await };
+                Ok(self.batch("Restarting", &targets, progress, each).await)
+            }
+            DockerCommand::UP
+            | DockerCommand::DOWN
+            | DockerCommand::RESTART
+            | DockerCommand::LOGS => {
+                let command = ComposeCommand::try_from(cmd.command)?;
+                self.compose(&targets, command).await
+            }
+            DockerCommand::SEARCH => Ok(self.search(&cmd.target).await?.table().lines().into()),
+            DockerCommand::HISTORY => self.history(&targets).await.map(Report::from),
+            DockerCommand::HEALTH => self.health(&targets).await.map(Report::from),
+            DockerCommand::ENV => self.env(&targets).await.map(Report::from),
+            DockerCommand::DANGLING => Ok(self.remove_dangling(&cmd.kind, &targets).await),
+            DockerCommand::CREATE if cmd.kind == DockerKind::Secret => {
+                let id = self.create_secret(&targets, cmd.secret.as_ref()).await?;
+                Ok(vec![format!("Created secret {id}")].into())
+            }
+            DockerCommand::CREATE if cmd.kind == DockerKind::Config => {
+                let id = self.create_config(&targets).await?;
+                Ok(vec![format!("Created config {id}")].into())
+            }
+            DockerCommand::CREATE => {
+                let name = self.create_volume(&targets).await?;
+                Ok(vec![format!("Created volume {name}")].into())
+            }
+            DockerCommand::BROWSE => {
+                let mut lines = vec![];
+                for volume in targets {
+                    let files = self.browse(volume, progress).await?;
+                    lines.extend(files.iter().map(VolumeFile::line));
+                }
+                Ok(lines.into())
+            }
+            // only the image of a service is updated, its tasks are replaced one by one
+            DockerCommand::UPDATE if cmd.kind == DockerKind::Service => {
+                let (settings, services): (Vec<&str>, Vec<&str>) =
+                    targets.iter().partition(|target| target.contains('='));
+                let image = settings
+                    .iter()
+                    .find_map(|setting| setting.strip_prefix("image="))
+                    .ok_or(Error::new(
+                        ErrorKind::InvalidInput,
+                        "Updating services needs the image to run, e.g. 'image=nginx:1.27'",
+                    ))?;
+                let each = |target| async move { self.update_image(&[target], image).await };
+                Ok(self.batch("Updating", &services, progress, each).await)
+            }
+            // settings are `name=value`, the rest are the containers they apply to
+            DockerCommand::UPDATE => {
+                let (settings, containers): (Vec<&str>, Vec<&str>) =
+                    targets.iter().partition(|target| target.contains('='));
+                let body = update_body(&settings)?;
+                Ok(self.update(&containers, body).await)
+            }
+            // the policy comes after the containers it's set on, like `docker update --restart`
+            DockerCommand::POLICY => match targets.split_last() {
+                Some((policy, containers)) if !containers.is_empty() => {
+                    let body = update_body(&[&format!("restart={policy}")])?;
+                    Ok(self.update(containers, body).await)
+                }
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Changing the restart policy needs the containers and a policy",
+                )),
+            },
+            // the signal comes after the containers it's sent to
+            DockerCommand::KILL => match targets.split_last() {
+                Some((signal, containers)) if !containers.is_empty() => {
+                    Ok(self.kill(containers, signal).await)
+                }
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Kill needs the containers and a signal",
+                )),
+            },
+            DockerCommand::PS if cmd.kind == DockerKind::Stack => {
+                let services = self.stack_services(&targets).await?;
+                let services: Vec<&str> = services.iter().map(String::as_str).collect();
+                self.tasks(&services).await.map(Report::from)
+            }
+            DockerCommand::PS => self.tasks(&targets).await.map(Report::from),
+            DockerCommand::DEPLOY => match targets[..] {
+                [file, stack] => self.deploy(file, stack, progress).await,
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Deploy needs a compose file and the name of the stack",
+                )),
+            },
+            // the replicas come after the services they're set on
+            DockerCommand::SCALE => match targets.split_last() {
+                Some((replicas, services)) if !services.is_empty() => {
+                    let replicas: u64 = replicas.parse().map_err(|_| {
+                        Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("Replicas should be a number, not '{replicas}'"),
+                        )
+                    })?;
+                    let each = |target| async move { self.scale(&[target], replicas).await };
+                    Ok(self.batch("Scaling", services, progress, each).await)
+                }
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Scale needs the services and a number of replicas",
+                )),
+            },
+            DockerCommand::RENAME => match targets[..] {
+                [container, name] => self
+                    .docker
+                    .rename_container(
+                        container,
+                        RenameContainerOptionsBuilder::new().name(name).build(),
+                    )
+                    .await
+                    .map(|_| Report::from(vec![format!("Renamed {container} to {name}")]))
+                    .map_err(engine_err),
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Rename needs one container and its new name",
+                )),
+            },
+            DockerCommand::TOP => {
+                let mut lines = vec![];
+                for container in targets {
+                    lines.push(container.to_string());
+                    lines.extend(self.top(container).await?);
+                    lines.push(String::new());
+                }
+                Ok(lines.into())
+            }
+            DockerCommand::LAYERS => {
+                let mut lines = vec![];
+                for image in targets {
+                    let layers = self.layers(image, progress).await?;
+                    lines.extend(layers.iter().flat_map(Layer::lines));
+                }
+                Ok(lines.into())
+            }
+            // several at once when picked from search results
+            DockerCommand::PULL => match targets[..] {
+                [] => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Pull needs an image reference",
+                )),
+                _ => {
+                    let mut lines = vec![];
+                    for reference in targets {
+                        lines.extend(self.pull(reference, progress).await?);
+                    }
+                    Ok(lines.into())
+                }
+            },
+            // the archive comes after the images saved into it
+            DockerCommand::SAVE => match targets.split_last() {
+                Some((path, images)) if !images.is_empty() => {
+                    let path = local_path(path);
+                    let size = self.save(images, &path, progress).await?;
+                    Ok(Report::from(vec![format!(
+                        "Saved {} to {} ({})",
+                        images.join(", "),
+                        path.display(),
+                        human_size(size as i64)
+                    )]))
+                }
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Save needs the images and the path of the archive",
+                )),
+            },
+            // the archive comes after the container exported into it
+            DockerCommand::EXPORT => match targets[..] {
+                [container, path] => {
+                    let path = local_path(path);
+                    let size = self.export(container, &path, progress).await?;
+                    Ok(Report::from(vec![format!(
+                        "Exported {container} to {} ({})",
+                        path.display(),
+                        human_size(size as i64)
+                    )]))
+                }
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Export needs one container and the path of the archive",
+                )),
+            },
+            DockerCommand::IMPORT => match targets[..] {
+                [path] => self.import(path, None, progress).await,
+                [path, reference] => self.import(path, Some(reference), progress).await,
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Import needs the path of an archive and optionally the image reference",
+                )),
+            },
+            DockerCommand::LOAD => match targets[..] {
+                [path] => self.load(path, progress).await,
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Load needs the path of one archive",
+                )),
+            },
+            DockerCommand::CP => match targets[..] {
+                [source, destination] => self.cp(source, destination, progress).await,
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Copy needs a source and a destination",
+                )),
+            },
+            DockerCommand::LOGIN => match (&targets[..], &cmd.secret) {
+                ([server, username], Some(secret)) => self.login(server, username, &secret.0).await,
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Login needs a registry, a username and a password or token",
+                )),
+            },
+            DockerCommand::LOGOUT => self.logout(&targets).await,
+            DockerCommand::PUSH => {
+                let mut output = Report::default();
+                for reference in targets {
+                    output.extend(self.push(reference, progress).await?);
+                }
+                Ok(output)
+            }
+            DockerCommand::LINE => self.line(&cmd.target, progress).await,
+            DockerCommand::CUSTOM(_) => self.custom(&cmd.target, progress).await,
+            DockerCommand::BUILD => match targets[..] {
+                [context] => self.build(context, None, progress).await,
+                [context, tag] => self.build(context, Some(tag), progress).await,
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Build needs a context path and optionally a tag",
+                )),
+            },
+        }
+    }
+
+    /// Same json as `docker inspect`, pretty-printed one line per entry.
+    /// A project is inspected as the containers it's made of.
+    pub async fn inspect(&self, kind: &DockerKind, targets: &[&str]) -> Result<Vec<String>, Error> {
+        let mut inspected = vec![];
+        for target in targets {
+            match kind {
+                DockerKind::Image => inspected.push(to_json(
+                    self.docker
+                        .inspect_image(target)
+                        .await
+                        .map_err(engine_err)?,
+                )?),
+                DockerKind::Container => inspected.push(to_json(
+                    self.docker
+                        .inspect_container(target, None::<InspectContainerOptions>)
+                        .await
+                        .map_err(engine_err)?,
+                )?),
+                DockerKind::Volume => inspected.push(to_json(
+                    self.docker
+                        .inspect_volume(target)
+                        .await
+                        .map_err(engine_err)?,
+                )?),
+                DockerKind::Project => {
+                    let containers = self
+                        .compose_containers(Some(target))
+                        .await
+                        .map_err(engine_err)?;
+                    for container in containers.iter().filter_map(|c| c.id.as_deref()) {
+                        inspected.push(to_json(
+                            self.docker
+                                .inspect_container(container, None::<InspectContainerOptions>)
+                                .await
+                                .map_err(engine_err)?,
+                        )?);
+                    }
+                }
+                DockerKind::System => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "There is nothing to inspect for the system",
+                    ))
+                }
+                DockerKind::Registry => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "Registries are only listed, there is nothing to inspect",
+                    ))
+                }
+                DockerKind::Stack => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "Stacks are inspected by their services",
+                    ))
+                }
+                DockerKind::Secret => inspected.push(to_json(
+                    self.docker
+                        .inspect_secret(target)
+                        .await
+                        .map_err(engine_err)?,
+                )?),
+                DockerKind::Config => inspected.push(to_json(
+                    self.docker
+                        .inspect_config(target)
+                        .await
+                        .map_err(engine_err)?,
+                )?),
+                DockerKind::Service => inspected.push(to_json(
+                    self.docker
+                        .inspect_service(target, None::<InspectServiceOptions>)
+                        .await
+                        .map_err(engine_err)?,
+                )?),
+                DockerKind::Fleet => {
+                    let (docker, container) = self.fleet_host(target)?;
+                    inspected.push(to_json(
+                        docker
+                            .inspect_container(container, None::<InspectContainerOptions>)
+                            .await
+                            .map_err(engine_err)?,
+                    )?)
+                }
+            }
+        }
+        let pretty = serde_json::to_string_pretty(&inspected).map_err(Error::other)?;
+        Ok(pretty.lines().map(String::from).collect())
+    }
+
+    /// Runs `docker build`, reporting the log as it's printed and a summary once it exits.
+    pub async fn build(
+        &self,
+        context: &str,
+        tag: Option<&str>,
+        progress: &mut Progress,
+    ) -> Result<Report, Error> {
+        let mut log = vec![];
+        let mut tail = Tail::new(progress);
+        let status = follow(cli::build(context, tag), |line| {
+            log.push(line);
+            tail.show(&log);
+        })
+        .await?;
+        log.push(String::new());
+        let summary = match (status.success(), tag) {
+            (true, Some(tag)) => format!("Build succeeded, tagged {tag}"),
+            (true, None) => String::from("Build succeeded"),
+            (false, _) => format!("Build failed ({status})"),
+        };
+        Ok(summed(log, status, summary))
+    }
+
+    /// Runs a command line through the docker CLI, its output shown as it comes.
+    pub async fn line(&self, line: &str, progress: &mut Progress) -> Result<Report, Error> {
+        let command = cli::line(line).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "The command line has to start with 'docker'",
+            )
+        })?;
+        let mut log = vec![];
+        let mut tail = Tail::new(progress);
+        let status = follow(command, |line| {
+            log.push(line);
+            tail.show(&log);
+        })
+        .await?;
+        let mut output = Report::from(log);
+        if !status.success() {
+            output.fail(format!("docker exited with {status}"));
+        }
+        Ok(output)
+    }
+
+    /// Runs the lines of a custom command one after the other, with any program.
+    pub async fn custom(&self, lines: &str, progress: &mut Progress) -> Result<Report, Error> {
+        let mut log = vec![];
+        let mut failures = vec![];
+        let mut tail = Tail::new(progress);
+        for line in lines.lines() {
+            let command = cli::custom(line).ok_or_else(|| {
+                Error::new(ErrorKind::InvalidInput, "The custom command is empty")
+            })?;
+            let status = follow(command, |line| {
+                log.push(line);
+                tail.show(&log);
+            })
+            .await?;
+            if !status.success() {
+                let why = format!("{line} exited with {status}");
+                log.push(format!("Error: {why}"));
+                failures.push(why);
+            }
+        }
+        Ok(Report {
+            lines: log,
+            failures,
+        })
+    }
+
+    /// Runs `docker login`, the secret is handed over on stdin.
+    pub async fn login(&self, server: &str, username: &str, secret: &str) -> Result<Report, Error> {
+        let mut child = tokio::process::Command::from(cli::login(server, username))
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // closed once written, or docker keeps waiting for more
+            stdin.write_all(secret.as_bytes()).await?;
+        }
+        let output = child.wait_with_output().await?;
+        let mut lines: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .chain(String::from_utf8_lossy(&output.stderr).lines())
+            .map(String::from)
+            .collect();
+        lines.push(String::new());
+        let summary = match output.status.success() {
+            true => format!("Logged in to {server} as {username}"),
+            false => format!("Login to {server} failed ({})", output.status),
+        };
+        Ok(summed(lines, output.status, summary))
+    }
+
+    pub async fn logout(&self, targets: &[&str]) -> Result<Report, Error> {
+        let mut log = Report::default();
+        for server in targets {
+            let status = follow(cli::logout(server), |line| log.lines.push(line)).await?;
+            if !status.success() {
+                log.fail(format!("logout from {server} failed ({status})"));
+            }
+        }
+        Ok(log)
+    }
+
+    /// Runs `docker cp`, either side can be a `container:path`.
+    pub async fn cp(
+        &self,
+        source: &str,
+        destination: &str,
+        progress: &mut Progress,
+    ) -> Result<Report, Error> {
+        let mut log = vec![];
+        let mut tail = Tail::new(progress);
+        let status = follow(cli::cp(source, destination), |line| {
+            log.push(line);
+            tail.show(&log);
+        })
+        .await?;
+        log.push(String::new());
+        let summary = match status.success() {
+            true => format!("Copied {source} to {destination}"),
+            false => format!("Copy failed ({status})"),
+        };
+        Ok(summed(log, status, summary))
+    }
+
+    /// Creates a volume from `name=`, `driver=`, `options=` and `labels=` settings, the last
+    /// two comma separated `key=value` lists. Returns the name, docker picks one unless set.
+    pub async fn create_volume(&self, settings: &[&str]) -> Result<String, Error> {
+        let mut request = VolumeCreateRequest::default();
+        for setting in settings {
+            let (name, value) = setting.split_once('=').unwrap_or((setting, ""));
+            match name {
+                "name" => request.name = Some(String::from(value)),
+                "driver" => request.driver = Some(String::from(value)),
+                "options" => request.driver_opts = Some(pairs(value)?),
+                "labels" => request.labels = Some(pairs(value)?),
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Unknown setting '{name}'"),
+                    ))
+                }
+            }
+        }
+        self.docker
+            .create_volume(request)
+            .await
+            .map(|volume| volume.name)
+            .map_err(engine_err)
+    }
+
+    /// Creates a secret from `name=`, `file=` and `labels=` settings, its value read from
+    /// the file or else the one typed. Returns its id.
+    pub async fn create_secret(
+        &self,
+        settings: &[&str],
+        typed: Option<&Secret>,
+    ) -> Result<String, Error> {
+        let mut spec = SecretSpec::default();
+        let mut value = typed.map(|secret| secret.0.clone().into_bytes());
+        for setting in settings {
+            let (name, setting) = setting.split_once('=').unwrap_or((setting, ""));
+            match name {
+                "name" => spec.name = Some(String::from(setting)),
+                "file" => value = Some(fs::read(local_path(setting))?),
+                "labels" => spec.labels = Some(pairs(setting)?),
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Unknown setting '{name}'"),
+                    ))
+                }
+            }
+        }
+        if spec.name.is_none() {
+            return Err(Error::new(ErrorKind::InvalidInput, "A secret needs a name"));
+        }
+        let value = value.ok_or(Error::new(
+            ErrorKind::InvalidInput,
+            "A secret needs a file to read its value from, or the value typed",
+        ))?;
+        spec.data = Some(STANDARD.encode(value));
+        self.docker
+            .create_secret(spec)
+            .await
+            .map(|created| created.id)
+            .map_err(engine_err)
+    }
+
+    /// Creates a config from `name=`, `file=` and `labels=` settings, its data read from
+    /// the file. Returns its id.
+    pub async fn create_config(&self, settings: &[&str]) -> Result<String, Error> {
+        let mut spec = ConfigSpec::default();
+        for setting in settings {
+            let (name, setting) = setting.split_once('=').unwrap_or((setting, ""));
+            match name {
+                "name" => spec.name = Some(String::from(setting)),
+                "file" => spec.data = Some(STANDARD.encode(fs::read(local_path(setting))?)),
+                "labels" => spec.labels = Some(pairs(setting)?),
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Unknown setting '{name}'"),
+                    ))
+                }
+            }
+        }
+        if spec.name.is_none() || spec.data.is_none() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "A config needs a name and a file to read its data from",
+            ));
+        }
+        self.docker
+            .create_config(spec)
+            .await
+            .map(|created| created.id)
+            .map_err(engine_err)
+    }
+
+    /// Everything inside `volume`, listed by a throwaway container it's mounted in.
+    /// What else the container prints, e.g. alpine being pulled, shows as progress.
+    pub async fn browse(
+        &self,
+        volume: &str,
+        progress: &mut Progress,
+    ) -> Result<Vec<VolumeFile>, Error> {
+        let mut files = vec![];
+        let mut log = vec![];
+        let mut tail = Tail::new(progress);
+        let status = follow(cli::browse(volume), |line| match VolumeFile::parse(&line) {
+            Some(file) => files.push(file),
+            None => {
+                log.push(line);
+                tail.show(&log);
+            }
+        })
+        .await?;
+        // find fails on files it can't read, the rest is still listed
+        if !status.success() && files.is_empty() {
+            return Err(Error::other(format!(
+                "Browsing {volume} failed ({status}): {}",
+                log.join(" ")
+            )));
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    /// Runs `docker push`, so the credentials of `docker login` and its helpers are used,
+    /// reporting the state of every layer as it goes.
+    pub async fn push(&self, reference: &str, progress: &mut Progress) -> Result<Report, Error> {
+        let mut layers = LayerProgress::default();
+        let mut denied = false;
+        let status = follow(cli::push(reference), |line| {
+            denied |= ["unauthorized", "denied", "authentication required"]
+                .iter()
+                .any(|reason| line.contains(reason));
+            match line.split_once(": ") {
+                // layers print as `5f70bf18a086: Pushing`
+                Some((id, status))
+                    if id.len() == 12 && id.chars().all(|c| c.is_ascii_hexdigit()) =>
+                {
+                    layers.update(Some(id), Some(status), 0, 0)
+                }
+                _ => layers.update(None, Some(&line), 0, 0),
+            }
+            progress(layers.lines());
+        })
+        .await?;
+        let mut lines = layers.lines();
+        lines.push(String::new());
+        let summary = match (status.success(), denied) {
+            (true, _) => format!("Pushed {reference}"),
+            (false, true) => format!(
+                "Push denied, log in to {} with push access to {reference} first",
+                registry(reference)
+            ),
+            (false, false) => format!("Push failed ({status})"),
+        };
+        Ok(summed(lines, status, summary))
+    }
+
+    /// The layers of every image like `docker image history`, newest first.
+    pub async fn history(&self, targets: &[&str]) -> Result<Vec<String>, Error> {
+        let mut lines = vec![];
+        for target in targets {
+            let layers = self
+                .docker
+                .image_history(target)
+                .await
+                .map_err(engine_err)?;
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(target.to_string());
+            lines.extend(Listing::History(layers).table().lines());
+        }
+        Ok(lines)
+    }
+
+    /// The last probes of the healthcheck of every container, oldest first like inspect has
+    /// them, with what each one printed.
+    /// The environment the containers were started with, one variable per row so the
+    /// filter narrows it down. The container is told apart when there are several.
+    pub async fn env(&self, targets: &[&str]) -> Result<Vec<String>, Error> {
+        let several = targets.len() > 1;
+        let mut rows = vec![match several {
+            true => vec![
+                String::from("CONTAINER"),
+                String::from("KEY"),
+                String::from("VALUE"),
+            ],
+            false => vec![String::from("KEY"), String::from("VALUE")],
+        }];
+        for target in targets {
+            let inspected = self
+                .docker
+                .inspect_container(target, None::<InspectContainerOptions>)
+                .await
+                .map_err(engine_err)?;
+            let env = inspected.config.and_then(|config| config.env);
+            for variable in env.unwrap_or_default() {
+                let (key, value) = variable.split_once('=').unwrap_or((&variable, ""));
+                let mut row = vec![String::from(key), String::from(value)];
+                if several {
+                    row.insert(0, target.to_string());
+                }
+                rows.push(row);
+            }
+        }
+        Ok(align(rows))
+    }
+
+    pub async fn health(&self, targets: &[&str]) -> Result<Vec<String>, Error> {
+        let mut lines = vec![];
+        for target in targets {
+            let inspected = self
+                .docker
+                .inspect_container(target, None::<InspectContainerOptions>)
+                .await
+                .map_err(engine_err)?;
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            let Some(health) = inspected.state.and_then(|state| state.health) else {
+                lines.push(format!("{target} has no healthcheck"));
+                continue;
+            };
+            lines.push(format!(
+                "{target} is {}, {} failing in a row",
+                health
+                    .status
+                    .map(|status| status.to_string())
+                    .unwrap_or_default(),
+                health.failing_streak.unwrap_or_default()
+            ));
+            let probes = health.log.unwrap_or_default();
+            let skip = probes.len().saturating_sub(HEALTH_PROBES);
+            for probe in probes.into_iter().skip(skip) {
+                lines.push(format!(
+                    "{}  exit {}",
+                    probe.start.unwrap_or_default(),
+                    probe.exit_code.unwrap_or_default()
+                ));
+                lines.extend(
+                    probe
+                        .output
+                        .unwrap_or_default()
+                        .lines()
+                        .filter(|line| !line.trim().is_empty())
+                        .map(|line| format!("  {line}")),
+                );
+            }
+        }
+        Ok(lines)
+    }
+
+    /// The files every layer of `image` adds, changes or removes. The image is saved to a
+    /// temporary file to read them, like `docker save` would.
+    pub async fn layers(&self, image: &str, progress: &mut Progress) -> Result<Vec<Layer>, Error> {
+        let path = env::temp_dir().join(format!("tocker-{}.tar", process::id()));
+        let saved = self.save(&[image], &path, progress).await;
+        let layers = match saved {
+            Ok(_) => {
+                progress(vec![format!("Reading the layers of {image}…")]);
+                let read = path.clone();
+                tokio::task::spawn_blocking(move || layers::read(&read))
+                    .await
+                    .map_err(Error::other)
+                    .and_then(|layers| layers)
+            }
+            Err(err) => Err(err),
+        };
+        fs::remove_file(&path).ok();
+        layers
+    }
+
+    /// Writes the images into one archive at `path` like `docker save`, returns its size.
+    pub async fn save(
+        &self,
+        images: &[&str],
+        path: &Path,
+        progress: &mut Progress,
+    ) -> Result<usize, Error> {
+        let stream = self.docker.export_images(images);
+        let doing = format!("Saving {}", images.join(", "));
+        write_archive(stream, path, &doing, progress).await
+    }
+
+    /// Writes the filesystem of the container into an archive at `path` like
+    /// `docker export`, returns its size.
+    pub async fn export(
+        &self,
+        container: &str,
+        path: &Path,
+        progress: &mut Progress,
+    ) -> Result<usize, Error> {
+        let stream = self.docker.export_container(container);
+        let doing = format!("Exporting {container}");
+        write_archive(stream, path, &doing, progress).await
+    }
+
+    /// Runs `docker import`, a new image from the filesystem in the archive at `path`.
+    pub async fn import(
+        &self,
+        path: &str,
+        reference: Option<&str>,
+        progress: &mut Progress,
+    ) -> Result<Report, Error> {
+        let path = local_path(path);
+        let mut log = vec![format!("Importing {}…", path.display())];
+        let mut tail = Tail::new(progress);
+        tail.show(&log);
+        let status = follow(cli::import(&path.to_string_lossy(), reference), |line| {
+            log.push(line);
+            tail.show(&log);
+        })
+        .await?;
+        let summary = match (status.success(), reference) {
+            (true, Some(reference)) => format!("Imported as {reference}"),
+            (true, None) => String::from("Imported, the image is untagged"),
+            (false, _) => format!("Import failed ({status})"),
+        };
+        Ok(summed(log, status, summary))
+    }
+
+    /// Runs `docker load` on the archive at `path`, its output is what got loaded.
+    pub async fn load(&self, path: &str, progress: &mut Progress) -> Result<Report, Error> {
+        let path = local_path(path);
+        let mut log = vec![format!("Loading {}…", path.display())];
+        let mut tail = Tail::new(progress);
+        tail.show(&log);
+        let status = follow(cli::load(&path.to_string_lossy()), |line| {
+            log.push(line);
+            tail.show(&log);
+        })
+        .await?;
+        Ok(match status.success() {
+            true => Report::from(log),
+            false => summed(log, status, format!("Load failed ({status})")),
+        })
+    }
+
+    /// Searches Docker Hub like `docker search`, the daemon does the request.
+    pub async fn search(&self, term: &str) -> Result<Listing, Error> {
+        if term.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "Search needs a term"));
+        }
+        let options = SearchImagesOptionsBuilder::new()
+            .term(term)
+            .limit(SEARCH_LIMIT)
+            .build();
+        self.docker
+            .search_images(options)
+            .await
+            .map(Listing::Search)
+            .map_err(engine_err)
+    }
+
+    /// Pulls `reference` (`latest` unless tagged), reporting per-layer progress as it goes.
+    pub async fn pull(
+        &self,
+        reference: &str,
+        progress: &mut Progress,
+    ) -> Result<Vec<String>, Error> {
+        let options = match reference.rsplit_once(':') {
+            _ if reference.contains('@') => CreateImageOptionsBuilder::new().from_image(reference),
+            Some((image, tag)) if !tag.contains('/') => {
+                CreateImageOptionsBuilder::new().from_image(image).tag(tag)
+            }
+            _ => CreateImageOptionsBuilder::new()
+                .from_image(reference)
+                .tag("latest"),
+        }
+        .build();
+        let mut stream = Box::pin(self.docker.create_image(Some(options), None, None));
+        let mut layers = LayerProgress::default();
+        while let Some(info) = stream.next().await {
+            let info = info.map_err(engine_err)?;
+            if let Some(message) = info.error_detail.and_then(|detail| detail.message) {
+                return Err(Error::other(message));
+            }
+            let (current, total) = info
+                .progress_detail
+                .map(|detail| {
+                    (
+                        detail.current.unwrap_or_default(),
+                        detail.total.unwrap_or_default(),
+                    )
+                })
+                .unwrap_or_default();
+            layers.update(info.id.as_deref(), info.status.as_deref(), current, total);
+            progress(layers.lines());
+        }
+        Ok(layers.lines())
+    }
+
+    /// One refresh of a watched view.
+    pub async fn watch(&self, watch: Watch) -> Result<Output, Error> {
+        match watch {
+            Watch::Stats => self.stats().await.map(Output::Lines),
+            Watch::Listing(kind) => Ok(Output::Table(self.list(&kind).await?.table())),
+            Watch::Reclaimable(kind) => Ok(Output::Table(self.reclaimable(&kind).await?.table())),
+            Watch::Top(container) => self.top(&container).await.map(Output::Lines),
+            Watch::Ping => match tokio::time::timeout(PING_TIMEOUT, self.ping()).await {
+                Ok(pinged) => pinged.map(|_| Output::Lines(vec![])),
+                Err(_) => Err(Error::new(
+                    ErrorKind::TimedOut,
+                    format!("{} did not answer", self.host),
+                )),
+            },
+        }
+    }
+
+    /// Follows `docker events` for the container lifecycle, one line per event, until
+    /// the daemon closes the stream.
+    pub async fn events(&self, each: &mut Events) -> Result<(), Error> {
+        let filters = HashMap::from([("type", vec!["container"]), ("event", EVENTS.to_vec())]);
+        let options = EventsOptionsBuilder::new().filters(&filters).build();
+        let mut stream = self.docker.events(Some(options));
+        while let Some(event) = stream.next().await {
+            let event = event.map_err(engine_err)?;
+            let attributes = event
+                .actor
+                .as_ref()
+                .and_then(|actor| actor.attributes.clone())
+                .unwrap_or_default();
+            let name = attributes
+                .get("name")
+                .map(String::as_str)
+                .unwrap_or_default();
+            let image = attributes
+                .get("image")
+                .map(String::as_str)
+                .unwrap_or_default();
+            each(Ok(format!(
+                "{}  {:<8} {name} ({image})",
+                clock(event.time.unwrap_or_default()),
+                event.action.unwrap_or_default(),
+            )));
+        }
+        Ok(())
+    }
+
+    /// Follows the output of `container`, its last lines first, one line at a time until
+    /// the container stops.
+    pub async fn logs(
+        &self,
+        container: &str,
+        options: LogOptions,
+        each: &mut Events,
+    ) -> Result<(), Error> {
+        let options = LogsOptionsBuilder::new()
+            .follow(true)
+            .stdout(true)
+            .stderr(true)
+            .timestamps(options.timestamps)
+            .tail(&options.tail.to_string())
+            .since(since(options.since))
+            .build();
+        let mut stream = self.docker.logs(container, Some(options));
+        while let Some(output) = stream.next().await {
+            let output = output.map_err(engine_err)?;
+            String::from_utf8_lossy(&output.into_bytes())
+                .lines()
+                .for_each(|line| each(Ok(String::from(line))));
+        }
+        Ok(())
+    }
+
+    /// A `docker stats --no-stream` of every running container.
+    /// The processes running in `container`, like `docker top`.
+    pub async fn top(&self, container: &str) -> Result<Vec<String>, Error> {
+        let top = self
+            .docker
+            .top_processes(container, None::<TopOptions>)
+            .await
+            .map_err(engine_err)?;
+        let mut rows = vec![top.titles.unwrap_or_default()];
+        rows.extend(top.processes.unwrap_or_default());
+        Ok(align(rows))
+    }
+
+    pub async fn stats(&self) -> Result<Vec<String>, Error> {
+        let containers = self
+            .docker
+            .list_containers(None::<ListContainersOptions>)
+            .await
+            .map_err(engine_err)?;
+        let options = StatsOptionsBuilder::new().stream(false).build();
+        let stats = join_all(
+            containers
+                .iter()
+                .filter_map(|container| container.id.as_deref())
+                .map(|id| {
+                    let mut stream = Box::pin(self.docker.stats(id, Some(options.clone())));
+                    async move { stream.next().await }
+                }),
+        )
+        .await;
+        let stats: Vec<_> = stats.into_iter().flatten().filter_map(Result::ok).collect();
+        Ok(stats::lines(&stats))
+    }
+
+    pub async fn list(&self, kind: &DockerKind) -> Result<Listing, Error> {
+        match kind {
+            // from the docker config, the daemon doesn't know about logins
+            DockerKind::Registry => return registry::registries().await.map(Listing::Registries),
+            DockerKind::Image => self
+                .docker
+                .list_images(None::<ListImagesOptions>)
+                .await
+                .map(Listing::Images),
+            // stopped containers too, so they can be started again from the list
+            DockerKind::Container => {
+                let options = ListContainersOptionsBuilder::new()
+                    .all(true)
+                    .size(self.sizes)
+                    .build();
+                return self.containers(options).await;
+            }
+            DockerKind::Volume => self
+                .docker
+                .list_volumes(None::<ListVolumesOptions>)
+                .await
+                .map(|response| Listing::Volumes(response.volumes.unwrap_or_default())),
+            DockerKind::Project => self
+                .compose_containers(None)
+                .await
+                .map(|containers| Listing::Projects(compose::projects(&containers))),
+            DockerKind::System => self
+                .docker
+                .df(None::<DataUsageOptions>)
+                .await
+                .map(|usage| Listing::Usage(Box::new(usage))),
+            DockerKind::Service => return self.services(None).await.map(Listing::Services),
+            DockerKind::Stack => {
+                let services = self.services(None).await?;
+                return Ok(Listing::Stacks(stack::stacks(&services)));
+            }
+            DockerKind::Secret => {
+                self.manager().await?;
+                self.docker
+                    .list_secrets(None::<ListSecretsOptions>)
+                    .await
+                    .map(Listing::Secrets)
+            }
+            DockerKind::Config => {
+                self.manager().await?;
+                self.docker
+                    .list_configs(None::<ListConfigsOptions>)
+                    .await
+                    .map(Listing::Configs)
+            }
+            DockerKind::Fleet => return self.fleet().await.map(Listing::Fleet),
+        }
+        .map_err(engine_err)
+    }
+
+    /// The containers of every host at once, one that doesn't answer in time is listed
+    /// with why rather than holding up the others.
+    async fn fleet(&self) -> Result<Vec<(String, Result<Vec<ContainerSummary>, String>)>, Error> {
+        if self.fleet.is_empty() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                "No hosts in the config, add them under [hosts], e.g. 'vps = \"ssh://me@vps\"'",
+            ));
+        }
+        let listed = self.fleet.iter().map(|(name, docker)| async move {
+            let containers = match docker {
+                Ok(docker) => {
+                    let options = ListContainersOptionsBuilder::new().all(true).build();
+                    match tokio::time::timeout(FLEET_TIMEOUT, docker.list_containers(Some(options)))
+                        .await
+                    {
+                        Ok(listed) => listed.map_err(|err| err.to_string()),
+                        Err(_) => Err(String::from("timed out")),
+                    }
+                }
+                Err(err) => Err(err.clone()),
+            };
+            (name.clone(), containers)
+        });
+        Ok(join_all(listed).await)
+    }
+
+    /// The host of a fleet row and the container on it, from its id `host/container`.
+    fn fleet_host<'a>(&self, target: &'a str) -> Result<(Docker, &'a str), Error> {
+        let (host, container) = target.split_once('/').ok_or(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{target} is a host, not a container"),
+        ))?;
+        match self.fleet.iter().find(|(name, _)| name == host) {
+            Some((_, Ok(docker))) => Ok((docker.clone(), container)),
+            Some((_, Err(err))) => Err(Error::other(format!("{host}: {err}"))),
+            None => Err(Error::new(
+                ErrorKind::NotFound,
+                format!("No host {host} in the config"),
+            )),
+        }
+    }
+
+    /// Runs a command of the fleet tab on the host of each target, as the containers tab
+    /// would run it there.
+    async fn across_fleet(&self, cmd: DockerPrompt) -> Report {
+        let mut output = Report::default();
+        for target in cmd.target.split_whitespace() {
+            let (docker, container) = match self.fleet_host(target) {
+                Ok(found) => found,
+                Err(err) => {
+                    output.fail(err);
+                    continue;
+                }
+            };
+            let engine = Engine {
+                docker,
+                host: String::new(),
+                fleet: Arc::default(),
+                sizes: self.sizes,
+            };
+            let done = match cmd.command {
+                DockerCommand::UP => engine.start(&[container]).await,
+                DockerCommand::STOP => engine.stop(&[container]).await,
+                DockerCommand::RESTART => engine.restart(&[container]).await,
+                command => {
+                    output.fail(format!("{command:?} isn't run across the fleet"));
+                    continue;
+                }
+            };
+            // the row is named by its own id, with the host in front
+            match done.failures.is_empty() {
+                true => output.lines.push(String::from(target)),
+                false => output.extend(done),
+            }
+        }
+        output
+    }
+
+    /// The services with their replicas running, only a swarm manager knows about them.
+    /// Only those of `stack` when there's one.
+    async fn services(&self, stack: Option<&str>) -> Result<Vec<Service>, Error> {
+        self.manager().await?;
+        let mut builder = ListServicesOptionsBuilder::new().status(true);
+        if let Some(stack) = stack {
+            let filters = HashMap::from([("label", vec![format!("{NAMESPACE_LABEL}={stack}")])]);
+            builder = builder.filters(&filters);
+        }
+        self.docker
+            .list_services(Some(builder.build()))
+            .await
+            .map_err(engine_err)
+    }
+
+    /// Fails unless the daemon is a swarm manager, the others know nothing about services,
+    /// stacks, secrets or configs.
+    async fn manager(&self) -> Result<(), Error> {
+        let info = self.docker.info().await.map_err(engine_err)?;
+        let manager = info
+            .swarm
+            .and_then(|swarm| swarm.control_available)
+            .unwrap_or_default();
+        match manager {
+            true => Ok(()),
+            false => Err(Error::new(
+                ErrorKind::Unsupported,
+                "Swarm objects need the daemon in swarm mode as a manager, see 'docker swarm init'",
+            )),
+        }
+    }
+
+    /// The names of the services every stack is made of.
+    async fn stack_services(&self, stacks: &[&str]) -> Result<Vec<String>, Error> {
+        let mut names = vec![];
+        for stack in stacks {
+            let services = self.services(Some(stack)).await?;
+            if services.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("Nothing found in stack: {stack}"),
+                ));
+            }
+            names.extend(
+                services
+                    .into_iter()
+                    .filter_map(|service| service.spec?.name),
+            );
+        }
+        Ok(names)
+    }
+
+    /// Runs `docker stack deploy`, the services it creates or updates shown as they come.
+    pub async fn deploy(
+        &self,
+        file: &str,
+        stack: &str,
+        progress: &mut Progress,
+    ) -> Result<Report, Error> {
+        let mut log = vec![];
+        let mut tail = Tail::new(progress);
+        let status = follow(cli::deploy(file, stack), |line| {
+            log.push(line);
+            tail.show(&log);
+        })
+        .await?;
+        let mut output = Report::from(log);
+        if !status.success() {
+            output.fail(format!("docker stack deploy exited with {status}"));
+        }
+        Ok(output)
+    }
+
+    /// Where the tasks of every service run and how they're doing, like `docker service ps`.
+    pub async fn tasks(&self, services: &[&str]) -> Result<Vec<String>, Error> {
+        let nodes: HashMap<String, String> = self
+            .docker
+            .list_nodes(None::<ListNodesOptions>)
+            .await
+            .map_err(engine_err)?
+            .into_iter()
+            .filter_map(|node| Some((node.id?, node.description?.hostname?)))
+            .collect();
+        let mut lines = vec![];
+        for service in services {
+            let filters = HashMap::from([("service", vec![*service])]);
+            let options = ListTasksOptionsBuilder::new().filters(&filters).build();
+            let mut tasks = self
+                .docker
+                .list_tasks(Some(options))
+                .await
+                .map_err(engine_err)?;
+            tasks.sort_by_key(|task| task.slot);
+            let mut rows = vec![[
+                "ID",
+                "NAME",
+                "IMAGE",
+                "NODE",
+                "DESIRED STATE",
+                "CURRENT STATE",
+                "ERROR",
+            ]
+            .map(String::from)
+            .to_vec()];
+            for task in tasks {
+                let node_id = task.node_id.clone().unwrap_or_default();
+                let node = nodes.get(&node_id).cloned().unwrap_or(node_id.clone());
+                // replicas are numbered by slot, global tasks go by their node
+                let name = match task.slot {
+                    Some(slot) => format!("{service}.{slot}"),
+                    None => format!("{service}.{}", short_id(&node_id)),
+                };
+                let image = task
+                    .spec
+                    .and_then(|spec| spec.container_spec)
+                    .and_then(|container| container.image)
+                    .unwrap_or_default();
+                let status = task.status.unwrap_or_default();
+                rows.push(vec![
+                    short_id(&task.id.unwrap_or_default()),
+                    name,
+                    String::from(image.split('@').next().unwrap_or_default()),
+                    node,
+                    task.desired_state
+                        .map(|state| state.to_string())
+                        .unwrap_or_default(),
+                    status
+                        .state
+                        .map(|state| state.to_string())
+                        .unwrap_or_default(),
+                    status.err.unwrap_or_default(),
+                ]);
+            }
+            lines.push(service.to_string());
+            lines.extend(align(rows));
+            lines.push(String::new());
+        }
+        Ok(lines)
+    }
+
+    /// The containers with their restart policy, which only inspecting them tells.
+    async fn containers(&self, options: ListContainersOptions) -> Result<Listing, Error> {
+        let containers = self
+            .docker
+            .list_containers(Some(options))
+            .await
+            .map_err(engine_err)?;
+        let inspected = join_all(
+            containers
+                .iter()
+                .filter_map(|container| container.id.as_deref())
+                .map(|id| {
+                    self.docker
+                        .inspect_container(id, None::<InspectContainerOptions>)
+                }),
+        )
+        .await;
+        let policies = inspected
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter_map(|container| {
+                let policy = container.host_config?.restart_policy?;
+                Some((container.id?, restart_policy(&policy)))
+            })
+            .collect();
+        Ok(Listing::Containers(containers, policies))
+    }
+
+    /// What a prune of `kind` would remove: stopped containers, dangling images and
+    /// volumes no container uses.
+    pub async fn reclaimable(&self, kind: &DockerKind) -> Result<Listing, Error> {
+        match kind {
+            DockerKind::Image => {
+                let filters = HashMap::from([("dangling", vec!["true"])]);
+                self.docker
+                    .list_images(Some(
+                        ListImagesOptionsBuilder::new().filters(&filters).build(),
+                    ))
+                    .await
+                    .map(Listing::Images)
+            }
+            DockerKind::Container => {
+                let filters = HashMap::from([("status", vec!["created", "exited", "dead"])]);
+                let options = ListContainersOptionsBuilder::new()
+                    .all(true)
+                    .size(self.sizes)
+                    .filters(&filters)
+                    .build();
+                return self.containers(options).await;
+            }
+            DockerKind::Volume => return self.unused_volumes().await.map(Listing::Volumes),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Only images, containers and volumes are listed by what's reclaimable",
+                ))
+            }
+        }
+        .map_err(engine_err)
+    }
+
+    /// The volumes the daemon calls dangling, less any a container still mounts, e.g.
+    /// one created meanwhile. Their sizes are filled in when the daemon tells them.
+    async fn unused_volumes(&self) -> Result<Vec<Volume>, Error> {
+        let filters = HashMap::from([("dangling", vec!["true"])]);
+        let options = ListVolumesOptionsBuilder::new().filters(&filters).build();
+        let volumes = self
+            .docker
+            .list_volumes(Some(options))
+            .await
+            .map_err(engine_err)?
+            .volumes
+            .unwrap_or_default();
+        let containers = self
+            .docker
+            .list_containers(Some(ListContainersOptionsBuilder::new().all(true).build()))
+            .await
+            .map_err(engine_err)?;
+        let mounted: HashSet<String> = containers
+            .iter()
+            .flat_map(|container| container.mounts.iter().flatten())
+            .filter_map(|mount| mount.name.clone())
+            .collect();
+        let sizes = self.volume_sizes().await;
+        Ok(volumes
+            .into_iter()
+            .filter(|volume| !mounted.contains(&volume.name))
+            .map(|mut volume| {
+                if let Some(size) = sizes.get(&volume.name) {
+                    volume.usage_data = Some(VolumeUsageData {
+                        size: *size,
+                        ref_count: 0,
+                    });
+                }
+                volume
+            })
+            .collect())
+    }
+
+    /// The `docker system df` rows a prune of `kind` would reclaim space from.
+    pub async fn usage(&self, kind: &DockerKind) -> Result<Listing, Error> {
+        let usage = self
+            .docker
+            .df(None::<DataUsageOptions>)
+            .await
+            .map_err(engine_err)?;
+        let usage = match kind {
+            DockerKind::Image => SystemDataUsageResponse {
+                image_usage: usage.image_usage,
+                ..Default::default()
+            },
+            DockerKind::Container => SystemDataUsageResponse {
+                container_usage: usage.container_usage,
+                ..Default::default()
+            },
+            DockerKind::Volume => SystemDataUsageResponse {
+                volume_usage: usage.volume_usage,
+                ..Default::default()
+            },
+            DockerKind::Project
+            | DockerKind::Registry
+            | DockerKind::Service
+            | DockerKind::Stack
+            | DockerKind::Secret
+            | DockerKind::Config
+            | DockerKind::Fleet => SystemDataUsageResponse::default(),
+            // like `docker system prune`, volumes are left alone
+            DockerKind::System => SystemDataUsageResponse {
+                volume_usage: None,
+                ..usage
+            },
+        };
+        Ok(Listing::Usage(Box::new(usage)))
+    }
+
+    /// Removes what is unused, like `docker <kind> prune --force`.
+    pub async fn prune(&self, kind: &DockerKind) -> Result<Vec<String>, Error> {
+        let mut output = vec![];
+        let mut reclaimed = 0;
+        if let DockerKind::Container | DockerKind::System = kind {
+            let pruned = self
+                .docker
+                .prune_containers(None::<PruneContainersOptions>)
+                .await
+                .map_err(engine_err)?;
+            output.extend(pruned.containers_deleted.unwrap_or_default());
+            reclaimed += pruned.space_reclaimed.unwrap_or_default();
+        }
+        if let DockerKind::System = kind {
+            let pruned = self
+                .docker
+                .prune_networks(None::<PruneNetworksOptions>)
+                .await
+                .map_err(engine_err)?;
+            output.extend(pruned.networks_deleted.unwrap_or_default());
+        }
+        if let DockerKind::Image | DockerKind::System = kind {
+            let pruned = self
+                .docker
+                .prune_images(None::<PruneImagesOptions>)
+                .await
+                .map_err(engine_err)?;
+            pruned
+                .images_deleted
+                .unwrap_or_default()
+                .into_iter()
+                .for_each(|item| {
+                    output.extend(item.untagged.map(|t| format!("Untagged: {t}")));
+                    output.extend(item.deleted.map(|d| format!("Deleted: {d}")));
+                });
+            reclaimed += pruned.space_reclaimed.unwrap_or_default();
+        }
+        if let DockerKind::Volume = kind {
+            let pruned = self
+                .docker
+                .prune_volumes(None::<PruneVolumesOptions>)
+                .await
+                .map_err(engine_err)?;
+            output.extend(pruned.volumes_deleted.unwrap_or_default());
+            reclaimed += pruned.space_reclaimed.unwrap_or_default();
+        }
+        if let DockerKind::System = kind {
+            let pruned = self
+                .docker
+                .prune_build(None::<PruneBuildOptions>)
+                .await
+                .map_err(engine_err)?;
+            output.extend(pruned.caches_deleted.unwrap_or_default());
+            reclaimed += pruned.space_reclaimed.unwrap_or_default();
+        }
+        if let DockerKind::Project = kind {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Use down to remove a compose project",
+            ));
+        }
+        if let DockerKind::Registry = kind {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "There is nothing to prune for registries",
+            ));
+        }
+        if let DockerKind::Fleet = kind {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Each host of the fleet is pruned from its own context",
+            ));
+        }
+        if let DockerKind::Service | DockerKind::Stack | DockerKind::Secret | DockerKind::Config =
+            kind
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Swarm objects aren't pruned, use rm to remove them",
+            ));
+        }
+        output.push(format!("Total reclaimed space: {}", human_size(reclaimed)));
+        Ok(output)
+    }
+
+    /// Every container, stopped ones too, that belongs to a compose project.
+    async fn compose_containers(
+        &self,
+        project: Option<&str>,
+    ) -> Result<Vec<bollard::models::ContainerSummary>, bollard::errors::Error> {
+        let label = match project {
+            Some(project) => format!("{PROJECT_LABEL}={project}"),
+            None => String::from(PROJECT_LABEL),
+        };
+        let filters = HashMap::from([("label", vec![label])]);
+        let options = ListContainersOptionsBuilder::new()
+            .all(true)
+            .filters(&filters)
+            .build();
+        self.docker.list_containers(Some(options)).await
+    }
+
+    /// Runs `docker compose` for every target project, gathering what it printed.
+    pub async fn compose(
+        &self,
+        targets: &[&str],
+        command: ComposeCommand,
+    ) -> Result<Report, Error> {
+        let mut output = Report::default();
+        for target in targets {
+            let containers = self
+                .compose_containers(Some(target))
+                .await
+                .map_err(engine_err)?;
+            let Some(project) = compose::projects(&containers).into_iter().next() else {
+                output.fail(format!("no such compose project: {target}"));
+                continue;
+            };
+            let compose = cli::compose(&project, command);
+            info!(command = ?compose, "running");
+            let result = tokio::process::Command::from(compose)
+                .kill_on_drop(true)
+                .output()
+                .await?;
+            info!(status = %result.status, "exited");
+            [result.stdout, result.stderr].iter().for_each(|printed| {
+                output
+                    .lines
+                    .extend(String::from_utf8_lossy(printed).lines().map(String::from))
+            });
+            if !result.status.success() {
+                output.fail(format!("docker compose exited with {}", result.status));
+            }
+        }
+        Ok(output)
+    }
+
+    /// Removes the images or volumes like `rm` does, then tells how much space that
+    /// reclaimed like `docker <kind> prune` does.
+    pub async fn remove_dangling(&self, kind: &DockerKind, targets: &[&str]) -> Report {
+        let volume_sizes = match kind {
+            DockerKind::Volume => self.volume_sizes().await,
+            _ => HashMap::new(),
+        };
+        let mut output = Report::default();
+        let mut reclaimed = 0;
+        for target in targets {
+            let size = match kind {
+                DockerKind::Image => self
+                    .docker
+                    .inspect_image(target)
+                    .await
+                    .ok()
+                    .and_then(|image| image.size)
+                    .unwrap_or_default(),
+                _ => volume_sizes.get(*target).copied().unwrap_or_default(),
+            };
+            let removed = self.remove(kind, &[target]).await;
+            if removed.failures.is_empty() {
+                reclaimed += size;
+            }
+            output.extend(removed);
+        }
+        output
+            .lines
+            .push(format!("Total reclaimed space: {}", human_size(reclaimed)));
+        output
+    }
+
+    /// What every volume takes on disk, by name. Daemons before API 1.52 don't list them,
+    /// nor do they for volumes of drivers that can't tell.
+    async fn volume_sizes(&self) -> HashMap<String, i64> {
+        let options = DataUsageOptionsBuilder::new()
+            ._type(vec![String::from("volume")])
+            .verbose(true)
+            .build();
+        let Ok(usage) = self.docker.df(Some(options)).await else {
+            return HashMap::new();
+        };
+        usage
+            .volume_usage
+            .and_then(|usage| usage.items)
+            .into_iter()
+            .flatten()
+            .filter_map(|item| serde_json::from_value::<Volume>(item).ok())
+            .filter_map(|volume| {
+                let size = volume.usage_data?.size;
+                (size >= 0).then_some((volume.name, size))
+            })
+            .collect()
+    }
+
+    /// Runs `each` on the targets one after the other, with a checklist of where every one
+    /// is at sent to `progress` as it goes. A lone target gets its output as is.
+    async fn batch<'a, F, Fut>(
+        &self,
+        doing: &'static str,
+        targets: &[&'a str],
+        progress: &mut Progress,
+        mut each: F,
+    ) -> Report
+    where
+        F: FnMut(&'a str) -> Fut,
+        Fut: Future<Output = Report>,
+    {
+        if targets.len() < 2 {
+            return match targets.first() {
+                Some(target) => each(target).await,
+                None => Report::default(),
+            };
+        }
+        let mut checklist = Checklist::new(doing, targets);
+        let mut output = vec![];
+        let mut failures = vec![];
+        for (index, target) in targets.iter().enumerate() {
+            checklist.set(index, Step::Running, None);
+            progress(checklist.lines());
+            let done = each(target).await;
+            match done.failures.first() {
+                Some(err) => checklist.set(index, Step::Failed, Some(err.clone())),
+                None => checklist.set(index, Step::Done, None),
+            }
+            // the checklist tells the rest, e.g. the target name or its error
+            let told: Vec<String> = done
+                .failures
+                .iter()
+                .map(|err| format!("Error: {err}"))
+                .collect();
+            output.extend(
+                done.lines
+                    .into_iter()
+                    .filter(|line| line != target && !told.contains(line)),
+            );
+            failures.extend(done.failures);
+        }
+        let mut lines = checklist.lines();
+        if !output.is_empty() {
+            lines.push(String::new());
+            lines.extend(output);
+        }
+        Report { lines, failures }
+    }
+
+    /// Removes every target, one line of output per target like the CLI does.
+    pub async fn remove(&self, kind: &DockerKind, targets: &[&str]) -> Report {
+        let mut output = Report::default();
+        for target in targets {
+            let result = match kind {
+                DockerKind::Image => self
+                    .docker
+                    .remove_image(target, None::<RemoveImageOptions>, None)
+                    .await
+                    .map(|items| {
+                        items
+                            .into_iter()
+                            .flat_map(|item| {
+                                let untagged = item.untagged.map(|t| format!("Untagged: {t}"));
+                                let deleted = item.deleted.map(|d| format!("Deleted: {d}"));
+                                untagged.into_iter().chain(deleted)
+                            })
+                            .collect()
+                    }),
+                DockerKind::Container => self
+                    .docker
+                    .remove_container(target, None::<RemoveContainerOptions>)
+                    .await
+                    .map(|_| vec![target.to_string()]),
+                DockerKind::Volume => self
+                    .docker
+                    .remove_volume(target, None::<RemoveVolumeOptions>)
+                    .await
+                    .map(|_| vec![target.to_string()]),
+                DockerKind::Project => {
+                    output.fail("use down to remove a compose project");
+                    continue;
+                }
+                DockerKind::System => {
+                    output.fail("use prune to clean up the system");
+                    continue;
+                }
+                DockerKind::Registry => {
+                    output.fail("use logout to forget a registry");
+                    continue;
+                }
+                DockerKind::Stack => {
+                    output.extend(self.remove_stack(target).await);
+                    continue;
+                }
+                DockerKind::Service => self
+                    .docker
+                    .delete_service(target)
+                    .await
+                    .map(|_| vec![target.to_string()]),
+                DockerKind::Secret => self
+                    .docker
+                    .delete_secret(target)
+                    .await
+                    .map(|_| vec![target.to_string()]),
+                DockerKind::Config => self
+                    .docker
+                    .delete_config(target)
+                    .await
+                    .map(|_| vec![target.to_string()]),
+                DockerKind::Fleet => {
+                    output.fail("stop fleet containers, then remove them from their host");
+                    continue;
+                }
+            };
+            match result {
+                Ok(lines) => output.lines.extend(lines),
+                Err(err) => output.fail(err),
+            }
+        }
+        output
+    }
+
+    pub async fn stop(&self, targets: &[&str]) -> Report {
+        let mut output = Report::default();
+        for target in targets {
+            match self
+                .docker
+                .stop_container(target, None::<StopContainerOptions>)
+                .await
+            {
+                Ok(_) => output.lines.push(target.to_string()),
+                Err(err) => output.fail(err),
+            }
+        }
+        output
+    }
+
+    pub async fn start(&self, targets: &[&str]) -> Report {
+        let mut output = Report::default();
+        for target in targets {
+            match self
+                .docker
+                .start_container(target, None::<StartContainerOptions>)
+                .await
+            {
+                Ok(_) => output.lines.push(target.to_string()),
+                Err(err) => output.fail(err),
+            }
+        }
+        output
+    }
+
+    pub async fn restart(&self, targets: &[&str]) -> Report {
+        let mut output = Report::default();
+        for target in targets {
+            match self
+                .docker
+                .restart_container(target, None::<RestartContainerOptions>)
+                .await
+            {
+                Ok(_) => output.lines.push(target.to_string()),
+                Err(err) => output.fail(err),
+            }
+        }
+        output
+    }
+
+    pub async fn pause(&self, targets: &[&str]) -> Report {
+        let mut output = Report::default();
+        for target in targets {
+            match self.docker.pause_container(target).await {
+                Ok(_) => output.lines.push(target.to_string()),
+                Err(err) => output.fail(err),
+            }
+        }
+        output
+    }
+
+    pub async fn update(&self, targets: &[&str], body: ContainerUpdateBody) -> Report {
+        let mut output = Report::default();
+        for target in targets {
+            match self.docker.update_container(target, body.clone()).await {
+                Ok(_) => output.lines.push(target.to_string()),
+                Err(err) => output.fail(err),
+            }
+        }
+        output
+    }
+
+    /// Runs `docker stack rm`, what it printed or why it failed.
+    async fn remove_stack(&self, stack: &str) -> Report {
+        let command = cli::stack_rm(stack);
+        info!(command = ?command, "running");
+        let result = match tokio::process::Command::from(command)
+            .kill_on_drop(true)
+            .output()
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                let mut output = Report::default();
+                output.fail(err);
+                return output;
+            }
+        };
+        info!(status = %result.status, "exited");
+        let mut output = Report::from(
+            [result.stdout, result.stderr]
+                .iter()
+                .flat_map(|printed| {
+                    String::from_utf8_lossy(printed)
+                        .lines()
+                        .map(String::from)
+                        .collect::<Vec<String>>()
+                })
+                .collect::<Vec<String>>(),
+        );
+        if !result.status.success() {
+            output.fail(format!("docker stack rm exited with {}", result.status));
+        }
+        output
+    }
+
+    /// Sets how many tasks every replicated service runs, like `docker service scale`.
+    pub async fn scale(&self, targets: &[&str], replicas: u64) -> Report {
+        let mut output = Report::default();
+        for target in targets {
+            let scaled = self
+                .update_service(target, |spec| {
+                    match spec.mode.as_mut().and_then(|mode| mode.replicated.as_mut()) {
+                        Some(replicated) => {
+                            replicated.replicas = Some(replicas as i64);
+                            Ok(())
+                        }
+                        None => Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("{target} is global, only replicated services scale"),
+                        )),
+                    }
+                })
+                .await;
+            match scaled {
+                Ok(_) => output.lines.push(format!("{target} scaled to {replicas}")),
+                Err(err) => output.fail(err),
+            }
+        }
+        output
+    }
+
+    /// Rolls every service out on `image`, like `docker service update --image`.
+    pub async fn update_image(&self, targets: &[&str], image: &str) -> Report {
+        let mut output = Report::default();
+        for target in targets {
+            let updated = self
+                .update_service(target, |spec| {
+                    let task = spec.task_template.get_or_insert_with(Default::default);
+                    let container = task.container_spec.get_or_insert_with(Default::default);
+                    container.image = Some(String::from(image));
+                    Ok(())
+                })
+                .await;
+            match updated {
+                Ok(_) => output.lines.push(target.to_string()),
+                Err(err) => output.fail(err),
+            }
+        }
+        output
+    }
+
+    /// Changes the spec of `service` as inspected, the daemon refuses it if someone else
+    /// updated the service meanwhile.
+    async fn update_service<F>(&self, service: &str, change: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut ServiceSpec) -> Result<(), Error>,
+    {
+        let inspected = self
+            .docker
+            .inspect_service(service, None::<InspectServiceOptions>)
+            .await
+            .map_err(engine_err)?;
+        let version = inspected
+            .version
+            .and_then(|version| version.index)
+            .unwrap_or_default();
+        let mut spec = inspected.spec.unwrap_or_default();
+        change(&mut spec)?;
+        let options = UpdateServiceOptionsBuilder::new()
+            .version(version as i32)
+            .build();
+        self.docker
+            .update_service(service, spec, options, None)
+            .await
+            .map(|_| ())
+            .map_err(engine_err)
+    }
+
+    pub async fn kill(&self, targets: &[&str], signal: &str) -> Report {
+        let mut output = Report::default();
+        let options = KillContainerOptionsBuilder::new().signal(signal).build();
+        for target in targets {
+            match self
+                .docker
+                .kill_container(target, Some(options.clone()))
+                .await
+            {
+                Ok(_) => output.lines.push(format!("{target} {signal}")),
+                Err(err) => output.fail(err),
+            }
+        }
+        output
+    }
+
+    pub async fn unpause(&self, targets: &[&str]) -> Report {
+        let mut output = Report::default();
+        for target in targets {
+            match self.docker.unpause_container(target).await {
+                Ok(_) => output.lines.push(target.to_string()),
+                Err(err) => output.fail(err),
+            }
+        }
+        output
+    }
+
+    /// Opens `http://localhost:<port>` in the browser, the lowest port the container publishes.
+    pub async fn open(&self, targets: &[&str]) -> Report {
+        let mut output = Report::default();
+        for target in targets {
+            let inspected = match self
+                .docker
+                .inspect_container(target, None::<InspectContainerOptions>)
+                .await
+            {
+                Ok(inspected) => inspected,
+                Err(err) => {
+                    output.fail(err);
+                    continue;
+                }
+            };
+            let port = inspected
+                .network_settings
+                .and_then(|settings| settings.ports)
+                .into_iter()
+                .flatten()
+                .filter(|(exposed, _)| exposed.ends_with("/tcp"))
+                .flat_map(|(_, bindings)| bindings.into_iter().flatten())
+                .filter_map(|binding| binding.host_port?.parse::<u16>().ok())
+                .min();
+            let Some(port) = port else {
+                output.fail(format!("{target} doesn't publish any port"));
+                continue;
+            };
+            let url = format!("http://{}:{port}", published_host(&self.host));
+            match tokio::process::Command::from(cli::open(&url))
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .await
+            {
+                Ok(status) if status.success() => output.lines.push(format!("Opened {url}")),
+                Ok(status) => output.fail(format!("opening {url} exited with {status}")),
+                Err(err) => output.fail(format!("can't open {url}: {err}")),
+            }
+        }
+        output
+    }
+
+    /// `source` is an image reference, `target` a `repo[:tag]` like `docker image tag`.
+    pub async fn tag(&self, source: &str, target: &str) -> Result<(), Error> {
+        let (repo, tag) = match target.rsplit_once(':') {
+            Some((repo, tag)) if !tag.contains('/') => (repo, tag),
+            _ => (target, "latest"),
+        };
+        let options = TagImageOptionsBuilder::new().repo(repo).tag(tag).build();
+        self.docker
+            .tag_image(source, Some(options))
+            .await
+            .map_err(engine_err)
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{BufRead, BufReader, Error, Read},
+    path::Path,
+};
+
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::listing::{created_by, human_size};
+
+/// Marks a path removed by the layer, e.g. `etc/.wh.motd`.
+const WHITEOUT: &str = ".wh.";
+/// Marks a directory whose lower contents are hidden, nothing to show for itself.
+const OPAQUE: &str = ".wh..wh..opq";
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// What a layer did to a path, compared to the layers below it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Change {
+    Added,
+    Modified,
+    Removed,
+}
+
+#[derive(Debug)]
+pub struct LayerFile {
+    pub path: String,
+    pub size: u64,
+    pub change: Change,
+}
+
+/// A layer of an image as `docker save` exports it, with the Dockerfile step that made it.
+#[derive(Debug)]
+pub struct Layer {
+    pub id: String,
+    pub created_by: String,
+    pub size: u64,
+    pub files: Vec<LayerFile>,
+}
+
+/// The `manifest.json` of a saved image, one per tag saved.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Manifest {
+    config: String,
+    layers: Vec<String>,
+}
+
+impl Layer {
+    /// One line for the layer, then its files indented, for plain output.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "{}  {}  {}",
+            self.id,
+            human_size(self.size as i64),
+            self.created_by
+        )];
+        lines.extend(self.files.iter().map(|file| {
+            let change = match file.change {
+                Change::Added => '+',
+                Change::Modified => '~',
+                Change::Removed => '-',
+            };
+            format!("  {change} {}  {}", file.path, human_size(file.size as i64))
+        }));
+        lines
+    }
+}
+
+/// The layers of the image saved at `path`, bottom one first.
+/// Both the classic `<id>/layer.tar` layout and the OCI `blobs/sha256/<digest>` one are read.
+pub fn read(path: &Path) -> Result<Vec<Layer>, Error> {
+    let mut archive = tar::Archive::new(File::open(path)?);
+    let mut manifests: Vec<Manifest> = vec![];
+    // json is kept to find the config among it, anything else is tried as a layer
+    let mut documents: HashMap<String, Value> = HashMap::new();
+    let mut contents: HashMap<String, Vec<(String, u64)>> = HashMap::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut reader = BufReader::new(entry);
+        let head = reader.fill_buf()?;
+        if head.starts_with(b"{") || head.starts_with(b"[") {
+            match name.as_str() {
+                "manifest.json" => {
+                    manifests = serde_json::from_reader(reader).map_err(Error::other)?
+                }
+                _ => {
+                    if let Ok(document) = serde_json::from_reader(reader) {
+                        documents.insert(name, document);
+                    }
+                }
+            }
+            continue;
+        }
+        let listed = match head.starts_with(&GZIP_MAGIC) {
+            true => list(GzDecoder::new(reader)),
+            false => list(reader),
+        };
+        // e.g. a blob that's neither json nor a tar, nothing to show for it
+        if let Ok(files) = listed {
+            contents.insert(name, files);
+        }
+    }
+    let Some(manifest) = manifests.into_iter().next() else {
+        return Err(Error::other("The saved image has no manifest"));
+    };
+    let steps = documents
+        .get(&manifest.config)
+        .map(steps)
+        .unwrap_or_default();
+
+    let mut present: HashSet<String> = HashSet::new();
+    let layers = manifest
+        .layers
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let files = changes(contents.remove(name).unwrap_or_default(), &mut present);
+            Layer {
+                id: layer_id(name),
+                // steps only line up with the layers when every one is there
+                created_by: match steps.len() == manifest.layers.len() {
+                    true => steps[index].clone(),
+                    false => String::new(),
+                },
+                size: files
+                    .iter()
+                    .filter(|file| file.change != Change::Removed)
+                    .map(|file| file.size)
+                    .sum(),
+                files,
+            }
+        })
+        .collect();
+    Ok(layers)
+}
+
+/// Every path a layer tar holds with its size, directories left out.
+fn list<R: Read>(reader: R) -> Result<Vec<(String, u64)>, Error> {
+    let mut archive = tar::Archive::new(reader);
+    let mut files = vec![];
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if entry.header().entry_type().is_dir() {
+            continue;
+        }
+        let path = entry.path()?.to_string_lossy().into_owned();
+        let path = path.trim_start_matches("./").trim_end_matches('/');
+        files.push((String::from(path), entry.header().size()?));
+    }
+    Ok(files)
+}
+
+/// Classifies the paths of a layer against the ones `present` below it, then adds its own.
+fn changes(files: Vec<(String, u64)>, present: &mut HashSet<String>) -> Vec<LayerFile> {
+    let mut changes: Vec<LayerFile> = files
+        .into_iter()
+        .filter_map(|(path, size)| {
+            let (dir, name) = match path.rsplit_once('/') {
+                Some((dir, name)) => (format!("{dir}/"), name),
+                None => (String::new(), path.as_str()),
+            };
+            if name == OPAQUE {
+                return None;
+            }
+            let (path, change) = match name.strip_prefix(WHITEOUT) {
+                Some(removed) => (format!("{dir}{removed}"), Change::Removed),
+                None if present.contains(&path) => (path.clone(), Change::Modified),
+                None => (path.clone(), Change::Added),
+            };
+            Some(LayerFile { path, size, change })
+        })
+        .collect();
+    changes.iter().for_each(|file| match file.change {
+        Change::Removed => {
+            present.remove(&file.path);
+        }
+        Change::Added | Change::Modified => {
+            present.insert(file.path.clone());
+        }
+    });
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+/// The Dockerfile steps of the image config that made a layer, in order.
+fn steps(config: &Value) -> Vec<String> {
+    config["history"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|step| !step["empty_layer"].as_bool().unwrap_or_default())
+        .map(|step| created_by(step["created_by"].as_str().unwrap_or_default()))
+        .collect()
+}
+
+/// Short digest of a layer, from `blobs/sha256/<digest>` or `<digest>/layer.tar`.
+fn layer_id(name: &str) -> String {
+    let digest = match name.strip_suffix("/layer.tar") {
+        Some(digest) => digest,
+        None => name.rsplit('/').next().unwrap_or(name),
+    };
+    digest.chars().take(12).collect()
+}
@@ -0,0 +1,1339 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::{Error, ErrorKind},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bollard::models::{
+    Config, ContainerSummary, EndpointPortConfig, ImageHistoryResponseItem,
+    ImageSearchResponseItem, ImageSummary, MountPoint, PortSummary, Secret, Service,
+    ServiceSpecMode, SystemDataUsageResponse, Volume,
+};
+
+use super::{
+    compose::Project,
+    registry::Registry,
+    stack::{Stack, NAMESPACE_LABEL},
+    DockerKind,
+};
+
+/// The `docker system df` rows that stand for a listing, the build cache has none.
+const USAGE_KINDS: [(&str, DockerKind); 3] = [
+    ("Images", DockerKind::Image),
+    ("Containers", DockerKind::Container),
+    ("Local Volumes", DockerKind::Volume),
+];
+
+/// What the rows of the fleet tab are narrowed to a host by, e.g. `host=vps`.
+pub const HOST_LABEL: &str = "host";
+
+/// Lines of a config shown in the details pane, the rest is only counted.
+const PREVIEW_LINES: usize = 50;
+
+/// Typed result of a `ls` against the docker engine.
+#[derive(Debug)]
+pub enum Listing {
+    Images(Vec<ImageSummary>),
+    /// with the restart policies by container id
+    Containers(Vec<ContainerSummary>, HashMap<String, String>),
+    Volumes(Vec<Volume>),
+    Projects(Vec<Project>),
+    Usage(Box<SystemDataUsageResponse>),
+    Registries(Vec<Registry>),
+    Services(Vec<Service>),
+    Stacks(Vec<Stack>),
+    Secrets(Vec<Secret>),
+    Configs(Vec<Config>),
+    /// the containers of each host by its name, or why they couldn't be listed
+    Fleet(Vec<(String, Result<Vec<ContainerSummary>, String>)>),
+    Search(Vec<ImageSearchResponseItem>),
+    History(Vec<ImageHistoryResponseItem>),
+}
+
+/// A listing ready to show, every row keeps what commands should target it by.
+#[derive(Debug, Default)]
+pub struct Table {
+    pub header: Vec<String>,
+    pub rows: Vec<Row>,
+    /// the header column each sort applies to
+    columns: Vec<(SortBy, usize)>,
+    sorted: Option<(SortBy, bool)>,
+    /// how many of the last columns show only when the config picks them
+    extras: usize,
+    /// the ids of the rows starred as favorites, listed first
+    favorites: HashSet<String>,
+}
+
+#[derive(Debug)]
+pub struct Row {
+    pub id: String,
+    pub cells: Vec<String>,
+    /// labelled values for the details pane, more than fits in the columns
+    pub details: Vec<(&'static str, Vec<String>)>,
+    /// the container state, e.g. `running` or `exited`, rows of other kinds have none
+    pub state: Option<String>,
+    /// what label selectors match against, rows of kinds without labels have none
+    pub labels: HashMap<String, String>,
+    keys: SortKeys,
+}
+
+/// What rows are picked by without the TUI, like `docker ps --filter` takes it.
+#[derive(Debug)]
+pub enum RowFilter {
+    /// the start of the id
+    Id(String),
+    /// part of the name
+    Name(String),
+    /// the container state, e.g. `exited`
+    Status(String),
+    /// label selectors, e.g. `com.docker.compose.project=foo`
+    Label(String),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SortBy {
+    Name,
+    Size,
+    Created,
+    Status,
+}
+
+/// The raw values behind the rendered cells, e.g. bytes instead of `1.2GB`.
+#[derive(Debug, Default)]
+struct SortKeys {
+    name: Option<String>,
+    size: Option<i64>,
+    created: Option<i64>,
+    status: Option<String>,
+}
+
+impl Row {
+    /// The name people know the row by, e.g. the container name rather than its id.
+    pub fn name(&self) -> &str {
+        self.keys.name.as_deref().unwrap_or(&self.id)
+    }
+}
+
+impl RowFilter {
+    /// `id=…`, `name=…`, `status=…` or `label=…`.
+    pub fn parse(filter: &str) -> Result<RowFilter, Error> {
+        let invalid = || {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid filter: {filter}, use id=, name=, status= or label="),
+            )
+        };
+        let (key, value) = filter.split_once('=').ok_or_else(invalid)?;
+        let value = String::from(value);
+        match key {
+            "id" => Ok(RowFilter::Id(value)),
+            "name" => Ok(RowFilter::Name(value)),
+            "status" => Ok(RowFilter::Status(value)),
+            "label" => Ok(RowFilter::Label(value)),
+            _ => Err(invalid()),
+        }
+    }
+
+    pub fn picks(&self, row: &Row) -> bool {
+        match self {
+            RowFilter::Id(id) => row.id.starts_with(id.as_str()),
+            RowFilter::Name(name) => row.name().contains(name.as_str()),
+            RowFilter::Status(status) => row.state.as_deref() == Some(status.as_str()),
+            RowFilter::Label(selector) => selects(&row.labels, selector),
+        }
+    }
+}
+
+impl Table {
+    /// A listing of plain rows sortable by name, the first cell of each is what commands
+    /// target it by, e.g. one made up without a daemon.
+    pub fn plain(columns: &[&str], rows: &[Vec<String>]) -> Table {
+        Table {
+            header: header(columns),
+            rows: rows
+                .iter()
+                .map(|cells| Row {
+                    id: cells.first().cloned().unwrap_or_default(),
+                    cells: cells.clone(),
+                    details: vec![],
+                    state: None,
+                    labels: HashMap::new(),
+                    keys: SortKeys {
+                        name: cells.first().cloned(),
+                        ..Default::default()
+                    },
+                })
+                .collect(),
+            columns: vec![(SortBy::Name, 0)],
+            ..Default::default()
+        }
+    }
+
+    /// Aligned text rows, header first, like the docker CLI prints them.
+    pub fn lines(&self) -> Vec<String> {
+        align(self.cells())
+    }
+
+    /// One line per row with each cell after its column name, e.g. `name: web, status: Up`,
+    /// for screen readers. The first line says how many rows there are and their order.
+    pub fn labelled(&self) -> Vec<String> {
+        let mut summary = format!("{} rows", self.rows.len());
+        if let Some((by, descending)) = self.sorted {
+            if let Some(column) = self.column(by).and_then(|column| self.header.get(column)) {
+                let order = match descending {
+                    true => "descending",
+                    false => "ascending",
+                };
+                summary.push_str(&format!(", sorted by {} {order}", column.to_lowercase()));
+            }
+        }
+        let mut lines = vec![summary];
+        lines.extend(self.rows.iter().map(|row| {
+            let cells = self
+                .header
+                .iter()
+                .zip(&row.cells)
+                .filter(|(_, cell)| !cell.is_empty())
+                .map(|(column, cell)| format!("{}: {cell}", column.to_lowercase()))
+                .collect::<Vec<String>>()
+                .join(", ");
+            match self.favorites.contains(&row.id) {
+                true => format!("favorite, {cells}"),
+                false => cells,
+            }
+        }));
+        lines
+    }
+
+    /// The header with the sorted column marked by its order.
+    pub fn marked_header(&self) -> Vec<String> {
+        let mut header = self.header.clone();
+        if let Some((by, descending)) = self.sorted {
+            if let Some(cell) = self.column(by).and_then(|column| header.get_mut(column)) {
+                cell.push_str(match descending {
+                    true => " ▼",
+                    false => " ▲",
+                });
+            }
+        }
+        header
+    }
+
+    /// The header, marked with the sort, then the cells of every row.
+    fn cells(&self) -> Vec<Vec<String>> {
+        let mut cells = vec![self.marked_header()];
+        cells.extend(self.rows.iter().map(|row| self.row_cells(row)));
+        cells
+    }
+
+    /// The cells of `row`, the first one starred if it's a favorite.
+    pub fn row_cells(&self, row: &Row) -> Vec<String> {
+        let mut cells = row.cells.clone();
+        if let Some(first) = cells
+            .first_mut()
+            .filter(|_| self.favorites.contains(&row.id))
+        {
+            first.insert_str(0, "★ ");
+        }
+        cells
+    }
+
+    /// Stars the rows named in `favorites` and moves them above the others, the rows
+    /// keep their order otherwise.
+    pub fn favor(&mut self, favorites: &[String]) {
+        self.favorites = self
+            .rows
+            .iter()
+            .filter(|row| favorites.iter().any(|favorite| favorite == row.name()))
+            .map(|row| row.id.clone())
+            .collect();
+        self.rows
+            .sort_by_key(|row| !self.favorites.contains(&row.id));
+    }
+
+    /// Keeps `columns` only and in their order, named like the header but in any case,
+    /// e.g. `["names", "status"]`.
+    pub fn pick_columns(&mut self, columns: &[String]) -> Result<(), Error> {
+        let picked = columns
+            .iter()
+            .map(|column| {
+                self.header
+                    .iter()
+                    .position(|name| name.eq_ignore_ascii_case(column))
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::InvalidData,
+                            format!(
+                                "Unknown column in config: {column}, there's {}",
+                                self.header.join(", ")
+                            ),
+                        )
+                    })
+            })
+            .collect::<Result<Vec<usize>, Error>>()?;
+        self.keep(&picked);
+        Ok(())
+    }
+
+    /// Leaves out the columns shown only when the config picks them, but the `shown` ones.
+    pub fn hide_extras(&mut self, shown: &[&str]) {
+        let defaults = self.header.len().saturating_sub(self.extras);
+        let kept: Vec<usize> = self
+            .header
+            .iter()
+            .enumerate()
+            .filter(|(index, name)| {
+                *index < defaults || shown.iter().any(|shown| name.eq_ignore_ascii_case(shown))
+            })
+            .map(|(index, _)| index)
+            .collect();
+        self.keep(&kept);
+    }
+
+    /// Keeps the columns at `indexes`, in that order, the sorts follow their column.
+    fn keep(&mut self, indexes: &[usize]) {
+        let pick = |cells: &[String]| -> Vec<String> {
+            indexes
+                .iter()
+                .map(|index| cells.get(*index).cloned().unwrap_or_default())
+                .collect()
+        };
+        self.header = pick(&self.header);
+        self.rows
+            .iter_mut()
+            .for_each(|row| row.cells = pick(&row.cells));
+        self.columns = self
+            .columns
+            .iter()
+            .filter_map(|(by, column)| {
+                let kept = indexes.iter().position(|index| index == column)?;
+                Some((*by, kept))
+            })
+            .collect();
+        self.extras = 0;
+    }
+
+    /// Sorts the rows, `false` when the listing has no such column.
+    pub fn sort(&mut self, by: SortBy, descending: bool) -> bool {
+        if self.column(by).is_none() {
+            return false;
+        }
+        self.rows.sort_by(|a, b| {
+            let order = match by {
+                SortBy::Name => a.keys.name.cmp(&b.keys.name),
+                SortBy::Size => a.keys.size.cmp(&b.keys.size),
+                SortBy::Created => a.keys.created.cmp(&b.keys.created),
+                SortBy::Status => a.keys.status.cmp(&b.keys.status),
+            };
+            match descending {
+                true => order.reverse(),
+                false => order,
+            }
+        });
+        self.sorted = Some((by, descending));
+        true
+    }
+
+    /// `rows` as an array of objects keyed by the header.
+    pub fn json(&self, rows: &[&Row]) -> String {
+        let objects: Vec<serde_json::Map<String, serde_json::Value>> = rows
+            .iter()
+            .map(|row| {
+                self.header
+                    .iter()
+                    .cloned()
+                    .zip(row.cells.iter().cloned().map(serde_json::Value::String))
+                    .collect()
+            })
+            .collect();
+        serde_json::to_string_pretty(&objects).unwrap_or_default()
+    }
+
+    /// `rows` as csv, header first.
+    pub fn csv(&self, rows: &[&Row]) -> String {
+        let mut lines = vec![csv_line(&self.header)];
+        lines.extend(rows.iter().map(|row| csv_line(&row.cells)));
+        lines.join("\n") + "\n"
+    }
+
+    fn column(&self, by: SortBy) -> Option<usize> {
+        self.columns
+            .iter()
+            .find(|(sort, _)| *sort == by)
+            .map(|(_, column)| *column)
+    }
+}
+
+/// Quotes only the fields that need it, like most spreadsheets write them.
+fn csv_line(cells: &[String]) -> String {
+    cells
+        .iter()
+        .map(|cell| match cell.contains([',', '"', '\n']) {
+            true => format!("\"{}\"", cell.replace('"', "\"\"")),
+            false => cell.clone(),
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn header(columns: &[&str]) -> Vec<String> {
+    columns.iter().map(|column| String::from(*column)).collect()
+}
+
+impl Listing {
+    pub fn table(&self) -> Table {
+        match self {
+            Listing::Images(images) => {
+                let mut rows = vec![];
+                images.iter().for_each(|image| {
+                    let id = short_id(&image.id);
+                    let created = time_ago(image.created);
+                    let size = human_size(image.size);
+                    let repo_tags = match image.repo_tags.is_empty() {
+                        true => vec![String::from("<none>:<none>")],
+                        false => image.repo_tags.clone(),
+                    };
+                    repo_tags.iter().for_each(|repo_tag| {
+                        let (repo, tag) = repo_tag.rsplit_once(':').unwrap_or((repo_tag, "<none>"));
+                        // like `docker images --digests`, the digest of the row's repository
+                        let digest = image
+                            .repo_digests
+                            .iter()
+                            .find_map(|digest| digest.strip_prefix(repo)?.strip_prefix('@'))
+                            .unwrap_or("<none>");
+                        // a tagged row stands for that tag only, e.g. rm just untags it
+                        let target = match repo_tag.as_str() {
+                            "<none>:<none>" => image.id.clone(),
+                            _ => repo_tag.clone(),
+                        };
+                        let mut details = vec![
+                            ("Id", vec![image.id.clone()]),
+                            ("Tags", image.repo_tags.clone()),
+                            ("Digests", image.repo_digests.clone()),
+                            ("Created", vec![created.clone()]),
+                            ("Size", vec![size.clone()]),
+                        ];
+                        // only counted when asked for, -1 otherwise
+                        if image.containers >= 0 {
+                            details.push(("Containers", vec![image.containers.to_string()]));
+                        }
+                        details.push(("Labels", label_list(&image.labels)));
+                        rows.push(Row {
+                            id: target,
+                            cells: vec![
+                                String::from(repo),
+                                String::from(tag),
+                                id.clone(),
+                                created.clone(),
+                                size.clone(),
+                                String::from(digest),
+                            ],
+                            details,
+                            state: None,
+                            labels: image.labels.clone(),
+                            keys: SortKeys {
+                                name: Some(repo_tag.clone()),
+                                size: Some(image.size),
+                                created: Some(image.created),
+                                status: None,
+                            },
+                        });
+                    });
+                });
+                Table {
+                    header: header(&["REPOSITORY", "TAG", "IMAGE ID", "CREATED", "SIZE", "DIGEST"]),
+                    rows,
+                    columns: vec![(SortBy::Name, 0), (SortBy::Created, 3), (SortBy::Size, 4)],
+                    extras: 1,
+                    ..Default::default()
+                }
+            }
+            Listing::Containers(containers, policies) => Table {
+                header: header(&[
+                    "CONTAINER ID",
+                    "IMAGE",
+                    "COMMAND",
+                    "CREATED",
+                    "STATUS",
+                    "HEALTH",
+                    "RESTART",
+                    "PORTS",
+                    "NAMES",
+                    "MOUNTS",
+                    "NETWORKS",
+                    "SIZE",
+                ]),
+                rows: containers
+                    .iter()
+                    .map(|container| {
+                        let id = container.id.clone().unwrap_or_default();
+                        let names = container
+                            .names
+                            .iter()
+                            .flatten()
+                            .map(|name| name.trim_start_matches('/'))
+                            .collect::<Vec<&str>>()
+                            .join(",");
+                        let image = container.image.clone().unwrap_or_default();
+                        let command = container.command.clone().unwrap_or_default();
+                        let created = time_ago(container.created.unwrap_or_default());
+                        let status = container.status.clone().unwrap_or_default();
+                        let port_list = container.ports.as_deref().unwrap_or_default();
+                        let mut networks: Vec<String> = container
+                            .network_settings
+                            .iter()
+                            .flat_map(|settings| settings.networks.iter().flatten())
+                            .map(|(network, _)| network.clone())
+                            .collect();
+                        networks.sort();
+                        let health = health(container);
+                        let policy = policies.get(&id).cloned().unwrap_or_default();
+                        let labels = container.labels.clone().unwrap_or_default();
+                        // like `docker ps --size`, counted only when asked for
+                        let size = match (container.size_rw, container.size_root_fs) {
+                            (Some(written), Some(root)) => Some(format!(
+                                "{} (virtual {})",
+                                human_size(written),
+                                human_size(root)
+                            )),
+                            _ => None,
+                        };
+                        Row {
+                            cells: vec![
+                                short_id(&id),
+                                image.clone(),
+                                format!("\"{command}\""),
+                                created.clone(),
+                                status.clone(),
+                                health.clone().unwrap_or_default(),
+                                policy.clone(),
+                                ports(port_list),
+                                names.clone(),
+                                container
+                                    .mounts
+                                    .iter()
+                                    .flatten()
+                                    .filter_map(|mount| mount.destination.clone())
+                                    .collect::<Vec<String>>()
+                                    .join(","),
+                                networks.join(","),
+                                size.clone().unwrap_or_default(),
+                            ],
+                            details: vec![
+                                ("Name", vec![names.clone()]),
+                                ("Id", vec![id.clone()]),
+                                ("Image", vec![image]),
+                                (
+                                    "State",
+                                    vec![container
+                                        .state
+                                        .map(|state| state.to_string())
+                                        .unwrap_or_default()],
+                                ),
+                                ("Status", vec![status]),
+                                ("Health", health.into_iter().collect()),
+                                ("Restart Policy", vec![policy]),
+                                ("Created", vec![created]),
+                                ("Command", vec![command]),
+                                ("Ports", port_list.iter().map(port).collect()),
+                                (
+                                    "Mounts",
+                                    container.mounts.iter().flatten().map(mount).collect(),
+                                ),
+                                ("Networks", networks),
+                                ("Size", size.into_iter().collect()),
+                                ("Labels", label_list(&labels)),
+                            ],
+                            state: container.state.map(|state| state.to_string()),
+                            labels,
+                            keys: SortKeys {
+                                name: Some(names),
+                                // by what the container wrote, the image layers are shared
+                                size: container.size_rw,
+                                created: container.created,
+                                // by state, the status text starts with "Up 2 hours"
+                                status: container.state.map(|state| state.to_string()),
+                            },
+                            id,
+                        }
+                    })
+                    .collect(),
+                columns: vec![
+                    (SortBy::Created, 3),
+                    (SortBy::Status, 4),
+                    (SortBy::Name, 8),
+                    (SortBy::Size, 11),
+                ],
+                extras: 3,
+                ..Default::default()
+            },
+            Listing::Volumes(volumes) => {
+                // only known when asked for, e.g. for the unused ones
+                let sized = volumes.iter().any(|volume| volume.usage_data.is_some());
+                let mut columns = vec!["DRIVER", "VOLUME NAME"];
+                if sized {
+                    columns.push("SIZE");
+                }
+                columns.push("MOUNTPOINT");
+                Table {
+                    header: header(&columns),
+                    rows: volumes
+                        .iter()
+                        .map(|volume| {
+                            let size = volume.usage_data.as_ref().map(|usage| usage.size);
+                            let mut cells = vec![volume.driver.clone(), volume.name.clone()];
+                            if sized {
+                                cells.push(size.map(human_size).unwrap_or_default());
+                            }
+                            cells.push(volume.mountpoint.clone());
+                            Row {
+                                id: volume.name.clone(),
+                                cells,
+                                details: vec![
+                                    ("Name", vec![volume.name.clone()]),
+                                    ("Driver", vec![volume.driver.clone()]),
+                                    ("Mountpoint", vec![volume.mountpoint.clone()]),
+                                    (
+                                        "Scope",
+                                        volume
+                                            .scope
+                                            .iter()
+                                            .map(|scope| scope.to_string())
+                                            .collect(),
+                                    ),
+                                    ("Size", size.map(human_size).into_iter().collect()),
+                                    ("Labels", label_list(&volume.labels)),
+                                ],
+                                state: None,
+                                labels: volume.labels.clone(),
+                                keys: SortKeys {
+                                    name: Some(volume.name.clone()),
+                                    size,
+                                    ..Default::default()
+                                },
+                            }
+                        })
+                        .collect(),
+                    columns: match sized {
+                        true => vec![(SortBy::Name, 1), (SortBy::Size, 2)],
+                        false => vec![(SortBy::Name, 1)],
+                    },
+                    extras: 1,
+                    ..Default::default()
+                }
+            }
+            Listing::Projects(projects) => Table {
+                header: header(&["NAME", "STATUS", "SERVICES", "CONFIG FILES"]),
+                rows: projects
+                    .iter()
+                    .map(|project| Row {
+                        id: project.name.clone(),
+                        cells: vec![
+                            project.name.clone(),
+                            project.status(),
+                            project.services.join(","),
+                            project.config_files.join(","),
+                        ],
+                        details: vec![
+                            ("Name", vec![project.name.clone()]),
+                            ("Status", vec![project.status()]),
+                            ("Services", project.services.clone()),
+                            ("Config files", project.config_files.clone()),
+                        ],
+                        state: None,
+                        labels: project.labels(),
+                        keys: SortKeys {
+                            name: Some(project.name.clone()),
+                            status: Some(project.status()),
+                            ..Default::default()
+                        },
+                    })
+                    .collect(),
+                columns: vec![(SortBy::Name, 0), (SortBy::Status, 1)],
+                ..Default::default()
+            },
+            Listing::Usage(usage) => {
+                let mut rows = vec![];
+                // (type, total, active, size, reclaimable) as reported by the daemon
+                let kinds = [
+                    usage.image_usage.as_ref().map(|u| {
+                        (
+                            "Images",
+                            u.total_count,
+                            u.active_count,
+                            u.total_size,
+                            u.reclaimable,
+                        )
+                    }),
+                    usage.container_usage.as_ref().map(|u| {
+                        (
+                            "Containers",
+                            u.total_count,
+                            u.active_count,
+                            u.total_size,
+                            u.reclaimable,
+                        )
+                    }),
+                    usage.volume_usage.as_ref().map(|u| {
+                        (
+                            "Local Volumes",
+                            u.total_count,
+                            u.active_count,
+                            u.total_size,
+                            u.reclaimable,
+                        )
+                    }),
+                    usage.build_cache_usage.as_ref().map(|u| {
+                        (
+                            "Build Cache",
+                            u.total_count,
+                            u.active_count,
+                            u.total_size,
+                            u.reclaimable,
+                        )
+                    }),
+                ];
+                kinds
+                    .into_iter()
+                    .flatten()
+                    .for_each(|(kind, total, active, size, reclaimable)| {
+                        let size = size.unwrap_or_default();
+                        let reclaimable = reclaimable.unwrap_or_default();
+                        let percent = match size > 0 {
+                            true => reclaimable * 100 / size,
+                            false => 0,
+                        };
+                        rows.push(Row {
+                            id: String::from(kind),
+                            cells: vec![
+                                String::from(kind),
+                                total.unwrap_or_default().to_string(),
+                                active.unwrap_or_default().to_string(),
+                                human_size(size),
+                                format!("{} ({percent}%)", human_size(reclaimable)),
+                            ],
+                            details: vec![],
+                            state: None,
+                            labels: HashMap::new(),
+                            keys: SortKeys {
+                                name: Some(String::from(kind)),
+                                size: Some(size),
+                                ..Default::default()
+                            },
+                        });
+                    });
+                Table {
+                    header: header(&["TYPE", "TOTAL", "ACTIVE", "SIZE", "RECLAIMABLE"]),
+                    rows,
+                    columns: vec![(SortBy::Name, 0), (SortBy::Size, 3)],
+                    ..Default::default()
+                }
+            }
+            Listing::Search(results) => Table {
+                header: header(&["NAME", "DESCRIPTION", "STARS", "OFFICIAL"]),
+                rows: results
+                    .iter()
+                    .map(|result| {
+                        let name = result.name.clone().unwrap_or_default();
+                        let description = result.description.clone().unwrap_or_default();
+                        let stars = result.star_count.unwrap_or_default();
+                        let official = match result.is_official {
+                            Some(true) => String::from("[OK]"),
+                            _ => String::new(),
+                        };
+                        Row {
+                            id: name.clone(),
+                            cells: vec![
+                                name.clone(),
+                                truncate(&description, 45),
+                                stars.to_string(),
+                                official.clone(),
+                            ],
+                            details: vec![
+                                ("Name", vec![name.clone()]),
+                                ("Description", vec![description]),
+                                ("Stars", vec![stars.to_string()]),
+                                ("Official", vec![official]),
+                            ],
+                            state: None,
+                            labels: HashMap::new(),
+                            keys: SortKeys {
+                                name: Some(name),
+                                ..Default::default()
+                            },
+                        }
+                    })
+                    .collect(),
+                columns: vec![(SortBy::Name, 0)],
+                ..Default::default()
+            },
+            Listing::History(layers) => Table {
+                header: header(&["IMAGE", "CREATED", "SIZE", "CREATED BY", "COMMENT"]),
+                rows: layers
+                    .iter()
+                    .map(|layer| {
+                        // layers built elsewhere aren't kept by id, docker shows them like this
+                        let id = match layer.id.as_str() {
+                            "" | "<missing>" => String::from("<missing>"),
+                            id => short_id(id),
+                        };
+                        let created_by = created_by(&layer.created_by);
+                        Row {
+                            id: layer.id.clone(),
+                            cells: vec![
+                                id,
+                                time_ago(layer.created),
+                                human_size(layer.size),
+                                created_by.clone(),
+                                layer.comment.clone(),
+                            ],
+                            details: vec![
+                                ("Id", vec![layer.id.clone()]),
+                                ("Created by", vec![created_by.clone()]),
+                                ("Tags", layer.tags.clone()),
+                            ],
+                            state: None,
+                            labels: HashMap::new(),
+                            keys: SortKeys {
+                                name: Some(created_by),
+                                size: Some(layer.size),
+                                created: Some(layer.created),
+                                status: None,
+                            },
+                        }
+                    })
+                    .collect(),
+                columns: vec![(SortBy::Created, 1), (SortBy::Size, 2)],
+                ..Default::default()
+            },
+            Listing::Registries(registries) => Table {
+                header: header(&["REGISTRY", "USERNAME", "STORE"]),
+                rows: registries
+                    .iter()
+                    .map(|registry| {
+                        let username = registry.username.clone().unwrap_or_default();
+                        Row {
+                            id: registry.server.clone(),
+                            cells: vec![
+                                registry.server.clone(),
+                                username.clone(),
+                                registry.store.clone(),
+                            ],
+                            details: vec![
+                                ("Registry", vec![registry.server.clone()]),
+                                ("Username", vec![username]),
+                                ("Store", vec![registry.store.clone()]),
+                            ],
+                            state: None,
+                            labels: HashMap::new(),
+                            keys: SortKeys {
+                                name: Some(registry.server.clone()),
+                                ..Default::default()
+                            },
+                        }
+                    })
+                    .collect(),
+                columns: vec![(SortBy::Name, 0)],
+                ..Default::default()
+            },
+            Listing::Services(services) => Table {
+                header: header(&["ID", "NAME", "MODE", "REPLICAS", "IMAGE", "PORTS"]),
+                rows: services
+                    .iter()
+                    .map(|service| {
+                        let id = service.id.clone().unwrap_or_default();
+                        let spec = service.spec.clone().unwrap_or_default();
+                        let name = spec.name.clone().unwrap_or_default();
+                        let image = spec
+                            .task_template
+                            .and_then(|task| task.container_spec)
+                            .and_then(|container| container.image)
+                            .unwrap_or_default();
+                        // pinned by digest once deployed, the CLI leaves it out too
+                        let image = image.split('@').next().unwrap_or_default().to_string();
+                        let mode = service_mode(&spec.mode.unwrap_or_default());
+                        let replicas = replicas(service);
+                        let port_list = service
+                            .endpoint
+                            .as_ref()
+                            .and_then(|endpoint| endpoint.ports.clone())
+                            .unwrap_or_default();
+                        let published: Vec<String> = port_list.iter().map(published).collect();
+                        let labels = spec.labels.unwrap_or_default();
+                        Row {
+                            id: name.clone(),
+                            cells: vec![
+                                short_id(&id),
+                                name.clone(),
+                                String::from(mode),
+                                replicas.clone(),
+                                image.clone(),
+                                published.join(", "),
+                            ],
+                            details: vec![
+                                ("Name", vec![name.clone()]),
+                                ("Id", vec![id]),
+                                ("Mode", vec![String::from(mode)]),
+                                ("Replicas", vec![replicas.clone()]),
+                                ("Image", vec![image]),
+                                ("Ports", published),
+                                ("Labels", label_list(&labels)),
+                            ],
+                            state: None,
+                            labels,
+                            keys: SortKeys {
+                                name: Some(name),
+                                status: Some(replicas),
+                                ..Default::default()
+                            },
+                        }
+                    })
+                    .collect(),
+                columns: vec![(SortBy::Name, 1), (SortBy::Status, 3)],
+                ..Default::default()
+            },
+            Listing::Stacks(stacks) => Table {
+                header: header(&["NAME", "SERVICES"]),
+                rows: stacks
+                    .iter()
+                    .map(|stack| Row {
+                        id: stack.name.clone(),
+                        cells: vec![stack.name.clone(), stack.services.len().to_string()],
+                        details: vec![
+                            ("Name", vec![stack.name.clone()]),
+                            ("Services", stack.services.clone()),
+                        ],
+                        state: None,
+                        labels: HashMap::from([(
+                            String::from(NAMESPACE_LABEL),
+                            stack.name.clone(),
+                        )]),
+                        keys: SortKeys {
+                            name: Some(stack.name.clone()),
+                            ..Default::default()
+                        },
+                    })
+                    .collect(),
+                columns: vec![(SortBy::Name, 0)],
+                ..Default::default()
+            },
+            Listing::Secrets(secrets) => Table {
+                header: header(&["ID", "NAME", "DRIVER", "CREATED", "UPDATED"]),
+                rows: secrets
+                    .iter()
+                    .map(|secret| {
+                        let id = secret.id.clone().unwrap_or_default();
+                        let spec = secret.spec.clone().unwrap_or_default();
+                        let name = spec.name.unwrap_or_default();
+                        let driver = spec.driver.map(|driver| driver.name).unwrap_or_default();
+                        let created = secret.created_at.as_deref().map(date).unwrap_or_default();
+                        let updated = secret.updated_at.as_deref().map(date).unwrap_or_default();
+                        let labels = spec.labels.unwrap_or_default();
+                        Row {
+                            cells: vec![
+                                short_id(&id),
+                                name.clone(),
+                                driver.clone(),
+                                created.clone(),
+                                updated.clone(),
+                            ],
+                            details: vec![
+                                ("Name", vec![name.clone()]),
+                                ("Id", vec![id.clone()]),
+                                ("Driver", vec![driver]),
+                                ("Created", vec![created]),
+                                ("Updated", vec![updated]),
+                                ("Labels", label_list(&labels)),
+                            ],
+                            state: None,
+                            labels,
+                            keys: SortKeys {
+                                name: Some(name),
+                                ..Default::default()
+                            },
+                            id,
+                        }
+                    })
+                    .collect(),
+                columns: vec![(SortBy::Name, 1)],
+                ..Default::default()
+            },
+            Listing::Configs(configs) => Table {
+                header: header(&["ID", "NAME", "CREATED", "UPDATED"]),
+                rows: configs
+                    .iter()
+                    .map(|config| {
+                        let id = config.id.clone().unwrap_or_default();
+                        let spec = config.spec.clone().unwrap_or_default();
+                        let name = spec.name.unwrap_or_default();
+                        let created = config.created_at.as_deref().map(date).unwrap_or_default();
+                        let updated = config.updated_at.as_deref().map(date).unwrap_or_default();
+                        let labels = spec.labels.unwrap_or_default();
+                        Row {
+                            cells: vec![
+                                short_id(&id),
+                                name.clone(),
+                                created.clone(),
+                                updated.clone(),
+                            ],
+                            details: vec![
+                                ("Name", vec![name.clone()]),
+                                ("Id", vec![id.clone()]),
+                                ("Created", vec![created]),
+                                ("Updated", vec![updated]),
+                                ("Labels", label_list(&labels)),
+                                ("Data", preview(spec.data.as_deref().unwrap_or_default())),
+                            ],
+                            state: None,
+                            labels,
+                            keys: SortKeys {
+                                name: Some(name),
+                                ..Default::default()
+                            },
+                            id,
+                        }
+                    })
+                    .collect(),
+                columns: vec![(SortBy::Name, 1)],
+                ..Default::default()
+            },
+            Listing::Fleet(hosts) => Table {
+                header: header(&["HOST", "CONTAINER ID", "IMAGE", "STATUS", "NAMES"]),
+                rows: hosts
+                    .iter()
+                    .flat_map(|(host, listed)| match listed {
+                        Ok(containers) => containers
+                            .iter()
+                            .map(|container| fleet_row(host, container))
+                            .collect(),
+                        // a row of its own, so a host that's down doesn't go unnoticed
+                        Err(err) => vec![Row {
+                            id: host.clone(),
+                            cells: vec![
+                                host.clone(),
+                                String::new(),
+                                String::new(),
+                                format!("unreachable: {err}"),
+                                String::new(),
+                            ],
+                            details: vec![
+                                ("Host", vec![host.clone()]),
+                                ("Error", vec![err.clone()]),
+                            ],
+                            state: None,
+                            labels: HashMap::from([(String::from(HOST_LABEL), host.clone())]),
+                            keys: SortKeys {
+                                name: Some(host.clone()),
+                                ..Default::default()
+                            },
+                        }],
+                    })
+                    .collect(),
+                columns: vec![(SortBy::Status, 3), (SortBy::Name, 4)],
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// A container of the fleet tab, its id is the host and the container id, e.g. `vps/4f2a…`.
+fn fleet_row(host: &str, container: &ContainerSummary) -> Row {
+    let id = container.id.clone().unwrap_or_default();
+    let names = container
+        .names
+        .iter()
+        .flatten()
+        .map(|name| name.trim_start_matches('/'))
+        .collect::<Vec<&str>>()
+        .join(",");
+    let image = container.image.clone().unwrap_or_default();
+    let status = container.status.clone().unwrap_or_default();
+    let state = container.state.map(|state| state.to_string());
+    let mut labels = container.labels.clone().unwrap_or_default();
+    labels.insert(String::from(HOST_LABEL), String::from(host));
+    Row {
+        cells: vec![
+            String::from(host),
+            short_id(&id),
+            image.clone(),
+            status.clone(),
+            names.clone(),
+        ],
+        details: vec![
+            ("Host", vec![String::from(host)]),
+            ("Name", vec![names.clone()]),
+            ("Id", vec![id.clone()]),
+            ("Image", vec![image]),
+            ("Status", vec![status]),
+            ("Labels", label_list(&labels)),
+        ],
+        state: state.clone(),
+        labels,
+        keys: SortKeys {
+            name: Some(names),
+            created: container.created,
+            status: state,
+            ..Default::default()
+        },
+        id: format!("{host}/{id}"),
+    }
+}
+
+/// The first lines of base64 `data` when it's text, only its size when it isn't.
+fn preview(data: &str) -> Vec<String> {
+    let Ok(bytes) = STANDARD.decode(data) else {
+        return vec![];
+    };
+    let size = bytes.len();
+    match String::from_utf8(bytes) {
+        Ok(text) if !text.contains('\0') => {
+            let mut lines: Vec<String> =
+                text.lines().take(PREVIEW_LINES).map(String::from).collect();
+            let more = text.lines().count().saturating_sub(PREVIEW_LINES);
+            if more > 0 {
+                lines.push(format!("… {more} more lines"));
+            }
+            lines
+        }
+        _ => vec![format!("<binary, {}>", human_size(size as i64))],
+    }
+}
+
+/// How the tasks of a service are scheduled, named like `docker service ls` does.
+fn service_mode(mode: &ServiceSpecMode) -> &'static str {
+    match mode {
+        ServiceSpecMode {
+            global: Some(_), ..
+        } => "global",
+        ServiceSpecMode {
+            replicated_job: Some(_),
+            ..
+        } => "replicated job",
+        ServiceSpecMode {
+            global_job: Some(_),
+            ..
+        } => "global job",
+        _ => "replicated",
+    }
+}
+
+/// Running tasks out of the desired ones, e.g. `2/3`, known when listed with their status.
+fn replicas(service: &Service) -> String {
+    match &service.service_status {
+        Some(status) => format!(
+            "{}/{}",
+            status.running_tasks.unwrap_or_default(),
+            status.desired_tasks.unwrap_or_default()
+        ),
+        None => String::new(),
+    }
+}
+
+/// `*:8080->80/tcp`, published on every node of the swarm.
+fn published(port: &EndpointPortConfig) -> String {
+    let protocol = port
+        .protocol
+        .map(|protocol| protocol.to_string())
+        .unwrap_or_default();
+    format!(
+        "*:{}->{}/{protocol}",
+        port.published_port.unwrap_or_default(),
+        port.target_port.unwrap_or_default()
+    )
+}
+
+/// The width of every column, the widest of its cells.
+fn widths(rows: &[Vec<String>]) -> Vec<usize> {
+    let mut widths: Vec<usize> = vec![];
+    rows.iter().for_each(|row| {
+        row.iter().enumerate().for_each(|(index, cell)| {
+            let width = cell.chars().count();
+            match widths.get_mut(index) {
+                Some(max) => *max = (*max).max(width),
+                None => widths.push(width),
+            }
+        })
+    });
+    widths
+}
+
+/// The kind a `docker system df` row stands for.
+pub fn usage_kind(row: &str) -> Option<DockerKind> {
+    USAGE_KINDS
+        .iter()
+        .find(|(name, _)| *name == row)
+        .map(|(_, kind)| *kind)
+}
+
+pub fn align(rows: Vec<Vec<String>>) -> Vec<String> {
+    let widths = widths(&rows);
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .map(|(index, cell)| format!("{:width$}", cell, width = widths[index]))
+                .collect::<Vec<String>>()
+                .join("   ")
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+/// The healthcheck state of a container, `None` when it defines no healthcheck.
+/// Engines before API 1.52 only tell it in the status text, e.g. `Up 2 hours (healthy)`.
+fn health(container: &ContainerSummary) -> Option<String> {
+    let status = container.status.as_deref().unwrap_or_default();
+    let health = match container.health.as_ref().and_then(|health| health.status) {
+        Some(status) => status.to_string(),
+        None if status.ends_with("(health: starting)") => String::from("starting"),
+        None if status.ends_with("(unhealthy)") => String::from("unhealthy"),
+        None if status.ends_with("(healthy)") => String::from("healthy"),
+        None => return None,
+    };
+    match health.as_str() {
+        "" | "none" => None,
+        _ => Some(health),
+    }
+}
+
+pub fn short_id(id: &str) -> String {
+    id.trim_start_matches("sha256:").chars().take(12).collect()
+}
+
+/// The Dockerfile step a layer came from, the classic builder wraps every step in a shell.
+pub fn created_by(command: &str) -> String {
+    let step = match command.strip_prefix("/bin/sh -c ") {
+        Some(rest) => match rest.strip_prefix("#(nop) ") {
+            Some(step) => String::from(step),
+            None => format!("RUN {rest}"),
+        },
+        None => String::from(command),
+    };
+    step.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    match text.chars().count() > max {
+        true => format!("{}…", text.chars().take(max - 1).collect::<String>()),
+        false => String::from(text),
+    }
+}
+
+fn ports(ports: &[PortSummary]) -> String {
+    ports.iter().map(port).collect::<Vec<String>>().join(", ")
+}
+
+fn port(port: &PortSummary) -> String {
+    let typ = port.typ.map(|typ| typ.to_string()).unwrap_or_default();
+    match (&port.ip, port.public_port) {
+        (Some(ip), Some(public)) => format!("{ip}:{public}->{}/{typ}", port.private_port),
+        _ => format!("{}/{typ}", port.private_port),
+    }
+}
+
+/// `volume -> /data (ro)`, bind mounts show their host path instead of a name.
+fn mount(mount: &MountPoint) -> String {
+    let source = mount
+        .name
+        .as_deref()
+        .or(mount.source.as_deref())
+        .unwrap_or_default();
+    let destination = mount.destination.as_deref().unwrap_or_default();
+    match mount.rw {
+        Some(false) => format!("{source} -> {destination} (ro)"),
+        _ => format!("{source} -> {destination}"),
+    }
+}
+
+/// `key=value`, sorted by key since the engine keeps no order.
+fn label_list(labels: &HashMap<String, String>) -> Vec<String> {
+    let mut list: Vec<String> = labels
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect();
+    list.sort();
+    list
+}
+
+/// Whether the labels satisfy every comma or space separated requirement of `selector`,
+/// like `docker ps --filter label=…` takes them: `key=value`, `key!=value` or just `key`.
+pub fn selects(labels: &HashMap<String, String>, selector: &str) -> bool {
+    selector
+        .split([',', ' '])
+        .filter(|requirement| !requirement.is_empty())
+        .all(|requirement| {
+            if let Some((key, value)) = requirement.split_once("!=") {
+                return labels.get(key).map(String::as_str) != Some(value);
+            }
+            match requirement.split_once('=') {
+                Some((key, value)) => labels.get(key).map(String::as_str) == Some(value),
+                None => labels.contains_key(requirement),
+            }
+        })
+}
+
+/// Decimal units, matching the sizes printed by the docker CLI.
+pub fn human_size(bytes: i64) -> String {
+    let units = ["B", "kB", "MB", "GB", "TB"];
+    let mut size = bytes.max(0) as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < units.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{}{}", size, units[unit]),
+        _ => {
+            format!("{:.3}", size)
+                .chars()
+                .take(4)
+                .collect::<String>()
+                .trim_end_matches('.')
+                .to_string()
+                + units[unit]
+        }
+    }
+}
+
+/// `HH:MM:SS` in UTC, for things that happened this session.
+pub fn clock(timestamp: i64) -> String {
+    let seconds = timestamp.rem_euclid(86400);
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// `2024-05-01 10:22` out of the RFC 3339 timestamps swarm objects carry, in UTC.
+fn date(timestamp: &str) -> String {
+    timestamp
+        .chars()
+        .take(16)
+        .collect::<String>()
+        .replace('T', " ")
+}
+
+fn time_ago(timestamp: i64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default();
+    let seconds = (now - timestamp).max(0);
+    let (amount, unit) = match seconds {
+        s if s < 60 => (s, "second"),
+        s if s < 3600 => (s / 60, "minute"),
+        s if s < 86400 => (s / 3600, "hour"),
+        s if s < 86400 * 7 => (s / 86400, "day"),
+        s if s < 86400 * 30 => (s / (86400 * 7), "week"),
+        s if s < 86400 * 365 => (s / (86400 * 30), "month"),
+        s => (s / (86400 * 365), "year"),
+    };
+    match amount {
+        1 => format!("1 {unit} ago"),
+        _ => format!("{amount} {unit}s ago"),
+    }
+}
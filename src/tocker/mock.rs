@@ -0,0 +1,142 @@
+use std::{
+    collections::HashMap,
+    io::Error,
+    sync::{Arc, Mutex},
+};
+
+use super::{
+    backend::DockerBackend,
+    worker::{Events, Progress},
+    DockerCommand, DockerKind, DockerPrompt, LogOptions, Output, Report, Table, Watch,
+};
+
+/// A listing kept by the mock, rows of cells with the id first.
+#[derive(Clone, Default)]
+struct Listing {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+/// A daemon kept in memory, to drive tocker in tests: listings are made up per kind,
+/// `rm` removes from them and every command is recorded by its CLI line.
+#[derive(Clone, Default)]
+pub struct MockBackend {
+    listings: Arc<Mutex<HashMap<DockerKind, Listing>>>,
+    ran: Arc<Mutex<Vec<String>>>,
+    /// what every command fails with, like a daemon that went away
+    failing: Option<String>,
+}
+
+impl MockBackend {
+    /// Lists `rows` under `header` for `kind`, the first cell of a row is its id.
+    pub fn listing(self, kind: DockerKind, header: &[&str], rows: &[&[&str]]) -> MockBackend {
+        let listing = Listing {
+            header: header.iter().map(|cell| String::from(*cell)).collect(),
+            rows: rows
+                .iter()
+                .map(|row| row.iter().map(|cell| String::from(*cell)).collect())
+                .collect(),
+        };
+        self.listings.lock().unwrap().insert(kind, listing);
+        self
+    }
+
+    /// Fails every command with `why` from now on, they're still recorded.
+    pub fn failing(self, why: &str) -> MockBackend {
+        MockBackend {
+            failing: Some(String::from(why)),
+            ..self
+        }
+    }
+
+    /// The commands run so far, oldest first, e.g. `docker container rm web`.
+    pub fn ran(&self) -> Vec<String> {
+        self.ran.lock().unwrap().clone()
+    }
+
+    /// The ids `kind` lists right now.
+    pub fn ids(&self, kind: DockerKind) -> Vec<String> {
+        self.listings
+            .lock()
+            .unwrap()
+            .get(&kind)
+            .map(|listing| {
+                listing
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.first().cloned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn table(&self, kind: DockerKind) -> Table {
+        let listings = self.listings.lock().unwrap();
+        let listing = listings.get(&kind).cloned().unwrap_or_default();
+        let header: Vec<&str> = listing.header.iter().map(String::as_str).collect();
+        Table::plain(&header, &listing.rows)
+    }
+
+    /// Removes the targets listed, a line for each like the daemon would answer.
+    fn remove(&self, kind: DockerKind, targets: &str) -> Report {
+        let mut listings = self.listings.lock().unwrap();
+        let listing = listings.entry(kind).or_default();
+        let mut output = Report::default();
+        for target in targets.split_whitespace() {
+            let before = listing.rows.len();
+            listing
+                .rows
+                .retain(|row| row.first().map(String::as_str) != Some(target));
+            match listing.rows.len() < before {
+                true => output.lines.push(format!("Removed {target}")),
+                false => output.fail(format!("No such object: {target}")),
+            }
+        }
+        output
+    }
+}
+
+impl DockerBackend for MockBackend {
+    fn host(&self) -> &str {
+        "mock://"
+    }
+
+    async fn ping(&self) -> Result<(), Error> {
+        match &self.failing {
+            Some(why) => Err(Error::other(why.clone())),
+            None => Ok(()),
+        }
+    }
+
+    async fn execute(&self, cmd: DockerPrompt, _: &mut Progress) -> Result<Output, Error> {
+        self.ran.lock().unwrap().push(cmd.line());
+        if let Some(why) = &self.failing {
+            return Err(Error::other(why.clone()));
+        }
+        match cmd.command {
+            DockerCommand::LS | DockerCommand::RECLAIMABLE => {
+                Ok(Output::Table(self.table(cmd.kind)))
+            }
+            DockerCommand::RM => Ok(Output::Ran(self.remove(cmd.kind, &cmd.target))),
+            _ => Ok(Output::Lines(vec![cmd.line()])),
+        }
+    }
+
+    async fn watch(&self, watch: Watch) -> Result<Output, Error> {
+        if let Some(why) = &self.failing {
+            return Err(Error::other(why.clone()));
+        }
+        match watch {
+            Watch::Listing(kind) | Watch::Reclaimable(kind) => Ok(Output::Table(self.table(kind))),
+            Watch::Stats | Watch::Top(_) | Watch::Ping => Ok(Output::Lines(vec![])),
+        }
+    }
+
+    async fn events(&self, _: &mut Events) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn logs(&self, _: &str, _: LogOptions, _: &mut Events) -> Result<(), Error> {
+        Ok(())
+    }
+}
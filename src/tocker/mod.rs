@@ -1,55 +1,271 @@
+mod backend;
+mod cli;
+mod compose;
+mod config;
+mod context;
+mod edit;
+mod engine;
+mod layers;
+mod listing;
+mod mock;
+mod progress;
+mod registry;
+mod session;
+mod stack;
+mod stats;
+mod volume;
+mod worker;
+
 use std::{
-    collections::HashMap,
-    ffi::OsString,
+    collections::{BTreeMap, HashMap},
+    env, fmt,
     io::{Error, ErrorKind},
-    process::{exit, Command, ExitStatus, Output, Stdio},
+    process::Command,
+    time::Duration,
 };
 
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use tokio::runtime::{Builder, Runtime};
+use tokio_util::sync::CancellationToken;
 
-#[derive(Debug)]
+pub use backend::DockerBackend;
+use config::{key_for, key_label, Custom, Macro, TlsConfig};
+pub use config::{Colors, Config, ThemeConfig};
+pub use context::Context;
+pub use edit::BuildFile;
+use engine::Engine;
+pub use layers::{Change, Layer};
+pub use listing::{clock, human_size, selects, usage_kind, Row, RowFilter, SortBy, Table};
+pub use mock::MockBackend;
+pub use session::{Session, View};
+pub use volume::VolumeFile;
+use worker::{Job, Reply, Update, Worker};
+
+const STATS_RATE: Duration = Duration::from_secs(1);
+/// How often the daemon is pinged, to tell soon after it's gone and when it's back.
+const HEARTBEAT_RATE: Duration = Duration::from_secs(3);
+/// How long a context's daemon gets to answer before staying on the current one.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Log lines a pinned container starts out with.
+pub const PINNED_TAIL: usize = 50;
+/// Log lines the log viewer starts out with, unless the config says otherwise.
+const VIEWED_TAIL: usize = 1000;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Moment {
     KIND,
     COMMAND,
     TARGET,
+    INPUT,
+    STATS,
+    DETAIL,
+    CONFIRM,
+    FILTER,
+    SORT,
+    HISTORY,
+    LAYERS,
+    SIGNAL,
+    FORM,
+    BROWSE,
+    POLICY,
+    CONTEXT,
+    LOGS,
 }
 
-#[derive(PartialEq, Eq, Hash, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum DockerKind {
     Image,
     Container,
     Volume,
+    Project,
+    System,
+    Registry,
+    /// swarm services, only there when the daemon is a swarm manager
+    Service,
+    /// the services deployed together by `docker stack deploy`
+    Stack,
+    /// swarm secrets, their values never come back from the daemon
+    Secret,
+    /// swarm configs, unlike secrets their data can be read back
+    Config,
+    /// the containers of every daemon under `[hosts]` in the config, side by side
+    Fleet,
 }
 
-impl From<&DockerKind> for OsString {
-    fn from(value: &DockerKind) -> Self {
-        match value {
-            DockerKind::Image => OsString::from("image"),
-            DockerKind::Container => OsString::from("container"),
-            DockerKind::Volume => OsString::from("volume"),
-        }
-    }
-}
-
-#[derive(PartialEq, Eq, Hash, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum DockerCommand {
     LS,
     RM,
     TAG,
     STOP,
+    EXEC,
+    STATS,
+    UP,
+    DOWN,
+    RESTART,
+    LOGS,
+    PULL,
+    BUILD,
+    PAUSE,
+    UNPAUSE,
+    INSPECT,
+    PRUNE,
+    DF,
+    /// what a prune of the kind would remove, picked from the disk usage
+    RECLAIMABLE,
+    /// a docker command line as edited before running it
+    LINE,
+    OPEN,
+    CP,
+    PUSH,
+    LOGIN,
+    LOGOUT,
+    SEARCH,
+    HISTORY,
+    LAYERS,
+    TOP,
+    RENAME,
+    KILL,
+    UPDATE,
+    HEALTH,
+    BROWSE,
+    CREATE,
+    SAVE,
+    LOAD,
+    EXPORT,
+    IMPORT,
+    DANGLING,
+    POLICY,
+    ENV,
+    /// the Dockerfile or compose file behind a row, in `$EDITOR`
+    EDIT,
+    /// the logs of a container followed in a side pane
+    PIN,
+    /// the tasks of a service, like `docker service ps`
+    PS,
+    SCALE,
+    /// a compose file deployed as a stack
+    DEPLOY,
+    /// one of the user's commands from the config, by its position there
+    CUSTOM(usize),
+}
+
+/// The `docker compose` subcommands run against a whole project.
+#[derive(Clone, Copy, Debug)]
+pub enum ComposeCommand {
+    UP,
+    DOWN,
+    RESTART,
+    LOGS,
 }
 
-impl From<&DockerCommand> for OsString {
-    fn from(value: &DockerCommand) -> Self {
+impl TryFrom<DockerCommand> for ComposeCommand {
+    type Error = Error;
+
+    fn try_from(value: DockerCommand) -> Result<Self, Self::Error> {
         match value {
-            DockerCommand::LS => OsString::from("ls"),
-            DockerCommand::RM => OsString::from("rm"),
-            DockerCommand::TAG => OsString::from("tag"),
-            DockerCommand::STOP => OsString::from("stop"),
+            DockerCommand::UP => Ok(ComposeCommand::UP),
+            DockerCommand::DOWN => Ok(ComposeCommand::DOWN),
+            DockerCommand::RESTART => Ok(ComposeCommand::RESTART),
+            DockerCommand::LOGS => Ok(ComposeCommand::LOGS),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Not a docker compose command",
+            )),
+        }
+    }
+}
+
+/// What a docker job hands back: a listing to pick targets from, or plain output.
+#[derive(Debug)]
+pub enum Output {
+    Table(Table),
+    Lines(Vec<String>),
+    /// what a command run on its targets printed, and which of them failed
+    Ran(Report),
+    /// the layers of an image, to browse their files
+    Layers(Vec<Layer>),
+    /// everything inside a volume, to browse it
+    Files(Vec<VolumeFile>),
+}
+
+impl Output {
+    pub fn lines(self) -> Vec<String> {
+        match self {
+            Output::Table(table) => table.lines(),
+            Output::Lines(lines) => lines,
+            Output::Ran(report) => report.lines,
+            Output::Layers(layers) => layers.iter().flat_map(Layer::lines).collect(),
+            Output::Files(files) => files.iter().map(VolumeFile::line).collect(),
+        }
+    }
+
+    /// What the docker CLI would exit with, 1 when any target failed like `docker rm` does.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Output::Ran(report) => !report.failures.is_empty() as i32,
+            _ => 0,
+        }
+    }
+}
+
+/// The output of a command, every failure told in its lines as well as kept apart, so
+/// what it exits with doesn't depend on what the lines say.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub lines: Vec<String>,
+    /// why each target that failed did, e.g. the daemon's error
+    pub failures: Vec<String>,
+}
+
+impl Report {
+    /// A target failed for `why`, told as an `Error:` line like the docker CLI prints it.
+    pub fn fail(&mut self, why: impl fmt::Display) {
+        let why = why.to_string();
+        self.lines.push(format!("Error: {why}"));
+        self.failures.push(why);
+    }
+
+    /// Adds what another command printed, its failures with it.
+    pub fn extend(&mut self, other: Report) {
+        self.lines.extend(other.lines);
+        self.failures.extend(other.failures);
+    }
+}
+
+impl From<Vec<String>> for Report {
+    fn from(lines: Vec<String>) -> Report {
+        Report {
+            lines,
+            failures: vec![],
         }
     }
 }
 
+/// Views refreshed in place by the worker until cancelled.
+#[derive(Clone, Debug)]
+pub enum Watch {
+    Stats,
+    Listing(DockerKind),
+    /// only what a prune of the kind would remove
+    Reclaimable(DockerKind),
+    /// the processes running in the container
+    Top(String),
+    /// the daemon answering at all, to tell when it's gone and when it's back
+    Ping,
+}
+
+/// How much of a container's logs is followed and how its lines read.
+#[derive(Clone, Debug)]
+pub struct LogOptions {
+    /// lines printed before the logs were asked for, the newest ones
+    pub tail: usize,
+    /// every line starts with when it was printed, like `docker logs --timestamps`
+    pub timestamps: bool,
+    /// only lines printed since that long ago
+    pub since: Option<Duration>,
+}
+
 #[derive(Debug)]
 struct AllowedCommands {
     mapping: HashMap<DockerKind, Vec<DockerCommand>>,
@@ -60,23 +276,58 @@ struct AllowedCommands {
 pub enum TargetType {
     INPUT,
     SELECT,
+    /// rows are picked first, then the rest is typed, e.g. the new name of a container
+    SELECTINPUT,
+    CONFIRM,
     EMPTY,
 }
 
 #[derive(Debug)]
-pub struct DockerPrompt<'a> {
-    pub kind: &'a DockerKind,
-    pub command: &'a DockerCommand,
-    pub target: &'a String,
+pub struct DockerPrompt {
+    pub kind: DockerKind,
+    pub command: DockerCommand,
+    pub target: String,
+    /// typed apart from the target, so it never shows up in the history
+    pub secret: Option<Secret>,
 }
 
-#[derive(Debug)]
+impl DockerPrompt {
+    /// The CLI equivalent, the secret left out.
+    pub fn line(&self) -> String {
+        command_line(&self.kind, &self.command, &self.target)
+    }
+}
+
+/// A password or token, kept out of debug output.
+pub struct Secret(pub String);
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(***)")
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum GeneralCommand {
     QUIT,
     CANCEL,
     HELP,
     CLEAN,
-    // BUILD,
+    BUILD,
+    SPLIT,
+    TAB,
+    HISTORY,
+    EVENTS,
+    FIND,
+    CONTEXT,
+    /// starts recording the keys pressed, or stops to save them as a macro
+    RECORD,
+    /// every action searched by name
+    PALETTE,
+    /// what's on screen handed to `$PAGER`
+    PAGER,
+    /// one of the user's macros from the config, by its position there
+    MACRO(usize),
 }
 
 #[derive(Debug)]
@@ -87,39 +338,138 @@ pub enum Message {
     CANCEL,
     HELP,
     CLEAN,
+    BUILD,
+    SPLIT,
+    TAB,
+    HISTORY,
+    EVENTS,
+    FIND,
+    CONTEXT,
+    RECORD,
+    PALETTE,
+    PAGER,
+    MACRO(usize),
 }
 
-#[derive(Debug)]
+/// What's forwarded to the notifications of the desktop, when the config asks for it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Desktop {
+    /// a container exited, whichever it was
+    DIED,
+    BUILT,
+    PULLED,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Select {
     UP,
     DOWN,
     SELECT,
     CONFIRM,
     CANCEL,
+    FILTER,
+    NEXT,
+    PREV,
+    SORT,
+    COPY,
+    COPYNAME,
+    EXPORT,
+    ALL,
+    INVERT,
+    CLEAR,
+    PAGEUP,
+    PAGEDOWN,
+    FIRST,
+    LAST,
+    LABELS,
+    EDIT,
+    PREVTAB,
+    NEXTTAB,
+    COMMAND,
+    TRUNCATE,
+    DIGESTS,
+    FAVORITE,
+    WATCH,
 }
 
 pub struct Tocker {
+    worker: Worker,
     kind_keybindings: HashMap<KeyEvent, DockerKind>,
     command_keybindings: HashMap<KeyEvent, DockerCommand>,
     general_keybindings: HashMap<KeyEvent, GeneralCommand>,
     select_keybindings: HashMap<KeyEvent, Select>,
+    repeat_keybindings: HashMap<KeyEvent, DockerCommand>,
     target_mapping: HashMap<DockerCommand, TargetType>,
     allowed_commands: AllowedCommands,
-    help_string: String,
+    initial_string: String,
+    target_string: String,
+    exec_shell: String,
+    host: String,
+    /// the docker context picked in tocker, none until one is
+    context: Option<String>,
+    /// the daemons of the fleet tab and the TLS certs, kept for when another context
+    /// is picked
+    hosts: BTreeMap<String, String>,
+    tls: Option<TlsConfig>,
+    refresh: Option<Duration>,
+    /// how long a docker command may run before it's canceled
+    timeout: Option<Duration>,
+    theme: ThemeConfig,
+    custom: Vec<Custom>,
+    macros: Vec<Macro>,
+    /// the columns picked in the config, the default ones show for the other kinds
+    columns: HashMap<DockerKind, Vec<String>>,
+    /// image listings show the digest of each repository, toggled from the listing
+    digests: bool,
+    /// container listings show the stopped ones too, like `docker ps -a`
+    all: bool,
+    /// what's kept between runs, e.g. the favorites
+    session: Session,
+    /// forwarded to the notifications of the desktop
+    desktop: Vec<Desktop>,
+    /// what the log viewer opens with
+    logs: LogOptions,
 }
 
 impl Tocker {
-    pub fn new() -> Tocker {
-        let status = Command::new("docker")
-            .arg("info")
-            .stdout(Stdio::null())
-            .status()
-            .expect("Failed to contact deamon");
-        if !ExitStatus::success(&status) {
-            exit(1);
-        }
+    /// Runs against the daemon the environment and the config point to. Never exits or
+    /// panics, e.g. an invalid config or an endpoint that can't be used is returned.
+    pub fn new() -> Result<Tocker, Error> {
+        let runtime = Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(|err| {
+                Error::new(
+                    err.kind(),
+                    format!("Failed to start the docker runtime: {err}"),
+                )
+            })?;
+        let invalid = |err: Error| Error::new(err.kind(), format!("Invalid tocker config: {err}"));
+        let config = Config::load().map_err(invalid)?;
+        let tls = config.tls.as_ref();
+        let sizes = config.columns.iter().any(|(kind, picked)| {
+            config::kind(kind) == Some(DockerKind::Container) && picks(picked, "size")
+        });
+        let engine = {
+            let _guard = runtime.enter();
+            Engine::new(tls).map(|engine| engine.with_fleet(&config.hosts, tls).with_sizes(sizes))
+        };
+        // a daemon that's down is told in the TUI, where it's retried, only an endpoint
+        // that can't be used at all, e.g. missing TLS certs, is returned
+        let engine = engine
+            .map_err(|err| Error::new(err.kind(), format!("Can't use the docker daemon: {err}")))?;
+        Tocker::with_backend(runtime, engine, config, Session::load()).map_err(invalid)
+    }
 
-        let kind_keybindings = HashMap::from([
+    /// Runs every command against `backend` on `runtime`, e.g. a [`MockBackend`] in tests,
+    /// with the keybindings and settings of `config` and what `session` kept.
+    pub fn with_backend<B: DockerBackend>(
+        runtime: Runtime,
+        backend: B,
+        config: Config,
+        session: Session,
+    ) -> Result<Tocker, Error> {
+        let mut kind_keybindings = HashMap::from([
             (
                 KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE),
                 DockerKind::Image,
@@ -132,8 +482,40 @@ impl Tocker {
                 KeyEvent::new(KeyCode::Char('v'), KeyModifiers::NONE),
                 DockerKind::Volume,
             ),
+            (
+                KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE),
+                DockerKind::Project,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE),
+                DockerKind::System,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE),
+                DockerKind::Registry,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE),
+                DockerKind::Service,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE),
+                DockerKind::Stack,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE),
+                DockerKind::Secret,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE),
+                DockerKind::Config,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('m'), KeyModifiers::NONE),
+                DockerKind::Fleet,
+            ),
         ]);
-        let command_keybindings = HashMap::from([
+        let mut command_keybindings = HashMap::from([
             (
                 KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE),
                 DockerCommand::LS,
@@ -150,8 +532,160 @@ impl Tocker {
                 KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE),
                 DockerCommand::TAG,
             ),
+            (
+                KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE),
+                DockerCommand::EXEC,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('S'), KeyModifiers::NONE),
+                DockerCommand::STATS,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE),
+                DockerCommand::UP,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE),
+                DockerCommand::DOWN,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('R'), KeyModifiers::NONE),
+                DockerCommand::RESTART,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('L'), KeyModifiers::NONE),
+                DockerCommand::LOGS,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE),
+                DockerCommand::PULL,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('P'), KeyModifiers::NONE),
+                DockerCommand::PAUSE,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE),
+                DockerCommand::INSPECT,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE),
+                DockerCommand::PRUNE,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('U'), KeyModifiers::NONE),
+                DockerCommand::UNPAUSE,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE),
+                DockerCommand::OPEN,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE),
+                DockerCommand::CP,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE),
+                DockerCommand::PUSH,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE),
+                DockerCommand::LOGIN,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('G'), KeyModifiers::NONE),
+                DockerCommand::LOGOUT,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE),
+                DockerCommand::SEARCH,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('H'), KeyModifiers::NONE),
+                DockerCommand::HISTORY,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('D'), KeyModifiers::NONE),
+                DockerCommand::LAYERS,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('T'), KeyModifiers::NONE),
+                DockerCommand::TOP,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE),
+                DockerCommand::RENAME,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('K'), KeyModifiers::NONE),
+                DockerCommand::KILL,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('M'), KeyModifiers::NONE),
+                DockerCommand::UPDATE,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE),
+                DockerCommand::HEALTH,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('O'), KeyModifiers::NONE),
+                DockerCommand::POLICY,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE),
+                DockerCommand::ENV,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE),
+                DockerCommand::BROWSE,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('C'), KeyModifiers::NONE),
+                DockerCommand::CREATE,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('v'), KeyModifiers::NONE),
+                DockerCommand::SAVE,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('V'), KeyModifiers::NONE),
+                DockerCommand::LOAD,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('E'), KeyModifiers::NONE),
+                DockerCommand::EXPORT,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('I'), KeyModifiers::NONE),
+                DockerCommand::IMPORT,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('N'), KeyModifiers::NONE),
+                DockerCommand::DANGLING,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('w'), KeyModifiers::NONE),
+                DockerCommand::EDIT,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('F'), KeyModifiers::NONE),
+                DockerCommand::PIN,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('A'), KeyModifiers::NONE),
+                DockerCommand::PS,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('m'), KeyModifiers::NONE),
+                DockerCommand::SCALE,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE),
+                DockerCommand::DEPLOY,
+            ),
         ]);
-        let general_keybindings = HashMap::from([
+        let mut general_keybindings = HashMap::from([
             (
                 KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
                 GeneralCommand::CANCEL,
@@ -172,55 +706,231 @@ impl Tocker {
                 KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL),
                 GeneralCommand::CLEAN,
             ),
-            // (
-            //     KeyEvent::new(KeyCode::Char('b'), KeyModifiers::CONTROL),
-            //     GeneralCommand::BUILD,
-            // ),
+            (
+                KeyEvent::new(KeyCode::Char('b'), KeyModifiers::CONTROL),
+                GeneralCommand::BUILD,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL),
+                GeneralCommand::SPLIT,
+            ),
+            (
+                KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE),
+                GeneralCommand::TAB,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL),
+                GeneralCommand::HISTORY,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL),
+                GeneralCommand::EVENTS,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL),
+                GeneralCommand::FIND,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('o'), KeyModifiers::CONTROL),
+                GeneralCommand::CONTEXT,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL),
+                GeneralCommand::RECORD,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+                GeneralCommand::PALETTE,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL),
+                GeneralCommand::PAGER,
+            ),
         ]);
 
-        let help_string = String::from(
-            "[c/i/v] = container/image/volume; \n [ctrl+q] = quit; [ctrl+c] = cancel action; [ctrl+l] = clear content; [ctrl+b] build image from path",
-        );
-
-        let mapping = HashMap::from([
+        let mut mapping = HashMap::from([
             (
                 DockerKind::Image,
-                vec![DockerCommand::LS, DockerCommand::RM, DockerCommand::TAG],
+                vec![
+                    DockerCommand::LS,
+                    DockerCommand::RM,
+                    DockerCommand::DANGLING,
+                    DockerCommand::TAG,
+                    DockerCommand::PULL,
+                    DockerCommand::SEARCH,
+                    DockerCommand::PUSH,
+                    DockerCommand::SAVE,
+                    DockerCommand::LOAD,
+                    DockerCommand::IMPORT,
+                    DockerCommand::HISTORY,
+                    DockerCommand::LAYERS,
+                    DockerCommand::EDIT,
+                    DockerCommand::INSPECT,
+                    DockerCommand::PRUNE,
+                ],
             ),
             (
                 DockerKind::Container,
-                vec![DockerCommand::LS, DockerCommand::RM, DockerCommand::STOP],
+                vec![
+                    DockerCommand::LS,
+                    DockerCommand::RM,
+                    DockerCommand::STOP,
+                    DockerCommand::KILL,
+                    DockerCommand::UP,
+                    DockerCommand::RESTART,
+                    DockerCommand::PAUSE,
+                    DockerCommand::UNPAUSE,
+                    DockerCommand::EXEC,
+                    DockerCommand::STATS,
+                    DockerCommand::TOP,
+                    DockerCommand::LOGS,
+                    DockerCommand::PIN,
+                    DockerCommand::HEALTH,
+                    DockerCommand::ENV,
+                    DockerCommand::RENAME,
+                    DockerCommand::UPDATE,
+                    DockerCommand::POLICY,
+                    DockerCommand::INSPECT,
+                    DockerCommand::OPEN,
+                    DockerCommand::CP,
+                    DockerCommand::EXPORT,
+                    DockerCommand::PRUNE,
+                ],
             ),
             (
                 DockerKind::Volume,
-                vec![DockerCommand::LS, DockerCommand::RM],
+                vec![
+                    DockerCommand::LS,
+                    DockerCommand::RM,
+                    DockerCommand::CREATE,
+                    DockerCommand::BROWSE,
+                    DockerCommand::DANGLING,
+                    DockerCommand::INSPECT,
+                    DockerCommand::PRUNE,
+                ],
             ),
-        ]);
-        let legenda = HashMap::from([
             (
-                DockerKind::Image,
-                String::from("Available commands for image: \n l = ls, r = rm, t = tag"),
+                DockerKind::Project,
+                vec![
+                    DockerCommand::LS,
+                    DockerCommand::UP,
+                    DockerCommand::DOWN,
+                    DockerCommand::RESTART,
+                    DockerCommand::LOGS,
+                    DockerCommand::EDIT,
+                    DockerCommand::INSPECT,
+                ],
             ),
             (
-                DockerKind::Container,
-                String::from("Available commands for container: \n l = ls, r = rm, s = stop"),
+                DockerKind::System,
+                vec![DockerCommand::LS, DockerCommand::PRUNE],
             ),
             (
-                DockerKind::Volume,
-                String::from("Available commands for volume: \n l = ls, r = rm"),
+                DockerKind::Registry,
+                vec![
+                    DockerCommand::LS,
+                    DockerCommand::LOGIN,
+                    DockerCommand::LOGOUT,
+                ],
+            ),
+            (
+                DockerKind::Service,
+                vec![
+                    DockerCommand::LS,
+                    DockerCommand::PS,
+                    DockerCommand::SCALE,
+                    DockerCommand::UPDATE,
+                    DockerCommand::RM,
+                    DockerCommand::INSPECT,
+                ],
+            ),
+            (
+                DockerKind::Stack,
+                vec![
+                    DockerCommand::LS,
+                    DockerCommand::PS,
+                    DockerCommand::DEPLOY,
+                    DockerCommand::RM,
+                ],
+            ),
+            (
+                DockerKind::Secret,
+                vec![
+                    DockerCommand::LS,
+                    DockerCommand::CREATE,
+                    DockerCommand::RM,
+                    DockerCommand::INSPECT,
+                ],
+            ),
+            (
+                DockerKind::Config,
+                vec![
+                    DockerCommand::LS,
+                    DockerCommand::CREATE,
+                    DockerCommand::RM,
+                    DockerCommand::INSPECT,
+                ],
+            ),
+            (
+                DockerKind::Fleet,
+                vec![
+                    DockerCommand::LS,
+                    DockerCommand::UP,
+                    DockerCommand::STOP,
+                    DockerCommand::RESTART,
+                    DockerCommand::INSPECT,
+                ],
             ),
         ]);
-
-        let allowed_commands = AllowedCommands { mapping, legenda };
-
-        let target_mapping = HashMap::from([
+        let mut target_mapping = HashMap::from([
             (DockerCommand::RM, TargetType::SELECT),
             (DockerCommand::STOP, TargetType::SELECT),
             (DockerCommand::LS, TargetType::EMPTY),
             (DockerCommand::TAG, TargetType::INPUT),
+            (DockerCommand::EXEC, TargetType::SELECT),
+            (DockerCommand::STATS, TargetType::EMPTY),
+            (DockerCommand::UP, TargetType::SELECT),
+            (DockerCommand::DOWN, TargetType::SELECT),
+            (DockerCommand::RESTART, TargetType::SELECT),
+            (DockerCommand::LOGS, TargetType::SELECT),
+            (DockerCommand::PULL, TargetType::INPUT),
+            (DockerCommand::PAUSE, TargetType::SELECT),
+            (DockerCommand::INSPECT, TargetType::SELECT),
+            (DockerCommand::PRUNE, TargetType::CONFIRM),
+            (DockerCommand::UNPAUSE, TargetType::SELECT),
+            (DockerCommand::OPEN, TargetType::SELECT),
+            (DockerCommand::CP, TargetType::INPUT),
+            (DockerCommand::PUSH, TargetType::SELECT),
+            (DockerCommand::LOGIN, TargetType::INPUT),
+            (DockerCommand::SEARCH, TargetType::INPUT),
+            (DockerCommand::HISTORY, TargetType::SELECT),
+            (DockerCommand::LAYERS, TargetType::SELECT),
+            (DockerCommand::TOP, TargetType::SELECT),
+            (DockerCommand::HEALTH, TargetType::SELECT),
+            (DockerCommand::ENV, TargetType::SELECT),
+            (DockerCommand::BROWSE, TargetType::SELECT),
+            (DockerCommand::CREATE, TargetType::INPUT),
+            (DockerCommand::SAVE, TargetType::SELECTINPUT),
+            (DockerCommand::LOAD, TargetType::INPUT),
+            (DockerCommand::EXPORT, TargetType::SELECTINPUT),
+            (DockerCommand::IMPORT, TargetType::INPUT),
+            (DockerCommand::DANGLING, TargetType::SELECT),
+            (DockerCommand::RENAME, TargetType::SELECTINPUT),
+            (DockerCommand::KILL, TargetType::SELECTINPUT),
+            (DockerCommand::UPDATE, TargetType::SELECTINPUT),
+            (DockerCommand::POLICY, TargetType::SELECTINPUT),
+            (DockerCommand::LOGOUT, TargetType::SELECT),
+            (DockerCommand::EDIT, TargetType::SELECT),
+            (DockerCommand::PIN, TargetType::SELECT),
+            (DockerCommand::PS, TargetType::SELECT),
+            (DockerCommand::SCALE, TargetType::SELECTINPUT),
+            (DockerCommand::DEPLOY, TargetType::INPUT),
         ]);
 
-        let select_keybindings = HashMap::from([
+        // shell started by exec, e.g. TOCKER_SHELL=bash
+        let exec_shell = env::var("TOCKER_SHELL").unwrap_or(String::from("sh"));
+
+        let mut select_keybindings = HashMap::from([
             (KeyEvent::new(KeyCode::Up, KeyModifiers::NONE), Select::UP),
             (
                 KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE),
@@ -246,28 +956,292 @@ impl Tocker {
                 KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
                 Select::CANCEL,
             ),
+            (
+                KeyEvent::new(KeyCode::Char('/'), KeyModifiers::NONE),
+                Select::FILTER,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('#'), KeyModifiers::NONE),
+                Select::LABELS,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE),
+                Select::EDIT,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE),
+                Select::NEXT,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('N'), KeyModifiers::NONE),
+                Select::PREV,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE),
+                Select::SORT,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE),
+                Select::COPY,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('Y'), KeyModifiers::SHIFT),
+                Select::COPYNAME,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('w'), KeyModifiers::NONE),
+                Select::EXPORT,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE),
+                Select::ALL,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT),
+                Select::INVERT,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE),
+                Select::INVERT,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE),
+                Select::CLEAR,
+            ),
+            (
+                KeyEvent::new(KeyCode::PageUp, KeyModifiers::NONE),
+                Select::PAGEUP,
+            ),
+            (
+                KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE),
+                Select::PAGEDOWN,
+            ),
+            (
+                KeyEvent::new(KeyCode::Home, KeyModifiers::NONE),
+                Select::FIRST,
+            ),
+            (
+                KeyEvent::new(KeyCode::End, KeyModifiers::NONE),
+                Select::LAST,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE),
+                Select::TRUNCATE,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('@'), KeyModifiers::NONE),
+                Select::DIGESTS,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('*'), KeyModifiers::NONE),
+                Select::FAVORITE,
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('!'), KeyModifiers::NONE),
+                Select::WATCH,
+            ),
         ]);
 
-        Tocker {
-            kind_keybindings,
-            command_keybindings,
-            general_keybindings,
-            select_keybindings,
-            target_mapping,
-            allowed_commands,
-            help_string,
-        }
-    }
+        let mut repeat_keybindings = HashMap::new();
 
-    pub fn extract_key_event(&self, e: Event) -> Result<KeyEvent, Error> {
-        match e {
-            Event::Key(key_event) => Ok(key_event),
-            _ => Err(Error::new(ErrorKind::InvalidInput, "Press a valid key")),
+        // the preset, then the user config, remap on top of the defaults
+        let preset = config.preset()?;
+        for layer in [&preset, &config] {
+            config::rebind(&mut kind_keybindings, &layer.kind, config::kind)?;
+            config::rebind(&mut command_keybindings, &layer.command, config::command)?;
+            config::rebind(&mut general_keybindings, &layer.general, config::general)?;
+            config::rebind(&mut select_keybindings, &layer.select, config::select)?;
+            config::rebind(&mut repeat_keybindings, &layer.repeat, config::command)?;
         }
-    }
-
-    pub fn check_select(&self, event: KeyEvent) -> Result<&Select, Error> {
-        self.select_keybindings.get(&event).ok_or(Error::new(
+        // custom commands take keys nothing else is bound to, so no command goes missing
+        for (index, custom) in config.custom.iter().enumerate() {
+            let invalid = |what: &str| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{what} of custom command {} in config", custom.name),
+                )
+            };
+            let kind = config::kind(&custom.kind).ok_or_else(|| invalid("Unknown kind"))?;
+            let target = config::target(&custom.target).ok_or_else(|| invalid("Unknown target"))?;
+            let key = config::parse_key(&custom.key)?;
+            if command_keybindings.contains_key(&key) {
+                return Err(invalid("Key already bound"));
+            }
+            let command = DockerCommand::CUSTOM(index);
+            command_keybindings.insert(key, command);
+            mapping.entry(kind).or_default().push(command);
+            target_mapping.insert(command, target);
+        }
+        // macros as well, on keys free of anything they'd replay
+        for (index, recorded) in config.macros.iter().enumerate() {
+            let key = config::parse_key(&recorded.key)?;
+            recorded.keys()?;
+            if general_keybindings.contains_key(&key)
+                || kind_keybindings.contains_key(&key)
+                || command_keybindings.contains_key(&key)
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Key already bound of macro {} in config", recorded.name),
+                ));
+            }
+            general_keybindings.insert(key, GeneralCommand::MACRO(index));
+        }
+        for name in config.columns.keys() {
+            if config::kind(name).is_none() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unknown kind of columns in config: {name}"),
+                ));
+            }
+        }
+        for name in &config.desktop {
+            if config::desktop(name).is_none() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unknown desktop notification in config: {name}"),
+                ));
+            }
+        }
+        if let Some(since) = &config.logs.since {
+            parse_since(since).map_err(|_| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid logs since in config: {since}"),
+                )
+            })?;
+        }
+        let columns: HashMap<DockerKind, Vec<String>> = config
+            .columns
+            .iter()
+            .filter_map(|(name, columns)| Some((config::kind(name)?, columns.clone())))
+            .collect();
+        let desktop = config
+            .desktop
+            .iter()
+            .filter_map(|name| config::desktop(name))
+            .collect();
+        let logs = LogOptions {
+            tail: config.logs.tail.unwrap_or(VIEWED_TAIL),
+            timestamps: false,
+            since: config
+                .logs
+                .since
+                .as_deref()
+                .and_then(|since| parse_since(since).ok()),
+        };
+        // picked in the config, the digests show until toggled off
+        let digests = columns
+            .get(&DockerKind::Image)
+            .is_some_and(|picked| picks(picked, "digest"));
+
+        // legends are built from the bindings, so they follow the remaps
+        let legenda = mapping
+            .iter()
+            .map(|(kind, commands)| {
+                let commands = commands
+                    .iter()
+                    .map(|command| {
+                        let key = key_for(&command_keybindings, command);
+                        let label = match command {
+                            DockerCommand::CUSTOM(index) => config.custom[*index].name.clone(),
+                            _ => command_label(kind, command),
+                        };
+                        format!("{key} = {label}")
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                let legend = format!("Available commands for {}: \n {commands}", kind_label(kind));
+                (*kind, legend)
+            })
+            .collect();
+        let allowed_commands = AllowedCommands { mapping, legenda };
+
+        let initial_string = format!(
+            "Available commands: \n press {}.",
+            [
+                DockerKind::Image,
+                DockerKind::Container,
+                DockerKind::Volume,
+                DockerKind::Project,
+                DockerKind::System,
+                DockerKind::Registry,
+                DockerKind::Service,
+                DockerKind::Stack,
+                DockerKind::Secret,
+                DockerKind::Config,
+                DockerKind::Fleet,
+            ]
+            .iter()
+            .map(|kind| format!(
+                "'{}' = {}",
+                key_for(&kind_keybindings, kind),
+                kind_label(kind)
+            ))
+            .collect::<Vec<String>>()
+            .join(", ")
+        );
+        let target_string = format!(
+            "Available commands: \n press '{}' = select, '{}' = confirm, '{}'/'{}' = filter by text/labels, '{}'/'{}' = next/previous, '{}' = sort, '{}'/'{}' = copy id/name, '{}' = export, '{}'/'{}'/'{}' = all/invert/none, '{}' = full/truncated names, '{}' = edit the command line first",
+            key_for(&select_keybindings, &Select::SELECT),
+            key_for(&select_keybindings, &Select::CONFIRM),
+            key_for(&select_keybindings, &Select::FILTER),
+            key_for(&select_keybindings, &Select::LABELS),
+            key_for(&select_keybindings, &Select::NEXT),
+            key_for(&select_keybindings, &Select::PREV),
+            key_for(&select_keybindings, &Select::SORT),
+            key_for(&select_keybindings, &Select::COPY),
+            key_for(&select_keybindings, &Select::COPYNAME),
+            key_for(&select_keybindings, &Select::EXPORT),
+            key_for(&select_keybindings, &Select::ALL),
+            key_for(&select_keybindings, &Select::INVERT),
+            key_for(&select_keybindings, &Select::CLEAR),
+            key_for(&select_keybindings, &Select::TRUNCATE),
+            key_for(&select_keybindings, &Select::EDIT),
+        );
+
+        let host = String::from(backend.host());
+        let worker = Worker::start(runtime, backend);
+
+        Ok(Tocker {
+            worker,
+            kind_keybindings,
+            command_keybindings,
+            general_keybindings,
+            select_keybindings,
+            repeat_keybindings,
+            target_mapping,
+            allowed_commands,
+            initial_string,
+            target_string,
+            exec_shell,
+            host,
+            context: None,
+            refresh: config.refresh_every(),
+            timeout: config.timeout_after(),
+            theme: config.theme,
+            custom: config.custom,
+            macros: config.macros,
+            columns,
+            digests,
+            all: true,
+            session,
+            desktop,
+            logs,
+            hosts: config.hosts,
+            tls: config.tls,
+        })
+    }
+
+    pub fn extract_key_event(&self, e: Event) -> Result<KeyEvent, Error> {
+        match e {
+            Event::Key(key_event) => Ok(key_event),
+            _ => Err(Error::new(ErrorKind::InvalidInput, "Press a valid key")),
+        }
+    }
+
+    pub fn check_select(&self, event: KeyEvent) -> Result<&Select, Error> {
+        self.select_keybindings.get(&event).ok_or(Error::new(
             ErrorKind::InvalidInput,
             "Invalid key input for selection",
         ))
@@ -280,7 +1254,17 @@ impl Tocker {
                 GeneralCommand::CANCEL => Ok(Message::CANCEL),
                 GeneralCommand::HELP => Ok(Message::HELP),
                 GeneralCommand::CLEAN => Ok(Message::CLEAN),
-                // GeneralCommand::BUILD => Ok(Message::BUILD),
+                GeneralCommand::BUILD => Ok(Message::BUILD),
+                GeneralCommand::SPLIT => Ok(Message::SPLIT),
+                GeneralCommand::TAB => Ok(Message::TAB),
+                GeneralCommand::HISTORY => Ok(Message::HISTORY),
+                GeneralCommand::EVENTS => Ok(Message::EVENTS),
+                GeneralCommand::FIND => Ok(Message::FIND),
+                GeneralCommand::CONTEXT => Ok(Message::CONTEXT),
+                GeneralCommand::RECORD => Ok(Message::RECORD),
+                GeneralCommand::PALETTE => Ok(Message::PALETTE),
+                GeneralCommand::PAGER => Ok(Message::PAGER),
+                GeneralCommand::MACRO(index) => Ok(Message::MACRO(*index)),
             },
             None => match moment {
                 Moment::KIND => match self.kind_keybindings.get(event) {
@@ -291,6 +1275,20 @@ impl Tocker {
                     Some(_) => Ok(Message::OK),
                     None => Ok(Message::WRONG),
                 },
+                Moment::STATS => Ok(Message::WRONG),
+                Moment::INPUT
+                | Moment::DETAIL
+                | Moment::CONFIRM
+                | Moment::FILTER
+                | Moment::SORT
+                | Moment::HISTORY
+                | Moment::LAYERS
+                | Moment::SIGNAL
+                | Moment::POLICY
+                | Moment::CONTEXT
+                | Moment::FORM
+                | Moment::BROWSE
+                | Moment::LOGS => Ok(Message::OK),
                 Moment::TARGET => Err(Error::new(
                     ErrorKind::InvalidInput,
                     "Input should not be considered as commands",
@@ -299,8 +1297,222 @@ impl Tocker {
         }
     }
 
-    pub fn get_help_commands(&self) -> &String {
-        &self.help_string
+    /// Every binding grouped by when it applies, straight from the maps so remaps show.
+    pub fn help_sections(&self) -> Vec<(&'static str, Vec<(String, String)>)> {
+        let (custom, commands): (HashMap<KeyEvent, DockerCommand>, _) = self
+            .command_keybindings
+            .iter()
+            .partition(|(_, command)| matches!(command, DockerCommand::CUSTOM(_)));
+        let (macros, general): (HashMap<KeyEvent, GeneralCommand>, _) = self
+            .general_keybindings
+            .iter()
+            .partition(|(_, command)| matches!(command, GeneralCommand::MACRO(_)));
+        let mut sections = vec![
+            ("Kind", config::legend(&self.kind_keybindings)),
+            ("Command", config::legend(&commands)),
+            ("Select", config::legend(&self.select_keybindings)),
+            ("General", config::legend(&general)),
+        ];
+        if !self.repeat_keybindings.is_empty() {
+            sections.push(("Twice", config::legend(&self.repeat_keybindings)));
+        }
+        if !custom.is_empty() {
+            let mut legend: Vec<(String, String)> = custom
+                .iter()
+                .filter_map(|(key, command)| match command {
+                    DockerCommand::CUSTOM(index) => {
+                        Some((key_label(key), self.custom[*index].name.clone()))
+                    }
+                    _ => None,
+                })
+                .collect();
+            legend.sort();
+            sections.push(("Custom", legend));
+        }
+        if !macros.is_empty() {
+            let mut legend: Vec<(String, String)> = macros
+                .iter()
+                .filter_map(|(key, command)| match command {
+                    GeneralCommand::MACRO(index) => {
+                        Some((key_label(key), self.macros[*index].name.clone()))
+                    }
+                    _ => None,
+                })
+                .collect();
+            legend.sort();
+            sections.push(("Macros", legend));
+        }
+        sections
+    }
+
+    /// Every action by what it does, e.g. `remove containers`, with the keys that run it,
+    /// the commands of every kind, the general ones and the macros.
+    pub fn palette(&self) -> Vec<(String, Vec<KeyEvent>)> {
+        let mut actions: Vec<(String, Vec<KeyEvent>)> = self
+            .allowed_commands
+            .mapping
+            .iter()
+            .flat_map(|(kind, commands)| {
+                commands.iter().filter_map(move |command| {
+                    let (first, second) = self.keys(*kind, *command)?;
+                    let label = match command {
+                        DockerCommand::CUSTOM(index) => {
+                            format!("{} {}", self.custom[*index].name, kind_plural(kind))
+                        }
+                        _ => format!("{} {}", action_label(kind, command), kind_plural(kind)),
+                    };
+                    Some((label, vec![first, second]))
+                })
+            })
+            .collect();
+        let mut general: Vec<(&KeyEvent, &GeneralCommand)> =
+            self.general_keybindings.iter().collect();
+        // the same key every time for actions bound to several
+        general.sort_by_key(|(key, _)| key_label(key));
+        general.iter().for_each(|(key, command)| {
+            let label = match command {
+                GeneralCommand::MACRO(index) => {
+                    format!("replay macro {}", self.macros[*index].name)
+                }
+                _ => match general_label(command) {
+                    Some(label) => String::from(label),
+                    None => return,
+                },
+            };
+            if !actions.iter().any(|(known, _)| *known == label) {
+                actions.push((label, vec![**key]));
+            }
+        });
+        actions.sort_by(|a, b| a.0.cmp(&b.0));
+        actions
+            .into_iter()
+            .map(|(label, keys)| {
+                let pressed: Vec<String> = keys.iter().map(key_label).collect();
+                (format!("{label}  {}", pressed.join(" ")), keys)
+            })
+            .collect()
+    }
+
+    /// The kind and the command the keys pressed so far stand for, e.g. `container` and
+    /// `rm`, as far as they're known.
+    pub fn crumbs(&self, first: Option<&KeyEvent>, second: Option<&KeyEvent>) -> Vec<String> {
+        let Some(kind) = first.and_then(|first| self.kind_keybindings.get(first)) else {
+            return vec![];
+        };
+        let mut crumbs = vec![String::from(kind_label(kind))];
+        if let Some(command) = second.and_then(|second| self.command_keybindings.get(second)) {
+            crumbs.push(match command {
+                DockerCommand::CUSTOM(index) => self.custom[*index].name.clone(),
+                _ => command_label(kind, command),
+            });
+        }
+        crumbs
+    }
+
+    /// The keys a macro replays, as they were recorded.
+    pub fn macro_keys(&self, index: usize) -> Result<Vec<KeyEvent>, Error> {
+        self.macros
+            .get(index)
+            .ok_or(Error::new(ErrorKind::NotFound, "No such macro"))?
+            .keys()
+    }
+
+    /// Saves `keys` to the config as a macro named `name`, replayed from now on by `key`.
+    /// Keys the config can't name, e.g. `F1`, are left out.
+    pub fn save_macro(&mut self, name: &str, key: &str, keys: &[KeyEvent]) -> Result<(), Error> {
+        if name.is_empty() || self.macros.iter().any(|recorded| recorded.name == name) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Name the macro, a name no other macro has",
+            ));
+        }
+        let bound = config::parse_key(key)
+            .map_err(|_| Error::new(ErrorKind::InvalidInput, format!("Invalid key: {key}")))?;
+        if self.general_keybindings.contains_key(&bound)
+            || self.kind_keybindings.contains_key(&bound)
+            || self.command_keybindings.contains_key(&bound)
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{key} is bound already, pick another key"),
+            ));
+        }
+        let recorded = Macro {
+            name: String::from(name),
+            key: String::from(key),
+            keys: keys.iter().filter_map(config::key_name).collect(),
+        };
+        recorded.save()?;
+        self.general_keybindings
+            .insert(bound, GeneralCommand::MACRO(self.macros.len()));
+        self.macros.push(recorded);
+        Ok(())
+    }
+
+    /// The colors from the config, the theme picked with its overrides.
+    pub fn get_theme(&self) -> &ThemeConfig {
+        &self.theme
+    }
+
+    /// Whether the config forwards `event` to the notifications of the desktop.
+    pub fn notifies(&self, event: Desktop) -> bool {
+        self.desktop.contains(&event)
+    }
+
+    /// How much of a container's logs the log viewer opens with, from the config.
+    pub fn log_options(&self) -> LogOptions {
+        self.logs.clone()
+    }
+
+    /// The daemon everything runs against.
+    pub fn get_host(&self) -> &String {
+        &self.host
+    }
+
+    pub fn get_context(&self) -> Option<&String> {
+        self.context.as_ref()
+    }
+
+    pub fn contexts(&self) -> Result<Vec<Context>, Error> {
+        context::list()
+    }
+
+    /// Runs everything against `context` from now on, once its daemon answers. What was
+    /// queued or followed on the previous one stops with its worker.
+    pub fn use_context(&mut self, context: &Context) -> Result<(), Error> {
+        let runtime = Builder::new_multi_thread().enable_all().build()?;
+        let engine = {
+            let _guard = runtime.enter();
+            let tls = self.tls.as_ref();
+            let sizes = self
+                .columns
+                .get(&DockerKind::Container)
+                .is_some_and(|picked| picks(picked, "size"));
+            Engine::connect(&context.docker_endpoint, tls)?
+                .with_fleet(&self.hosts, tls)
+                .with_sizes(sizes)
+        };
+        runtime
+            .block_on(async { tokio::time::timeout(CONNECT_TIMEOUT, engine.ping()).await })
+            .map_err(|_| {
+                Error::new(
+                    ErrorKind::TimedOut,
+                    format!("{} did not answer", context.docker_endpoint),
+                )
+            })??;
+        context.activate();
+        self.host = String::from(engine.host());
+        self.context = Some(context.name.clone());
+        self.worker = Worker::start(runtime, engine);
+        Ok(())
+    }
+
+    pub fn get_initial_commands(&self) -> &String {
+        &self.initial_string
+    }
+
+    pub fn get_target_commands(&self) -> &String {
+        &self.target_string
     }
 
     pub fn get_available_commands(&self, key_event: &KeyEvent) -> Result<&String, Error> {
@@ -330,12 +1542,455 @@ impl Tocker {
         }
     }
 
-    pub fn exec_cmd(
+    /// The docker command a combination stands for, e.g. `docker container stop web`.
+    pub fn command_line(&self, first: &KeyEvent, second: &KeyEvent, target: &str) -> String {
+        let (Some(kind), Some(command)) = (
+            self.kind_keybindings.get(first),
+            self.command_keybindings.get(second),
+        ) else {
+            return String::from("docker");
+        };
+        command_line(kind, command, &self.expand(command, target))
+    }
+
+    /// What a command runs with, the lines of a custom command filled in with the target.
+    fn expand(&self, command: &DockerCommand, target: &str) -> String {
+        match command {
+            DockerCommand::CUSTOM(index) => self.custom[*index].lines(target).join("\n"),
+            _ => String::from(target),
+        }
+    }
+
+    /// What an `INPUT` command expects to be typed.
+    pub fn input_hint(&self, second: &KeyEvent) -> &'static str {
+        match self.command_keybindings.get(second) {
+            Some(DockerCommand::TAG) => {
+                "Type the source image and the new reference, e.g. 'app:latest app:v1'"
+            }
+            Some(DockerCommand::PULL) => "Type the image to pull, e.g. 'nginx:latest'",
+            Some(DockerCommand::RENAME) => "Type the new name, e.g. 'web-old'",
+            Some(DockerCommand::KILL) => "Type the signal, e.g. 'SIGUSR2' or '12'",
+            Some(DockerCommand::SCALE) => "Type the number of replicas, e.g. '3'",
+            Some(DockerCommand::POLICY) => {
+                "Type the restart policy, e.g. 'on-failure:5' to give up after 5 retries"
+            }
+            Some(DockerCommand::SEARCH) => "Type what to search Docker Hub for, e.g. 'postgres'",
+            Some(DockerCommand::LOGIN) => {
+                "Type the registry and your username, e.g. 'ghcr.io octocat', the password or token is asked next"
+            }
+            Some(DockerCommand::SAVE) => {
+                "Type the path of the archive to write, e.g. 'images.tar', 'tab' = complete"
+            }
+            Some(DockerCommand::LOAD) => {
+                "Type the path of the archive to load, e.g. 'images.tar', 'tab' = complete"
+            }
+            Some(DockerCommand::EXPORT) => {
+                "Type the path of the archive to write, e.g. 'web.tar', 'tab' = complete"
+            }
+            Some(DockerCommand::IMPORT) => {
+                "Type the path of the archive and optionally the new image, e.g. 'web.tar web:flat', 'tab' = complete"
+            }
+            Some(DockerCommand::DEPLOY) => {
+                "Type the compose file and the name of the stack, e.g. 'compose.yaml web', 'tab' = complete"
+            }
+            Some(DockerCommand::CP) => {
+                "Type the source and destination, 'container:path' on the container side, e.g. 'web:/etc/hosts ./hosts', 'tab' = complete"
+            }
+            _ => "Type the target",
+        }
+    }
+
+    /// Commands that need the terminal for themselves, to be run in the foreground.
+    pub fn interactive_cmd(&self, second: &KeyEvent, target: &str) -> Option<Command> {
+        match self.command_keybindings.get(second)? {
+            DockerCommand::EXEC => {
+                let container = target.split_whitespace().next()?;
+                Some(cli::exec(container, &self.exec_shell))
+            }
+            _ => None,
+        }
+    }
+
+    /// Commands whose output keeps refreshing instead of running once.
+    pub fn watch_cmd(&self, second: &KeyEvent, target: &str) -> Option<Watch> {
+        match self.command_keybindings.get(second)? {
+            DockerCommand::STATS => Some(Watch::Stats),
+            // one container at a time, like exec
+            DockerCommand::TOP => Some(Watch::Top(String::from(target.split_whitespace().next()?))),
+            _ => None,
+        }
+    }
+
+    /// The kind a combination lists, its output belongs in that kind's tab.
+    pub fn listing_cmd(&self, first: &KeyEvent, second: &KeyEvent) -> Option<DockerKind> {
+        match self.command_keybindings.get(second)? {
+            // the listing shows what was created
+            DockerCommand::LS | DockerCommand::CREATE => self.kind_keybindings.get(first).copied(),
+            _ => None,
+        }
+    }
+
+    /// Listings keep refreshing in the background, unless turned off in the config.
+    pub fn refresh(&self, kind: DockerKind, reclaimable: bool) -> Option<Watch> {
+        self.refresh.map(|_| match reclaimable {
+            true => Watch::Reclaimable(kind),
+            false => Watch::Listing(kind),
+        })
+    }
+
+    /// Commands whose input is picked from the common signals first.
+    pub fn signal_cmd(&self, second: &KeyEvent) -> bool {
+        matches!(
+            self.command_keybindings.get(second),
+            Some(DockerCommand::KILL)
+        )
+    }
+
+    /// Commands whose input is picked from the restart policies first.
+    pub fn policy_cmd(&self, second: &KeyEvent) -> bool {
+        matches!(
+            self.command_keybindings.get(second),
+            Some(DockerCommand::POLICY)
+        )
+    }
+
+    /// Commands whose input is a form of `name=value` settings.
+    pub fn form_cmd(&self, second: &KeyEvent) -> bool {
+        matches!(
+            self.command_keybindings.get(second),
+            Some(DockerCommand::UPDATE | DockerCommand::CREATE)
+        )
+    }
+
+    /// The title of the form a command is filled in with, then its fields: the setting
+    /// each one is sent as, a label and what it expects.
+    pub fn form_fields(
+        &self,
+        first: &KeyEvent,
+        second: &KeyEvent,
+    ) -> (
+        &'static str,
+        Vec<(&'static str, &'static str, &'static str)>,
+    ) {
+        match (
+            self.kind_keybindings.get(first),
+            self.command_keybindings.get(second),
+        ) {
+            // rolled out task by task, like `docker service update --image`
+            (Some(DockerKind::Service), _) => (
+                "Update services",
+                vec![("image", "Image", "e.g. nginx:1.27")],
+            ),
+            (Some(DockerKind::Secret), _) => (
+                "Create a secret",
+                vec![
+                    ("name", "Name", "e.g. db_password"),
+                    ("file", "File", "left empty, the value is typed next"),
+                    ("labels", "Labels", "e.g. team=web,env=dev"),
+                ],
+            ),
+            (Some(DockerKind::Config), _) => (
+                "Create a config",
+                vec![
+                    ("name", "Name", "e.g. nginx_conf"),
+                    ("file", "File", "e.g. ./nginx.conf"),
+                    ("labels", "Labels", "e.g. team=web,env=dev"),
+                ],
+            ),
+            (_, Some(DockerCommand::CREATE)) => (
+                "Create a volume",
+                vec![
+                    ("name", "Name", "left empty, docker picks one"),
+                    ("driver", "Driver", "local unless set"),
+                    ("options", "Driver options", "e.g. type=tmpfs,device=tmpfs"),
+                    ("labels", "Labels", "e.g. team=web,env=dev"),
+                ],
+            ),
+            _ => (
+                "Update containers",
+                vec![
+                    ("memory", "Memory", "e.g. 512m or 2g"),
+                    ("cpus", "CPUs", "e.g. 1.5"),
+                    (
+                        "restart",
+                        "Restart policy",
+                        "no, always, unless-stopped or on-failure:3",
+                    ),
+                ],
+            ),
+        }
+    }
+
+    /// Commands opening the file a row is built from in the editor, nothing runs on docker.
+    pub fn edit_cmd(&self, second: &KeyEvent) -> bool {
+        matches!(
+            self.command_keybindings.get(second),
+            Some(DockerCommand::EDIT)
+        )
+    }
+
+    /// Commands following the logs of a container in the side pane.
+    pub fn pin_cmd(&self, second: &KeyEvent) -> bool {
+        matches!(
+            self.command_keybindings.get(second),
+            Some(DockerCommand::PIN)
+        )
+    }
+
+    /// Commands opening the logs of a container in the log viewer, those of a project
+    /// print once.
+    pub fn logs_cmd(&self, first: &KeyEvent, second: &KeyEvent) -> bool {
+        matches!(
+            (
+                self.kind_keybindings.get(first),
+                self.command_keybindings.get(second)
+            ),
+            (Some(DockerKind::Container), Some(DockerCommand::LOGS))
+        )
+    }
+
+    /// Commands whose output opens in the layer explorer.
+    pub fn layers_cmd(&self, second: &KeyEvent) -> bool {
+        matches!(
+            self.command_keybindings.get(second),
+            Some(DockerCommand::LAYERS)
+        )
+    }
+
+    /// Commands whose output opens in the volume browser.
+    pub fn browse_cmd(&self, second: &KeyEvent) -> bool {
+        matches!(
+            self.command_keybindings.get(second),
+            Some(DockerCommand::BROWSE)
+        )
+    }
+
+    /// Commands whose results are picked from to pull them.
+    pub fn search_cmd(&self, second: &KeyEvent) -> bool {
+        matches!(
+            self.command_keybindings.get(second),
+            Some(DockerCommand::SEARCH)
+        )
+    }
+
+    /// The keys that pull images, to pick search results for.
+    pub fn pull_keys(&self) -> Option<(KeyEvent, KeyEvent)> {
+        self.keys(DockerKind::Image, DockerCommand::PULL)
+    }
+
+    /// The keys that remove dangling images or volumes, to pick them once listed.
+    pub fn dangling_keys(&self, kind: DockerKind) -> Option<(KeyEvent, KeyEvent)> {
+        self.keys(kind, DockerCommand::DANGLING)
+    }
+
+    /// The kind a first key stands for.
+    pub fn kind_of(&self, first: &KeyEvent) -> Option<DockerKind> {
+        self.kind_keybindings.get(first).copied()
+    }
+
+    /// The keys of the command bound to `key` pressed twice, on the tab on screen.
+    pub fn repeat_keys(
+        &self,
+        key: &KeyEvent,
+        active: Option<DockerKind>,
+    ) -> Option<(KeyEvent, KeyEvent)> {
+        let command = self.repeat_keybindings.get(key)?;
+        self.keys(active?, *command)
+    }
+
+    /// The keys of a command typed by its config name, `container rm web` or `rm web`
+    /// for the tab on screen, with the rest of the line as its target.
+    pub fn named(
+        &self,
+        line: &str,
+        active: Option<DockerKind>,
+    ) -> Result<(KeyEvent, KeyEvent, String), Error> {
+        let unknown = || Error::new(ErrorKind::InvalidInput, format!("Unknown command: {line}"));
+        let mut words = line.split_whitespace().peekable();
+        let kind = match words.peek().and_then(|word| config::kind(word)) {
+            Some(kind) => {
+                words.next();
+                kind
+            }
+            None => active.ok_or_else(unknown)?,
+        };
+        let command = words
+            .next()
+            .and_then(|word| {
+                config::command(word).or_else(|| {
+                    let index = self.custom.iter().position(|custom| custom.name == word)?;
+                    Some(DockerCommand::CUSTOM(index))
+                })
+            })
+            .ok_or_else(unknown)?;
+        let (first, second) = self.keys(kind, command).ok_or_else(unknown)?;
+        Ok((first, second, words.collect::<Vec<&str>>().join(" ")))
+    }
+
+    fn keys(&self, kind: DockerKind, command: DockerCommand) -> Option<(KeyEvent, KeyEvent)> {
+        let first = self
+            .kind_keybindings
+            .iter()
+            .find(|(_, bound)| **bound == kind)?;
+        let second = self
+            .command_keybindings
+            .iter()
+            .find(|(_, bound)| **bound == command)?;
+        Some((*first.0, *second.0))
+    }
+
+    /// Commands that list what they act on first, everything picked, and ask before.
+    pub fn dangling_cmd(&self, second: &KeyEvent) -> bool {
+        matches!(
+            self.command_keybindings.get(second),
+            Some(DockerCommand::DANGLING)
+        )
+    }
+
+    /// Commands that need a password, token or secret value typed after the target,
+    /// a secret is created from its file instead when it was given one.
+    pub fn secret_cmd(&self, first: &KeyEvent, second: &KeyEvent, target: &str) -> bool {
+        match (
+            self.kind_keybindings.get(first),
+            self.command_keybindings.get(second),
+        ) {
+            (_, Some(DockerCommand::LOGIN)) => true,
+            (Some(DockerKind::Secret), Some(DockerCommand::CREATE)) => !target
+                .split_whitespace()
+                .any(|setting| setting.starts_with("file=")),
+            _ => false,
+        }
+    }
+
+    /// What to type the secret of `target` in for, or why it can't be asked yet.
+    pub fn secret_hint(&self, second: &KeyEvent, target: &str) -> Result<String, Error> {
+        match self.command_keybindings.get(second) {
+            Some(DockerCommand::LOGIN) if target.split_whitespace().count() != 2 => {
+                Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Login needs a registry and a username",
+                ))
+            }
+            Some(DockerCommand::LOGIN) => Ok(format!("Type the password or token for '{target}'")),
+            _ => Ok(String::from("Type the value of the secret")),
+        }
+    }
+
+    /// Inputs naming host paths, completed with tab.
+    pub fn path_input(&self, second: &KeyEvent) -> bool {
+        matches!(
+            self.command_keybindings.get(second),
+            Some(
+                DockerCommand::CP
+                    | DockerCommand::DEPLOY
+                    | DockerCommand::SAVE
+                    | DockerCommand::LOAD
+                    | DockerCommand::EXPORT
+                    | DockerCommand::IMPORT
+            )
+        )
+    }
+
+    /// Commands whose output opens in the detail view instead of the list.
+    pub fn detail_cmd(&self, second: &KeyEvent) -> bool {
+        matches!(
+            self.command_keybindings.get(second),
+            Some(DockerCommand::INSPECT)
+        )
+    }
+
+    /// Starts refreshing `watch`, `update` gets every new output until the token is cancelled.
+    pub fn watch<F>(&self, watch: Watch, update: F) -> Result<CancellationToken, Error>
+    where
+        F: FnMut(Result<Output, Error>) + Send + 'static,
+    {
+        let every = match watch {
+            Watch::Stats | Watch::Top(_) => STATS_RATE,
+            Watch::Listing(_) | Watch::Reclaimable(_) => self.refresh.unwrap_or(STATS_RATE),
+            Watch::Ping => HEARTBEAT_RATE,
+        };
+        // listings show the columns picked in the config
+        let update: Update = match &watch {
+            Watch::Listing(kind) | Watch::Reclaimable(kind) => {
+                let columns = self.columns.get(kind).cloned();
+                let toggled = self.toggled(kind);
+                let running = self.running_only(kind) && matches!(watch, Watch::Listing(_));
+                let mut update = update;
+                Box::new(move |output: Result<Output, Error>| {
+                    let output = with_columns(output, columns.as_deref(), &toggled);
+                    update(output.map(|output| without_stopped(output, running)))
+                })
+            }
+            _ => Box::new(update),
+        };
+        self.worker.watch(watch, every, update)
+    }
+
+    /// Follows the container events, `each` gets them as lines until the token is cancelled.
+    pub fn events<F>(&self, each: F) -> Result<CancellationToken, Error>
+    where
+        F: FnMut(Result<String, Error>) + Send + 'static,
+    {
+        self.worker.events(Box::new(each))
+    }
+
+    /// Follows the logs of `container`, `each` gets them as lines until the token is cancelled.
+    pub fn logs<F>(
+        &self,
+        container: &str,
+        options: LogOptions,
+        each: F,
+    ) -> Result<CancellationToken, Error>
+    where
+        F: FnMut(Result<String, Error>) + Send + 'static,
+    {
+        self.worker.logs(container, options, Box::new(each))
+    }
+
+    pub fn exec_cmd<F, P>(
+        &self,
+        first: &KeyEvent,
+        second: &KeyEvent,
+        target: &str,
+        done: F,
+        progress: P,
+    ) -> Result<(), Error>
+    where
+        F: FnOnce(Result<Output, Error>) + Send + 'static,
+        P: FnMut(Vec<String>) + Send + 'static,
+    {
+        let kind = self
+            .kind_keybindings
+            .get(first)
+            .ok_or(Error::new(ErrorKind::InvalidInput, "Invalid kind input"))?;
+        let command = self
+            .command_keybindings
+            .get(second)
+            .ok_or(Error::new(ErrorKind::InvalidInput, "Invalid command input"))?;
+        let prompt = DockerPrompt {
+            kind: *kind,
+            command: *command,
+            target: self.expand(command, target),
+            secret: None,
+        };
+        self.docker_execute_prompt(prompt, done, progress)
+    }
+
+    /// Runs a command with its secret typed apart, e.g. a login where `target` is the
+    /// registry followed by the username.
+    pub fn secret_exec_cmd<F, P>(
         &self,
         first: &KeyEvent,
         second: &KeyEvent,
-        target: &String,
-    ) -> Result<Output, Error> {
+        target: &str,
+        secret: &str,
+        done: F,
+        progress: P,
+    ) -> Result<(), Error>
+    where
+        F: FnOnce(Result<Output, Error>) + Send + 'static,
+        P: FnMut(Vec<String>) + Send + 'static,
+    {
         let kind = self
             .kind_keybindings
             .get(first)
@@ -344,19 +1999,659 @@ impl Tocker {
             .command_keybindings
             .get(second)
             .ok_or(Error::new(ErrorKind::InvalidInput, "Invalid command input"))?;
+        let prompt = DockerPrompt {
+            kind: *kind,
+            command: *command,
+            target: String::from(target),
+            secret: Some(Secret(String::from(secret))),
+        };
+        self.docker_execute_prompt(prompt, done, progress)
+    }
+
+    /// A `ls` of `kind` without going through its keys, e.g. when opening its tab.
+    pub fn list_cmd<F>(&self, kind: DockerKind, done: F) -> Result<(), Error>
+    where
+        F: FnOnce(Result<Output, Error>) + Send + 'static,
+    {
         let prompt = DockerPrompt {
             kind,
-            command,
-            target,
+            command: DockerCommand::LS,
+            target: String::new(),
+            secret: None,
+        };
+        self.docker_execute_prompt(prompt, done, |_| {})
+    }
+
+    /// What `docker system df` reports for the kind, to confirm a prune against.
+    pub fn usage_cmd<F>(&self, first: &KeyEvent, done: F) -> Result<(), Error>
+    where
+        F: FnOnce(Result<Output, Error>) + Send + 'static,
+    {
+        let kind = self
+            .kind_keybindings
+            .get(first)
+            .ok_or(Error::new(ErrorKind::InvalidInput, "Invalid kind input"))?;
+        let prompt = DockerPrompt {
+            kind: *kind,
+            command: DockerCommand::DF,
+            target: String::new(),
+            secret: None,
+        };
+        self.docker_execute_prompt(prompt, done, |_| {})
+    }
+
+    /// Lists only what a prune of `kind` would remove, e.g. the stopped containers.
+    pub fn reclaimable_cmd<F>(&self, kind: DockerKind, done: F) -> Result<(), Error>
+    where
+        F: FnOnce(Result<Output, Error>) + Send + 'static,
+    {
+        let prompt = DockerPrompt {
+            kind,
+            command: DockerCommand::RECLAIMABLE,
+            target: String::new(),
+            secret: None,
+        };
+        self.docker_execute_prompt(prompt, done, |_| {})
+    }
+
+    /// The command line the docker CLI would run for the keys, to edit before running it.
+    /// `None` for what only tocker does, e.g. browsing a volume.
+    pub fn cli_line(&self, first: &KeyEvent, second: &KeyEvent, target: &str) -> Option<String> {
+        let kind = self.kind_keybindings.get(first)?;
+        let command = self.command_keybindings.get(second)?;
+        let verb = cli_verb(kind, command)?;
+        Some(format!("docker {} {verb} {target}", kind_label(kind)))
+    }
+
+    /// Runs a docker command line as typed, e.g. one edited with extra flags.
+    pub fn line_cmd<F, P>(&self, line: &str, done: F, progress: P) -> Result<(), Error>
+    where
+        F: FnOnce(Result<Output, Error>) + Send + 'static,
+        P: FnMut(Vec<String>) + Send + 'static,
+    {
+        let prompt = DockerPrompt {
+            kind: DockerKind::System,
+            command: DockerCommand::LINE,
+            target: String::from(line),
+            secret: None,
+        };
+        self.docker_execute_prompt(prompt, done, progress)
+    }
+
+    /// Builds an image, `target` is the context path optionally followed by the tag.
+    pub fn build_cmd<F, P>(&self, target: &str, done: F, progress: P) -> Result<(), Error>
+    where
+        F: FnOnce(Result<Output, Error>) + Send + 'static,
+        P: FnMut(Vec<String>) + Send + 'static,
+    {
+        let prompt = DockerPrompt {
+            kind: DockerKind::Image,
+            command: DockerCommand::BUILD,
+            target: String::from(target),
+            secret: None,
+        };
+        self.docker_execute_prompt(prompt, done, progress)
+    }
+
+    /// Queues the prompt on the worker, `done` is called with its output once finished
+    /// and `progress` with partial output of long running commands.
+    pub fn docker_execute_prompt<F, P>(
+        &self,
+        cmd: DockerPrompt,
+        done: F,
+        progress: P,
+    ) -> Result<(), Error>
+    where
+        F: FnOnce(Result<Output, Error>) + Send + 'static,
+        P: FnMut(Vec<String>) + Send + 'static,
+    {
+        // listings show the columns picked in the config
+        let reply: Reply = match cmd.command {
+            DockerCommand::LS | DockerCommand::RECLAIMABLE => {
+                let columns = self.columns.get(&cmd.kind).cloned();
+                let toggled = self.toggled(&cmd.kind);
+                let running = self.running_only(&cmd.kind) && cmd.command == DockerCommand::LS;
+                Box::new(move |output: Result<Output, Error>| {
+                    let output = with_columns(output, columns.as_deref(), &toggled);
+                    done(output.map(|output| without_stopped(output, running)))
+                })
+            }
+            _ => Box::new(done),
+        };
+        self.worker.submit(Job {
+            prompt: cmd,
+            reply,
+            progress: Box::new(progress),
+            timeout: self.timeout,
+        })
+    }
+
+    /// Gives up the docker commands in flight, killing the CLI they may have started.
+    pub fn cancel_jobs(&self) {
+        self.worker.cancel()
+    }
+
+    /// Shows the DIGEST column of image listings or hides it again, from their next
+    /// listing on. Returns whether it shows.
+    pub fn toggle_digests(&mut self) -> bool {
+        self.digests = !self.digests;
+        self.digests
+    }
+
+    /// Lists the stopped containers too or leaves them out, from the next listing on.
+    /// Returns whether they're listed.
+    pub fn toggle_all(&mut self) -> bool {
+        self.all = !self.all;
+        self.all
+    }
+
+    /// Whether the stopped containers are listed too.
+    pub fn all_containers(&self) -> bool {
+        self.all
+    }
+
+    /// The tab on screen when tocker last quit, to open it the same way again.
+    pub fn last_view(&self) -> Option<View> {
+        self.session.view()
+    }
+
+    /// Saves the tab on screen, if any, with the favorites for the next run.
+    pub fn save_view(&mut self, view: Option<&View>) -> Result<(), Error> {
+        self.session.keep(view);
+        self.session.save()
+    }
+
+    /// The names of the rows of `kind` starred as favorites.
+    pub fn favorites(&self, kind: &DockerKind) -> &[String] {
+        self.session
+            .favorites
+            .get(kind_label(kind))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Stars the row of `kind` named `name` as a favorite or unstars it, saved for the
+    /// next runs right away. Returns whether it's a favorite.
+    pub fn toggle_favorite(&mut self, kind: &DockerKind, name: &str) -> Result<bool, Error> {
+        let favorites = self
+            .session
+            .favorites
+            .entry(String::from(kind_label(kind)))
+            .or_default();
+        let starred = match favorites.iter().position(|favorite| favorite == name) {
+            Some(index) => {
+                favorites.remove(index);
+                false
+            }
+            None => {
+                favorites.push(String::from(name));
+                true
+            }
+        };
+        self.session.save()?;
+        Ok(starred)
+    }
+
+    /// Whether listings of `kind` leave the stopped containers out.
+    fn running_only(&self, kind: &DockerKind) -> bool {
+        !self.all && *kind == DockerKind::Container
+    }
+
+    /// The columns of `kind` toggled from the listing, and whether they show.
+    fn toggled(&self, kind: &DockerKind) -> Vec<(&'static str, bool)> {
+        match kind {
+            DockerKind::Image => vec![("DIGEST", self.digests)],
+            _ => vec![],
+        }
+    }
+}
+
+/// Keeps the columns of a listing picked in the config, or its default ones, the
+/// `toggled` ones after them when they show.
+fn with_columns(
+    output: Result<Output, Error>,
+    columns: Option<&[String]>,
+    toggled: &[(&str, bool)],
+) -> Result<Output, Error> {
+    match output? {
+        Output::Table(mut table) => {
+            let shown: Vec<&str> = toggled
+                .iter()
+                .filter(|(_, shows)| *shows)
+                .map(|(name, _)| *name)
+                .collect();
+            match columns {
+                Some(columns) => {
+                    let mut picked: Vec<String> = columns
+                        .iter()
+                        .filter(|column| {
+                            !toggled
+                                .iter()
+                                .any(|(name, _)| column.eq_ignore_ascii_case(name))
+                        })
+                        .cloned()
+                        .collect();
+                    picked.extend(shown.iter().map(|name| String::from(*name)));
+                    table.pick_columns(&picked)?
+                }
+                None => table.hide_extras(&shown),
+            }
+            Ok(Output::Table(table))
+        }
+        output => Ok(output),
+    }
+}
+
+/// A duration the way `docker logs --since` takes it, e.g. `90s`, `10m`, `2h` or `1d`,
+/// bare numbers are seconds.
+pub fn parse_since(since: &str) -> Result<Duration, Error> {
+    let invalid = || {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("Since should be like 10m, 2h or 1d: {since}"),
+        )
+    };
+    let (number, unit) = match since.find(|c: char| !c.is_ascii_digit()) {
+        Some(at) => since.split_at(at),
+        None => (since, "s"),
+    };
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    Ok(Duration::from_secs(number * seconds))
+}
+
+/// Whether `name` is among the `picked` columns, in any case.
+fn picks(picked: &[String], name: &str) -> bool {
+    picked
+        .iter()
+        .any(|column| column.eq_ignore_ascii_case(name))
+}
+
+/// The rows of the containers still up when `running`, like `docker ps` without `-a`.
+fn without_stopped(output: Output, running: bool) -> Output {
+    match (output, running) {
+        (Output::Table(mut table), true) => {
+            table.rows.retain(|row| {
+                matches!(
+                    row.state.as_deref(),
+                    Some("running" | "paused" | "restarting")
+                )
+            });
+            Output::Table(table)
+        }
+        (output, _) => output,
+    }
+}
+
+fn kind_label(kind: &DockerKind) -> &'static str {
+    match kind {
+        DockerKind::Image => "image",
+        DockerKind::Container => "container",
+        DockerKind::Volume => "volume",
+        DockerKind::Project => "project",
+        DockerKind::System => "system",
+        DockerKind::Registry => "registry",
+        DockerKind::Service => "service",
+        DockerKind::Stack => "stack",
+        DockerKind::Secret => "secret",
+        DockerKind::Config => "config",
+        DockerKind::Fleet => "fleet",
+    }
+}
+
+/// Reads like the docker CLI would take it, e.g. `docker container rm web`.
+/// Custom commands are their own lines already.
+fn command_line(kind: &DockerKind, command: &DockerCommand, target: &str) -> String {
+    if let DockerCommand::CUSTOM(_) = command {
+        return target.replace('\n', "; ");
+    }
+    let group = match kind {
+        DockerKind::Project => "compose",
+        kind => kind_label(kind),
+    };
+    format!("docker {group} {} {target}", command_label(kind, command))
+        .trim_end()
+        .to_string()
+}
+
+/// The docker CLI subcommand doing the same, for the kinds docker has a group of commands for.
+fn cli_verb(kind: &DockerKind, command: &DockerCommand) -> Option<&'static str> {
+    if matches!(
+        kind,
+        DockerKind::Project | DockerKind::Registry | DockerKind::Fleet
+    ) {
+        return None;
+    }
+    match command {
+        // `docker service scale` and `update --image` take their input as flags
+        DockerCommand::SCALE | DockerCommand::UPDATE if *kind == DockerKind::Service => None,
+        // the data of secrets and configs is read from a file or stdin
+        DockerCommand::CREATE if matches!(kind, DockerKind::Secret | DockerKind::Config) => None,
+        DockerCommand::LS if *kind == DockerKind::System => Some("df"),
+        DockerCommand::LS => Some("ls"),
+        DockerCommand::RM => Some("rm"),
+        DockerCommand::STOP => Some("stop"),
+        DockerCommand::UP => Some("start"),
+        DockerCommand::RESTART => Some("restart"),
+        DockerCommand::LOGS => Some("logs"),
+        DockerCommand::PAUSE => Some("pause"),
+        DockerCommand::UNPAUSE => Some("unpause"),
+        DockerCommand::KILL => Some("kill"),
+        DockerCommand::INSPECT => Some("inspect"),
+        DockerCommand::PRUNE => Some("prune"),
+        DockerCommand::PULL => Some("pull"),
+        DockerCommand::PUSH => Some("push"),
+        DockerCommand::TAG => Some("tag"),
+        DockerCommand::HISTORY => Some("history"),
+        DockerCommand::TOP => Some("top"),
+        DockerCommand::PS => Some("ps"),
+        DockerCommand::RENAME => Some("rename"),
+        DockerCommand::UPDATE => Some("update"),
+        DockerCommand::CREATE => Some("create"),
+        DockerCommand::CP => Some("cp"),
+        DockerCommand::SAVE => Some("save"),
+        DockerCommand::LOAD => Some("load"),
+        DockerCommand::EXPORT => Some("export"),
+        DockerCommand::IMPORT => Some("import"),
+        _ => None,
+    }
+}
+
+/// What a command does, read before the kind in the palette, e.g. `view logs of containers`.
+fn action_label(kind: &DockerKind, command: &DockerCommand) -> &'static str {
+    match (kind, command) {
+        (DockerKind::Container | DockerKind::Fleet, DockerCommand::UP) => "start",
+        (DockerKind::System, DockerCommand::LS) => "disk usage of",
+        (DockerKind::Volume, DockerCommand::DANGLING) => "remove unused",
+        (_, DockerCommand::LS) => "list",
+        (_, DockerCommand::RM) => "remove",
+        (_, DockerCommand::TAG) => "tag",
+        (_, DockerCommand::STOP) => "stop",
+        (_, DockerCommand::EXEC) => "open a shell in",
+        (_, DockerCommand::STATS) => "view stats of",
+        (_, DockerCommand::UP) => "up",
+        (_, DockerCommand::DOWN) => "down",
+        (_, DockerCommand::RESTART) => "restart",
+        (_, DockerCommand::LOGS) => "view logs of",
+        (_, DockerCommand::PULL) => "pull",
+        (_, DockerCommand::BUILD) => "build",
+        (_, DockerCommand::PAUSE) => "pause",
+        (_, DockerCommand::UNPAUSE) => "unpause",
+        (_, DockerCommand::INSPECT) => "inspect",
+        (_, DockerCommand::PRUNE) => "prune",
+        (_, DockerCommand::DF) => "disk usage of",
+        (_, DockerCommand::RECLAIMABLE) => "list reclaimable",
+        (_, DockerCommand::LINE) => "run a command line on",
+        (_, DockerCommand::OPEN) => "open in the browser",
+        (_, DockerCommand::CP) => "copy files of",
+        (_, DockerCommand::PUSH) => "push",
+        (_, DockerCommand::LOGIN) => "log in to",
+        (_, DockerCommand::LOGOUT) => "log out of",
+        (_, DockerCommand::SEARCH) => "search",
+        (_, DockerCommand::HISTORY) => "view history of",
+        (_, DockerCommand::LAYERS) => "explore the layers of",
+        (_, DockerCommand::TOP) => "view processes of",
+        (_, DockerCommand::RENAME) => "rename",
+        (_, DockerCommand::KILL) => "kill",
+        (_, DockerCommand::UPDATE) => "update",
+        (_, DockerCommand::HEALTH) => "view health of",
+        (_, DockerCommand::BROWSE) => "browse the files of",
+        (_, DockerCommand::CREATE) => "create",
+        (_, DockerCommand::SAVE) => "save",
+        (_, DockerCommand::LOAD) => "load",
+        (_, DockerCommand::EXPORT) => "export",
+        (_, DockerCommand::IMPORT) => "import",
+        (_, DockerCommand::DANGLING) => "remove dangling",
+        (_, DockerCommand::POLICY) => "set the restart policy of",
+        (_, DockerCommand::ENV) => "view env of",
+        (_, DockerCommand::EDIT) => "edit the file of",
+        (_, DockerCommand::PIN) => "pin the logs of",
+        (_, DockerCommand::PS) => "list the tasks of",
+        (_, DockerCommand::SCALE) => "scale",
+        (_, DockerCommand::DEPLOY) => "deploy",
+        (_, DockerCommand::CUSTOM(_)) => "run",
+    }
+}
+
+fn kind_plural(kind: &DockerKind) -> &'static str {
+    match kind {
+        DockerKind::Image => "images",
+        DockerKind::Container => "containers",
+        DockerKind::Volume => "volumes",
+        DockerKind::Project => "projects",
+        DockerKind::System => "system",
+        DockerKind::Registry => "registries",
+        DockerKind::Service => "services",
+        DockerKind::Stack => "stacks",
+        DockerKind::Secret => "secrets",
+        DockerKind::Config => "configs",
+        DockerKind::Fleet => "fleet containers",
+    }
+}
+
+/// What a general command does in the palette, none for those that make no sense there.
+fn general_label(command: &GeneralCommand) -> Option<&'static str> {
+    match command {
+        GeneralCommand::QUIT => Some("quit"),
+        GeneralCommand::HELP => Some("show help"),
+        GeneralCommand::CLEAN => Some("clean up"),
+        GeneralCommand::BUILD => Some("build an image"),
+        GeneralCommand::SPLIT => Some("toggle the details pane"),
+        GeneralCommand::TAB => Some("next tab"),
+        GeneralCommand::HISTORY => Some("command history"),
+        GeneralCommand::EVENTS => Some("toggle the events pane"),
+        GeneralCommand::FIND => Some("find a row"),
+        GeneralCommand::CONTEXT => Some("switch docker context"),
+        GeneralCommand::RECORD => Some("record a macro"),
+        GeneralCommand::PAGER => Some("open in the pager"),
+        GeneralCommand::CANCEL | GeneralCommand::PALETTE | GeneralCommand::MACRO(_) => None,
+    }
+}
+
+/// How a command reads in the legend, verbs shared by kinds can mean different things.
+fn command_label(kind: &DockerKind, command: &DockerCommand) -> String {
+    match (kind, command) {
+        (DockerKind::Container | DockerKind::Fleet, DockerCommand::UP) => String::from("start"),
+        (DockerKind::System, DockerCommand::LS) => String::from("df"),
+        (DockerKind::Volume, DockerCommand::DANGLING) => String::from("unused"),
+        (DockerKind::Container, DockerCommand::POLICY) => String::from("restart policy"),
+        _ => format!("{command:?}").to_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::channel;
+
+    use super::*;
+
+    fn key(code: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(code), KeyModifiers::NONE)
+    }
+
+    fn tocker(backend: &MockBackend) -> Tocker {
+        configured(backend, "").unwrap()
+    }
+
+    /// Tocker on `backend` with the config file `toml` would be.
+    fn configured(backend: &MockBackend, toml: &str) -> Result<Tocker, Error> {
+        let runtime = Builder::new_multi_thread().enable_all().build().unwrap();
+        let config = toml::from_str(toml).unwrap();
+        Tocker::with_backend(runtime, backend.clone(), config, Session::default())
+    }
+
+    /// Runs the command of the key pair on `target` and waits for its output.
+    fn run(tocker: &Tocker, first: char, second: char, target: &str) -> Output {
+        ran(tocker, first, second, target).unwrap()
+    }
+
+    /// Like `run`, with what the backend answered even when it failed.
+    fn ran(tocker: &Tocker, first: char, second: char, target: &str) -> Result<Output, Error> {
+        let (sender, receiver) = channel();
+        tocker
+            .exec_cmd(
+                &key(first),
+                &key(second),
+                target,
+                move |output| sender.send(output).unwrap(),
+                |_| {},
+            )
+            .unwrap();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap()
+    }
+
+    fn containers() -> MockBackend {
+        MockBackend::default().listing(
+            DockerKind::Container,
+            &["CONTAINER ID", "NAMES"],
+            &[&["web", "web-1"], &["db", "db-1"], &["cache", "cache-1"]],
+        )
+    }
+
+    #[test]
+    fn every_preset_reaches_the_palette() {
+        for preset in ["", "preset = 'vim'", "preset = 'emacs'"] {
+            let tocker = configured(&MockBackend::default(), preset).unwrap();
+            let reachable = tocker.general_keybindings.iter().any(|(key, command)| {
+                *command == GeneralCommand::PALETTE && !tocker.select_keybindings.contains_key(key)
+            });
+            assert!(reachable, "the palette is out of reach with {preset:?}");
+        }
+    }
+
+    #[test]
+    fn key_pairs_ask_for_their_target() {
+        let tocker = tocker(&MockBackend::default());
+        assert!(matches!(
+            tocker.check_for_target(&key('c'), &key('r')),
+            Ok(TargetType::SELECT)
+        ));
+        assert!(matches!(
+            tocker.check_for_target(&key('c'), &key('l')),
+            Ok(TargetType::EMPTY)
+        ));
+        // volumes can't be stopped
+        assert!(tocker.check_for_target(&key('v'), &key('s')).is_err());
+        assert!(tocker.check_for_target(&key('z'), &key('l')).is_err());
+    }
+
+    #[test]
+    fn presets_move_the_cursor_their_way() {
+        let vim = configured(&MockBackend::default(), "preset = 'vim'").unwrap();
+        assert!(matches!(vim.check_select(key('j')), Ok(Select::NEXT)));
+        assert!(matches!(vim.check_select(key('k')), Ok(Select::PREV)));
+        let emacs = configured(&MockBackend::default(), "preset = 'emacs'").unwrap();
+        let ctrl = |code| KeyEvent::new(KeyCode::Char(code), KeyModifiers::CONTROL);
+        assert!(matches!(emacs.check_select(ctrl('n')), Ok(Select::NEXT)));
+        assert!(matches!(emacs.check_select(ctrl('p')), Ok(Select::PREV)));
+    }
+
+    #[test]
+    fn remaps_replace_the_default_keys() {
+        let backend = containers();
+        let tocker = configured(&backend, "[command]\nls = 'L'\nrm = 'X'").unwrap();
+        assert!(matches!(
+            tocker.check_for_target(&key('c'), &key('L')),
+            Ok(TargetType::EMPTY)
+        ));
+        assert!(tocker.check_for_target(&key('c'), &key('l')).is_err());
+        run(&tocker, 'c', 'X', "web");
+        assert_eq!(backend.ran(), ["docker container rm web"]);
+    }
+
+    #[test]
+    fn custom_commands_get_their_own_keys() {
+        let toml = "[[custom]]\nname = 'prune old'\nkey = 'alt+o'\nkind = 'image'\n\
+                    command = 'docker image prune -a'\ntarget = 'empty'";
+        let tocker = configured(&MockBackend::default(), toml).unwrap();
+        let alt_o = KeyEvent::new(KeyCode::Char('o'), KeyModifiers::ALT);
+        assert!(matches!(
+            tocker.check_for_target(&key('i'), &alt_o),
+            Ok(TargetType::EMPTY)
+        ));
+        assert_eq!(
+            tocker.command_line(&key('i'), &alt_o, ""),
+            "docker image prune -a"
+        );
+    }
+
+    #[test]
+    fn an_invalid_config_is_returned() {
+        let backend = MockBackend::default();
+        for toml in [
+            "preset = 'nano'",
+            "[command]\nnothing = 'n'",
+            "[command]\nls = 'ctrl+'",
+            "[columns]\nships = ['NAME']",
+            "[[custom]]\nname = 'x'\nkey = 'Z'\nkind = 'ship'\ncommand = 'true'",
+            // `r` removes already
+            "[[custom]]\nname = 'x'\nkey = 'r'\nkind = 'container'\ncommand = 'true'",
+        ] {
+            let err = configured(&backend, toml).err();
+            assert!(err.is_some(), "{toml:?} is taken as valid");
+        }
+    }
+
+    #[test]
+    fn a_failing_backend_is_returned() {
+        let backend = containers().failing("Cannot connect to the Docker daemon");
+        let tocker = tocker(&backend);
+        let err = ran(&tocker, 'c', 'r', "web").unwrap_err();
+        assert!(err.to_string().contains("Cannot connect"));
+        let (sender, receiver) = channel();
+        tocker
+            .list_cmd(DockerKind::Container, move |output| {
+                sender.send(output).unwrap()
+            })
+            .unwrap();
+        let listed = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(listed.is_err());
+        // nothing is removed when the daemon didn't answer
+        assert_eq!(backend.ids(DockerKind::Container), ["web", "db", "cache"]);
+    }
+
+    #[test]
+    fn selected_rows_are_removed_together() {
+        let backend = containers();
+        let tocker = tocker(&backend);
+        let Output::Table(table) = run(&tocker, 'c', 'l', "") else {
+            panic!("containers aren't listed in a table");
         };
-        self.docker_execute_prompt(prompt)
+        let selected: Vec<&str> = table
+            .rows
+            .iter()
+            .map(|row| row.id.as_str())
+            .filter(|id| *id != "cache")
+            .collect();
+        let removed = run(&tocker, 'c', 'r', &selected.join(" "));
+        assert_eq!(removed.exit_code(), 0);
+        assert_eq!(removed.lines(), ["Removed web", "Removed db"]);
+        assert_eq!(backend.ids(DockerKind::Container), ["cache"]);
+    }
+
+    #[test]
+    fn only_failed_targets_fail_a_command() {
+        let printed = Output::Lines(vec![String::from("Error: told by a container log")]);
+        assert_eq!(printed.exit_code(), 0);
+        let done = Report::from(vec![String::from("web")]);
+        assert_eq!(Output::Ran(done).exit_code(), 0);
+        let mut failed = Report::default();
+        failed.fail("No such container: gone");
+        assert_eq!(failed.lines, ["Error: No such container: gone"]);
+        assert_eq!(Output::Ran(failed).exit_code(), 1);
     }
 
-    pub fn docker_execute_prompt(&self, cmd: DockerPrompt) -> Result<Output, Error> {
-        Command::new("docker")
-            .arg(OsString::from(cmd.kind))
-            .arg(OsString::from(cmd.command))
-            .arg(OsString::from(cmd.target))
-            .output()
+    #[test]
+    fn remove_is_recorded_by_its_cli_line() {
+        let backend = containers();
+        let tocker = tocker(&backend);
+        let removed = run(&tocker, 'c', 'r', "web gone");
+        assert_eq!(removed.exit_code(), 1);
+        assert_eq!(backend.ran(), ["docker container rm web gone"]);
     }
 }
@@ -0,0 +1,125 @@
+use super::listing::human_size;
+
+const BAR_WIDTH: usize = 30;
+
+struct Layer {
+    id: String,
+    status: String,
+    current: i64,
+    total: i64,
+}
+
+/// Per-layer progress of a pull or push, rendered like the docker CLI progress bars.
+#[derive(Default)]
+pub struct LayerProgress {
+    layers: Vec<Layer>,
+    messages: Vec<String>,
+}
+
+impl LayerProgress {
+    pub fn update(&mut self, id: Option<&str>, status: Option<&str>, current: i64, total: i64) {
+        let status = String::from(status.unwrap_or_default());
+        let Some(id) = id else {
+            self.messages.push(status);
+            return;
+        };
+        match self.layers.iter_mut().find(|layer| layer.id == id) {
+            Some(layer) => {
+                layer.status = status;
+                layer.current = current;
+                layer.total = total;
+            }
+            None => self.layers.push(Layer {
+                id: String::from(id),
+                status,
+                current,
+                total,
+            }),
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .layers
+            .iter()
+            .map(|layer| match layer.total > 0 {
+                true => {
+                    let done = (layer.current.clamp(0, layer.total) as usize * BAR_WIDTH)
+                        / layer.total as usize;
+                    format!(
+                        "{}: {} [{}{}] {}/{}",
+                        layer.id,
+                        layer.status,
+                        "=".repeat(done),
+                        " ".repeat(BAR_WIDTH - done),
+                        human_size(layer.current),
+                        human_size(layer.total)
+                    )
+                }
+                false => format!("{}: {}", layer.id, layer.status),
+            })
+            .collect();
+        lines.extend(self.messages.iter().cloned());
+        lines
+    }
+}
+
+/// Starts the checklist line of a target that failed.
+const FAILED_MARK: &str = "[!]";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Step {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+/// Where every target of a batch is at, e.g. a `rm` of many selected containers, the
+/// error kept next to the ones that failed.
+pub struct Checklist {
+    doing: &'static str,
+    items: Vec<(String, Step, Option<String>)>,
+}
+
+impl Checklist {
+    pub fn new(doing: &'static str, targets: &[&str]) -> Checklist {
+        Checklist {
+            doing,
+            items: targets
+                .iter()
+                .map(|target| (target.to_string(), Step::Pending, None))
+                .collect(),
+        }
+    }
+
+    pub fn set(&mut self, index: usize, step: Step, error: Option<String>) {
+        if let Some(item) = self.items.get_mut(index) {
+            item.1 = step;
+            item.2 = error;
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let count = |step| self.items.iter().filter(|item| item.1 == step).count();
+        let finished = count(Step::Done) + count(Step::Failed);
+        let mut header = format!("{} {}/{}", self.doing, finished, self.items.len());
+        if count(Step::Failed) > 0 {
+            header.push_str(&format!(", {} failed", count(Step::Failed)));
+        }
+        let mut lines = vec![header];
+        lines.extend(self.items.iter().map(|(target, step, error)| {
+            let (mark, status) = match step {
+                Step::Pending => ("[ ]", "pending"),
+                Step::Running => ("[~]", "running"),
+                Step::Done => ("[x]", "done"),
+                Step::Failed => (FAILED_MARK, "failed"),
+            };
+            match error {
+                Some(error) => format!("{mark} {target}  {status}: {error}"),
+                None => format!("{mark} {target}  {status}"),
+            }
+        }));
+        lines
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{Error, ErrorKind},
+    path::PathBuf,
+    process::Stdio,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::Value;
+use tokio::process::Command;
+
+/// A registry `docker login` keeps credentials for.
+#[derive(Debug, Default)]
+pub struct Registry {
+    pub server: String,
+    pub username: Option<String>,
+    /// where the credentials are kept, the config file itself or a credential helper
+    pub store: String,
+}
+
+/// `config.json` in `DOCKER_CONFIG`, `~/.docker` unless set.
+fn config_path() -> Option<PathBuf> {
+    match env::var_os("DOCKER_CONFIG") {
+        Some(dir) => Some(PathBuf::from(dir).join("config.json")),
+        None => dirs::home_dir().map(|home| home.join(".docker").join("config.json")),
+    }
+}
+
+/// The registries logged into, read from the docker config the same way the CLI does.
+/// No config yet means no logins.
+pub async fn registries() -> Result<Vec<Registry>, Error> {
+    let Some(path) = config_path() else {
+        return Ok(vec![]);
+    };
+    let config: Value = match fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text).map_err(Error::other)?,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+    let creds_store = config["credsStore"].as_str();
+    let helpers = config["credHelpers"].as_object();
+    let helper = |server: &str| {
+        helpers
+            .and_then(|helpers| helpers.get(server))
+            .and_then(Value::as_str)
+            .or(creds_store)
+    };
+
+    let mut registries: BTreeMap<String, Registry> = BTreeMap::new();
+    // logins kept by a helper are listed in the config with an empty entry
+    for (server, auth) in config["auths"].as_object().into_iter().flatten() {
+        let username = auth["auth"]
+            .as_str()
+            .and_then(|auth| STANDARD.decode(auth).ok())
+            .and_then(|decoded| String::from_utf8(decoded).ok())
+            .and_then(|decoded| decoded.split_once(':').map(|(user, _)| String::from(user)));
+        let store = match (&username, helper(server)) {
+            (Some(_), _) | (None, None) => String::from("config file"),
+            (None, Some(helper)) => String::from(helper),
+        };
+        registries.insert(
+            server.clone(),
+            Registry {
+                server: server.clone(),
+                username,
+                store,
+            },
+        );
+    }
+    let mut stores: Vec<&str> = helpers
+        .into_iter()
+        .flatten()
+        .filter_map(|(_, helper)| helper.as_str())
+        .chain(creds_store)
+        .collect();
+    stores.sort();
+    stores.dedup();
+    for store in stores {
+        for (server, username) in helper_logins(store).await {
+            if helper(&server) != Some(store) {
+                continue;
+            }
+            let registry = registries
+                .entry(server.clone())
+                .or_insert_with(|| Registry {
+                    server,
+                    store: String::from(store),
+                    ..Default::default()
+                });
+            registry.username = registry.username.take().or(Some(username));
+        }
+    }
+    Ok(registries.into_values().collect())
+}
+
+/// What `docker-credential-<store> list` knows about, server → username.
+/// A helper that's missing or fails lists nothing.
+async fn helper_logins(store: &str) -> BTreeMap<String, String> {
+    let output = Command::new(format!("docker-credential-{store}"))
+        .arg("list")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .await;
+    match output {
+        Ok(output) if output.status.success() => {
+            serde_json::from_slice(&output.stdout).unwrap_or_default()
+        }
+        _ => BTreeMap::new(),
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{Error, ErrorKind},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{config, kind_label, DockerKind, SortBy};
+
+/// What tocker keeps between runs, next to its log in `~/.local/state/tocker`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Session {
+    /// the names of the rows starred as favorites, by the config name of their kind
+    pub favorites: BTreeMap<String, Vec<String>>,
+    /// the config name of the kind whose tab was on screen, none if no tab was
+    pub tab: Option<String>,
+    /// what its rows were narrowed by, label selectors when `labels`
+    pub filter: String,
+    pub labels: bool,
+    /// the column they were sorted by, e.g. `size`, and in which order
+    pub sort: Option<String>,
+    pub descending: bool,
+    /// the details pane showed next to the listing
+    pub split: bool,
+}
+
+/// A tab as it was left, to open it again narrowed and sorted the same.
+#[derive(Debug)]
+pub struct View {
+    pub kind: DockerKind,
+    pub filter: String,
+    pub labels: bool,
+    pub sort: Option<(SortBy, bool)>,
+    pub split: bool,
+}
+
+impl Session {
+    /// The tab on screen when the session was saved, if any.
+    pub fn view(&self) -> Option<View> {
+        let sort = match self.sort.as_deref() {
+            Some("name") => Some(SortBy::Name),
+            Some("size") => Some(SortBy::Size),
+            Some("created") => Some(SortBy::Created),
+            Some("status") => Some(SortBy::Status),
+            _ => None,
+        };
+        Some(View {
+            kind: config::kind(self.tab.as_deref()?)?,
+            filter: self.filter.clone(),
+            labels: self.labels,
+            sort: sort.map(|by| (by, self.descending)),
+            split: self.split,
+        })
+    }
+
+    /// Keeps `view` as the tab to start out with, none for the bare kind prompt.
+    pub fn keep(&mut self, view: Option<&View>) {
+        self.tab = view.map(|view| String::from(kind_label(&view.kind)));
+        self.filter = view.map(|view| view.filter.clone()).unwrap_or_default();
+        self.labels = view.is_some_and(|view| view.labels);
+        let sort = view.and_then(|view| view.sort);
+        self.sort = sort.map(|(by, _)| {
+            String::from(match by {
+                SortBy::Name => "name",
+                SortBy::Size => "size",
+                SortBy::Created => "created",
+                SortBy::Status => "status",
+            })
+        });
+        self.descending = sort.is_some_and(|(_, descending)| descending);
+        self.split = view.is_some_and(|view| view.split);
+    }
+
+    /// The session saved last, a fresh one when there's none or it can't be read.
+    pub fn load() -> Session {
+        path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the whole session over the one saved last.
+    pub fn save(&self) -> Result<(), Error> {
+        let path = path().ok_or(Error::new(ErrorKind::NotFound, "No home directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = toml::to_string(self).map_err(Error::other)?;
+        fs::write(path, content)
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".local/state/tocker/session.toml"))
+}
@@ -0,0 +1,36 @@
+use std::collections::BTreeMap;
+
+use bollard::models::Service;
+
+/// Put by `docker stack deploy` on everything it creates.
+pub const NAMESPACE_LABEL: &str = "com.docker.stack.namespace";
+
+/// A stack, rebuilt from the label `docker stack deploy` puts on its services.
+#[derive(Debug, Default)]
+pub struct Stack {
+    pub name: String,
+    pub services: Vec<String>,
+}
+
+/// Groups services by stack, services created on their own are left out.
+pub fn stacks(services: &[Service]) -> Vec<Stack> {
+    let mut stacks: BTreeMap<String, Stack> = BTreeMap::new();
+    services.iter().for_each(|service| {
+        let Some(spec) = &service.spec else {
+            return;
+        };
+        let Some(name) = spec
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.get(NAMESPACE_LABEL))
+        else {
+            return;
+        };
+        let stack = stacks.entry(name.clone()).or_insert_with(|| Stack {
+            name: name.clone(),
+            ..Default::default()
+        });
+        stack.services.extend(spec.name.clone());
+    });
+    stacks.into_values().collect()
+}
@@ -0,0 +1,125 @@
+use bollard::models::ContainerStatsResponse;
+
+use super::listing::{align, human_size};
+
+/// Renders one `docker stats --no-stream` like row per container, header first.
+pub fn lines(stats: &[ContainerStatsResponse]) -> Vec<String> {
+    let mut rows = vec![vec![
+        String::from("CONTAINER ID"),
+        String::from("NAME"),
+        String::from("CPU %"),
+        String::from("MEM USAGE / LIMIT"),
+        String::from("MEM %"),
+        String::from("NET I/O"),
+        String::from("BLOCK I/O"),
+    ]];
+    stats.iter().for_each(|stat| {
+        let (memory, limit) = memory(stat);
+        let memory_percent = match limit {
+            0 => 0.0,
+            _ => memory as f64 / limit as f64 * 100.0,
+        };
+        let (rx, tx) = stat
+            .networks
+            .iter()
+            .flatten()
+            .fold((0, 0), |(rx, tx), (_, network)| {
+                (
+                    rx + network.rx_bytes.unwrap_or_default(),
+                    tx + network.tx_bytes.unwrap_or_default(),
+                )
+            });
+        let (read, write) = block_io(stat);
+        rows.push(vec![
+            stat.id
+                .as_deref()
+                .unwrap_or_default()
+                .chars()
+                .take(12)
+                .collect(),
+            stat.name
+                .as_deref()
+                .unwrap_or_default()
+                .trim_start_matches('/')
+                .to_string(),
+            format!("{:.2}%", cpu_percent(stat)),
+            format!("{} / {}", binary_size(memory), binary_size(limit)),
+            format!("{:.2}%", memory_percent),
+            format!("{} / {}", human_size(rx as i64), human_size(tx as i64)),
+            format!("{} / {}", human_size(read as i64), human_size(write as i64)),
+        ]);
+    });
+    align(rows)
+}
+
+/// Same formula as the docker CLI: cpu time delta over system time delta, scaled by cpus.
+fn cpu_percent(stat: &ContainerStatsResponse) -> f64 {
+    let (Some(cpu), Some(precpu)) = (&stat.cpu_stats, &stat.precpu_stats) else {
+        return 0.0;
+    };
+    let total = |stats: &bollard::models::ContainerCpuStats| {
+        stats
+            .cpu_usage
+            .as_ref()
+            .and_then(|usage| usage.total_usage)
+            .unwrap_or_default()
+    };
+    let cpu_delta = total(cpu).saturating_sub(total(precpu)) as f64;
+    let system_delta = cpu
+        .system_cpu_usage
+        .unwrap_or_default()
+        .saturating_sub(precpu.system_cpu_usage.unwrap_or_default()) as f64;
+    let cpus = cpu.online_cpus.unwrap_or(1) as f64;
+    match system_delta > 0.0 {
+        true => cpu_delta / system_delta * cpus * 100.0,
+        false => 0.0,
+    }
+}
+
+/// Usage without the page cache, like the CLI reports it.
+fn memory(stat: &ContainerStatsResponse) -> (u64, u64) {
+    let Some(memory) = &stat.memory_stats else {
+        return (0, 0);
+    };
+    let cache = memory
+        .stats
+        .as_ref()
+        .and_then(|stats| {
+            stats
+                .get("inactive_file")
+                .or(stats.get("total_inactive_file"))
+        })
+        .copied()
+        .unwrap_or_default();
+    (
+        memory.usage.unwrap_or_default().saturating_sub(cache),
+        memory.limit.unwrap_or_default(),
+    )
+}
+
+fn block_io(stat: &ContainerStatsResponse) -> (u64, u64) {
+    stat.blkio_stats
+        .as_ref()
+        .and_then(|blkio| blkio.io_service_bytes_recursive.as_ref())
+        .into_iter()
+        .flatten()
+        .fold((0, 0), |(read, write), entry| {
+            let value = entry.value.unwrap_or_default();
+            match entry.op.as_deref().map(str::to_lowercase).as_deref() {
+                Some("read") => (read + value, write),
+                Some("write") => (read, write + value),
+                _ => (read, write),
+            }
+        })
+}
+
+fn binary_size(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.2}{}", size, units[unit])
+}
@@ -0,0 +1,39 @@
+use super::listing::human_size;
+
+/// Where the volume is mounted in the container that lists it.
+pub const MOUNT: &str = "/data";
+/// How `stat` prints every path found, the name last since it may hold the separator.
+pub const FORMAT: &str = "%s|%F|%n";
+
+/// A path inside a volume, relative to its root.
+#[derive(Debug)]
+pub struct VolumeFile {
+    pub path: String,
+    pub size: u64,
+    pub dir: bool,
+}
+
+impl VolumeFile {
+    /// One line printed by `stat -c FORMAT`, `None` for anything else the container printed.
+    pub fn parse(line: &str) -> Option<VolumeFile> {
+        let mut parts = line.splitn(3, '|');
+        let size = parts.next()?.parse().ok()?;
+        let kind = parts.next()?;
+        let path = parts.next()?.strip_prefix(MOUNT)?.trim_start_matches('/');
+        if path.is_empty() {
+            return None;
+        }
+        Some(VolumeFile {
+            path: String::from(path),
+            size,
+            dir: kind == "directory",
+        })
+    }
+
+    pub fn line(&self) -> String {
+        match self.dir {
+            true => format!("{:>9}  {}/", "", self.path),
+            false => format!("{:>9}  {}", human_size(self.size as i64), self.path),
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    future,
+    io::{Error, ErrorKind},
+    mem,
+    sync::{
+        mpsc::{channel, Sender},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use tokio::{
+    runtime::Runtime,
+    time::{interval_at, Instant, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info};
+
+use super::{backend::DockerBackend, DockerPrompt, LogOptions, Output, Watch};
+
+pub type Reply = Box<dyn FnOnce(Result<Output, Error>) + Send>;
+pub type Update = Box<dyn FnMut(Result<Output, Error>) + Send>;
+pub type Progress = Box<dyn FnMut(Vec<String>) + Send>;
+pub type Events = Box<dyn FnMut(Result<String, Error>) + Send>;
+
+/// A docker operation queued for the worker, `reply` receives its output
+/// and `progress` what long running operations have done so far.
+pub struct Job {
+    pub prompt: DockerPrompt,
+    pub reply: Reply,
+    pub progress: Progress,
+    /// how long it may run before it's given up, forever if none
+    pub timeout: Option<Duration>,
+}
+
+enum Task {
+    Run {
+        job: Job,
+        cancel: CancellationToken,
+    },
+    Watch {
+        watch: Watch,
+        every: Duration,
+        update: Update,
+        cancel: CancellationToken,
+    },
+    Events {
+        each: Events,
+        cancel: CancellationToken,
+    },
+    Logs {
+        container: String,
+        options: LogOptions,
+        each: Events,
+        cancel: CancellationToken,
+    },
+}
+
+/// Owns the docker runtime on its own thread, so the UI thread only ever queues jobs.
+pub struct Worker {
+    tasks: Sender<Task>,
+    /// the parent of the tokens of the jobs running now, replaced once they're canceled
+    jobs: Mutex<CancellationToken>,
+}
+
+impl Worker {
+    pub fn start<B: DockerBackend>(runtime: Runtime, backend: B) -> Worker {
+        let (tasks, queue) = channel::<Task>();
+        thread::spawn(move || {
+            while let Ok(task) = queue.recv() {
+                let backend = backend.clone();
+                match task {
+                    Task::Run { mut job, cancel } => {
+                        runtime.spawn(async move {
+                            let line = job.prompt.line();
+                            info!(command = %line, "running");
+                            let timeout = job.timeout;
+                            // dropping the execution kills the docker CLI it may have started
+                            let output = tokio::select! {
+                                _ = cancel.cancelled() => Err(Error::new(
+                                    ErrorKind::Interrupted,
+                                    format!("Canceled `{line}`"),
+                                )),
+                                _ = expired(timeout) => Err(Error::new(
+                                    ErrorKind::TimedOut,
+                                    format!(
+                                        "`{line}` timed out after {}s",
+                                        timeout.unwrap_or_default().as_secs()
+                                    ),
+                                )),
+                                output = backend.execute(job.prompt, &mut job.progress) => output,
+                            };
+                            match &output {
+                                Ok(_) => info!(command = %line, "succeeded"),
+                                Err(err) => info!(command = %line, error = %err, "failed"),
+                            }
+                            (job.reply)(output)
+                        });
+                    }
+                    Task::Watch {
+                        watch,
+                        every,
+                        mut update,
+                        cancel,
+                    } => {
+                        runtime.spawn(async move {
+                            // a listing is on screen already, stats start out empty
+                            let start = match watch {
+                                Watch::Stats | Watch::Top(_) | Watch::Ping => Instant::now(),
+                                Watch::Listing(_) | Watch::Reclaimable(_) => Instant::now() + every,
+                            };
+                            let mut refresh = interval_at(start, every);
+                            refresh.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                            loop {
+                                tokio::select! {
+                                    _ = cancel.cancelled() => break,
+                                    _ = refresh.tick() => {}
+                                }
+                                tokio::select! {
+                                    _ = cancel.cancelled() => break,
+                                    output = backend.watch(watch.clone()) => {
+                                        debug!(?watch, ok = output.is_ok(), "refreshed");
+                                        update(output)
+                                    }
+                                }
+                            }
+                        });
+                    }
+                    Task::Events { mut each, cancel } => {
+                        runtime.spawn(async move {
+                            tokio::select! {
+                                _ = cancel.cancelled() => {}
+                                followed = backend.events(&mut each) => {
+                                    if let Err(err) = followed {
+                                        each(Err(err));
+                                    }
+                                }
+                            }
+                        });
+                    }
+                    Task::Logs {
+                        container,
+                        options,
+                        mut each,
+                        cancel,
+                    } => {
+                        runtime.spawn(async move {
+                            tokio::select! {
+                                _ = cancel.cancelled() => {}
+                                followed = backend.logs(&container, options, &mut each) => {
+                                    if let Err(err) = followed {
+                                        each(Err(err));
+                                    }
+                                }
+                            }
+                        });
+                    }
+                }
+            }
+        });
+        Worker {
+            tasks,
+            jobs: Mutex::new(CancellationToken::new()),
+        }
+    }
+
+    pub fn submit(&self, job: Job) -> Result<(), Error> {
+        let cancel = self
+            .jobs
+            .lock()
+            .map_err(|_| Error::other("The docker worker is gone"))?
+            .child_token();
+        self.send(Task::Run { job, cancel })
+    }
+
+    /// Gives up every job running or queued now, their replies tell they were canceled.
+    pub fn cancel(&self) {
+        if let Ok(mut jobs) = self.jobs.lock() {
+            mem::take(&mut *jobs).cancel();
+        }
+    }
+
+    /// Refreshes `watch` every `every` until the returned token is cancelled.
+    pub fn watch(
+        &self,
+        watch: Watch,
+        every: Duration,
+        update: Update,
+    ) -> Result<CancellationToken, Error> {
+        let cancel = CancellationToken::new();
+        self.send(Task::Watch {
+            watch,
+            every,
+            update,
+            cancel: cancel.clone(),
+        })?;
+        Ok(cancel)
+    }
+
+    /// Follows the daemon events until the returned token is cancelled.
+    pub fn events(&self, each: Events) -> Result<CancellationToken, Error> {
+        let cancel = CancellationToken::new();
+        self.send(Task::Events {
+            each,
+            cancel: cancel.clone(),
+        })?;
+        Ok(cancel)
+    }
+
+    /// Follows the logs of `container` until the returned token is cancelled.
+    pub fn logs(
+        &self,
+        container: &str,
+        options: LogOptions,
+        each: Events,
+    ) -> Result<CancellationToken, Error> {
+        let cancel = CancellationToken::new();
+        self.send(Task::Logs {
+            container: String::from(container),
+            options,
+            each,
+            cancel: cancel.clone(),
+        })?;
+        Ok(cancel)
+    }
+
+    fn send(&self, task: Task) -> Result<(), Error> {
+        self.tasks
+            .send(task)
+            .map_err(|_| Error::other("The docker worker is gone"))
+    }
+}
+
+/// Never for a job without a timeout.
+async fn expired(timeout: Option<Duration>) {
+    match timeout {
+        Some(timeout) => tokio::time::sleep(timeout).await,
+        None => future::pending().await,
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use ratatui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, List, ListItem, ListState},
+    Frame,
+};
+
+use tocker_core::{human_size, VolumeFile};
+
+const PARENT: &str = "..";
+
+/// Walks the directories of a volume one at a time, every entry with its size and the
+/// directories with the size of everything under them.
+pub struct Browser {
+    volume: String,
+    files: Vec<VolumeFile>,
+    sizes: HashMap<String, u64>,
+    /// the directory shown, empty for the root of the volume
+    dir: String,
+    /// what the directory holds: name, size and whether it's a directory
+    entries: Vec<(String, u64, bool)>,
+    pub list: ListState,
+}
+
+impl Browser {
+    pub fn new(volume: String, files: Vec<VolumeFile>) -> Browser {
+        let mut sizes: HashMap<String, u64> = HashMap::new();
+        files.iter().filter(|file| !file.dir).for_each(|file| {
+            file.path.match_indices('/').for_each(|(slash, _)| {
+                *sizes.entry(String::from(&file.path[..slash])).or_default() += file.size
+            });
+        });
+        let mut browser = Browser {
+            volume,
+            files,
+            sizes,
+            dir: String::new(),
+            entries: vec![],
+            list: ListState::default(),
+        };
+        browser.enter(String::new());
+        browser
+    }
+
+    pub fn up(&mut self) {
+        let selected = self.list.selected().unwrap_or_default();
+        self.list.select(Some(selected.saturating_sub(1)));
+    }
+
+    pub fn down(&mut self) {
+        let last = self.entries.len().saturating_sub(1);
+        let selected = self.list.selected().unwrap_or_default();
+        self.list.select(Some((selected + 1).min(last)));
+    }
+
+    /// Goes into the selected directory, `false` when a file is selected.
+    pub fn open(&mut self) -> bool {
+        let Some((name, _, true)) = self.list.selected().and_then(|i| self.entries.get(i)) else {
+            return false;
+        };
+        match name.as_str() {
+            PARENT => self.parent(),
+            _ => self.enter(self.path(name)),
+        }
+        true
+    }
+
+    /// Goes back to the directory above, with the one left selected.
+    pub fn parent(&mut self) {
+        let left = self.dir.clone();
+        let parent = match left.rsplit_once('/') {
+            Some((parent, _)) => String::from(parent),
+            None => String::new(),
+        };
+        self.enter(parent);
+        let name = left.rsplit('/').next().unwrap_or_default();
+        if let Some(index) = self.entries.iter().position(|(entry, ..)| entry == name) {
+            self.list.select(Some(index));
+        }
+    }
+
+    pub fn render<B: Backend>(
+        &mut self,
+        f: &mut Frame<B>,
+        area: Rect,
+        highlight: Style,
+        borders: Borders,
+    ) {
+        let items: Vec<ListItem> = match self.entries.is_empty() {
+            true => vec![ListItem::new(Span::styled(
+                "The volume is empty",
+                Style::default().fg(Color::DarkGray),
+            ))],
+            false => self
+                .entries
+                .iter()
+                .map(|(name, size, dir)| {
+                    let size = match name.as_str() {
+                        PARENT => String::new(),
+                        _ => human_size(*size as i64),
+                    };
+                    match dir {
+                        true => ListItem::new(format!("{size:>9}  {name}/"))
+                            .style(Style::default().fg(Color::Cyan)),
+                        false => ListItem::new(format!("{size:>9}  {name}")),
+                    }
+                })
+                .collect(),
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(borders)
+                    .title(format!(" {}:/{} ", self.volume, self.dir)),
+            )
+            .highlight_style(highlight);
+        f.render_stateful_widget(list, area, &mut self.list);
+    }
+
+    fn path(&self, name: &str) -> String {
+        match self.dir.is_empty() {
+            true => String::from(name),
+            false => format!("{}/{name}", self.dir),
+        }
+    }
+
+    /// Shows `dir`, directories first then files, both by name.
+    fn enter(&mut self, dir: String) {
+        let prefix = match dir.is_empty() {
+            true => String::new(),
+            false => format!("{dir}/"),
+        };
+        let mut entries: Vec<(String, u64, bool)> = self
+            .files
+            .iter()
+            .filter_map(|file| {
+                let name = file.path.strip_prefix(&prefix)?;
+                if name.contains('/') {
+                    return None;
+                }
+                let size = match file.dir {
+                    true => self.sizes.get(&file.path).copied().unwrap_or_default(),
+                    false => file.size,
+                };
+                Some((String::from(name), size, file.dir))
+            })
+            .collect();
+        entries.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+        if !dir.is_empty() {
+            entries.insert(0, (String::from(PARENT), 0, true));
+        }
+        self.dir = dir;
+        self.entries = entries;
+        self.list.select((!self.entries.is_empty()).then_some(0));
+    }
+}
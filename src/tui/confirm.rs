@@ -0,0 +1,49 @@
+use ratatui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+const ANSWERS: &str = "press 'y' = yes, 'n'/'esc' = no";
+
+/// Yes/no dialog drawn over the content, with a summary of what is at stake.
+pub struct Confirm {
+    pub question: String,
+    pub summary: Vec<String>,
+}
+
+impl Confirm {
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, borders: Borders) {
+        let mut lines = vec![
+            Spans::from(Span::styled(
+                self.question.clone(),
+                Style::default().fg(Color::Yellow),
+            )),
+            Spans::from(""),
+        ];
+        lines.extend(self.summary.iter().map(|line| Spans::from(line.clone())));
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(ANSWERS));
+
+        let width = lines
+            .iter()
+            .map(Spans::width)
+            .max()
+            .unwrap_or_default()
+            .saturating_add(4)
+            .min(area.width as usize) as u16;
+        let height = (lines.len() + 2).min(area.height as usize) as u16;
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        let block = Block::default().borders(borders).title(" Confirm ");
+        f.render_widget(Clear, popup);
+        f.render_widget(Paragraph::new(lines).block(block), popup);
+    }
+}
@@ -0,0 +1,41 @@
+use ratatui::widgets::ListState;
+
+use tocker_core::Context;
+
+/// The docker contexts to switch between, opened on the one in use.
+pub struct Contexts {
+    contexts: Vec<Context>,
+    pub list: ListState,
+}
+
+impl Contexts {
+    pub fn new(contexts: Vec<Context>) -> Contexts {
+        let mut list = ListState::default();
+        list.select(Some(
+            contexts
+                .iter()
+                .position(|context| context.current)
+                .unwrap_or_default(),
+        ));
+        Contexts { contexts, list }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.contexts.iter().map(Context::line).collect()
+    }
+
+    pub fn up(&mut self) {
+        let cursor = self.list.selected().unwrap_or_default().saturating_sub(1);
+        self.list.select(Some(cursor));
+    }
+
+    pub fn down(&mut self) {
+        let last = self.contexts.len().saturating_sub(1);
+        let cursor = (self.list.selected().unwrap_or_default() + 1).min(last);
+        self.list.select(Some(cursor));
+    }
+
+    pub fn selected(&self) -> Option<&Context> {
+        self.contexts.get(self.list.selected()?)
+    }
+}
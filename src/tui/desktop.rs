@@ -0,0 +1,20 @@
+use std::thread;
+
+use notify_rust::Notification;
+use tracing::info;
+
+/// Shows `summary` in the notifications of the desktop, tocker runs on without them
+/// when there's no notification daemon. Sent from a thread of its own, it can take a
+/// while to answer.
+pub fn notify(summary: String, body: String) {
+    thread::spawn(move || {
+        let shown = Notification::new()
+            .appname("tocker")
+            .summary(&summary)
+            .body(&body)
+            .show();
+        if let Err(err) = shown {
+            info!(error = %err, "desktop notification not shown");
+        }
+    });
+}
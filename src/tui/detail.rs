@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+
+use ratatui::{
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::ListState,
+};
+use serde_json::Value;
+
+const INDENT: &str = "  ";
+/// Nested objects deeper than this start collapsed, `docker inspect` output is long.
+const EXPANDED_DEPTH: usize = 2;
+
+/// One rendered line of the json, `path` identifies the object or array it opens.
+struct Row {
+    path: Option<String>,
+    spans: Vec<Span<'static>>,
+}
+
+/// Scrollable, highlighted json where nested objects and arrays can be collapsed.
+pub struct Detail {
+    root: Value,
+    collapsed: HashSet<String>,
+    rows: Vec<Row>,
+    pub list: ListState,
+}
+
+impl Detail {
+    pub fn new(root: Value) -> Detail {
+        let mut collapsed = HashSet::new();
+        collapse_deep(&root, String::new(), 0, &mut collapsed);
+        let mut detail = Detail {
+            root,
+            collapsed,
+            rows: vec![],
+            list: ListState::default(),
+        };
+        detail.rebuild();
+        detail.list.select(Some(0));
+        detail
+    }
+
+    /// The whole json, the collapsed parts too.
+    pub fn json(&self) -> String {
+        serde_json::to_string_pretty(&self.root).unwrap_or_default()
+    }
+
+    pub fn lines(&self) -> Vec<Spans<'static>> {
+        self.rows
+            .iter()
+            .map(|row| Spans::from(row.spans.clone()))
+            .collect()
+    }
+
+    pub fn up(&mut self, by: usize) {
+        let cursor = self.cursor().saturating_sub(by);
+        self.list.select(Some(cursor));
+    }
+
+    pub fn down(&mut self, by: usize) {
+        let last = self.rows.len().saturating_sub(1);
+        let cursor = (self.cursor() + by).min(last);
+        self.list.select(Some(cursor));
+    }
+
+    /// Collapses or expands the object or array opened on the cursor line.
+    pub fn toggle(&mut self) {
+        let Some(path) = self
+            .rows
+            .get(self.cursor())
+            .and_then(|row| row.path.clone())
+        else {
+            return;
+        };
+        if !self.collapsed.remove(&path) {
+            self.collapsed.insert(path);
+        }
+        self.rebuild();
+    }
+
+    fn cursor(&self) -> usize {
+        self.list.selected().unwrap_or_default()
+    }
+
+    fn rebuild(&mut self) {
+        let mut rows = vec![];
+        self.render(&self.root, None, String::new(), 0, true, &mut rows);
+        self.rows = rows;
+    }
+
+    fn render(
+        &self,
+        value: &Value,
+        key: Option<&str>,
+        path: String,
+        depth: usize,
+        last: bool,
+        rows: &mut Vec<Row>,
+    ) {
+        let mut spans = vec![Span::raw(INDENT.repeat(depth))];
+        if let Some(key) = key {
+            spans.push(Span::styled(
+                format!("\"{key}\""),
+                Style::default().fg(Color::Cyan),
+            ));
+            spans.push(Span::raw(": "));
+        }
+        let comma = match last {
+            true => "",
+            false => ",",
+        };
+        let (open, close, children): (&str, &str, Vec<(Option<&str>, &Value)>) = match value {
+            Value::Object(map) => (
+                "{",
+                "}",
+                map.iter().map(|(k, v)| (Some(k.as_str()), v)).collect(),
+            ),
+            Value::Array(items) => ("[", "]", items.iter().map(|v| (None, v)).collect()),
+            scalar => {
+                spans.push(highlight(scalar));
+                spans.push(Span::raw(comma));
+                rows.push(Row { path: None, spans });
+                return;
+            }
+        };
+        if children.is_empty() {
+            spans.push(Span::raw(format!("{open}{close}{comma}")));
+            rows.push(Row { path: None, spans });
+            return;
+        }
+        if self.collapsed.contains(&path) {
+            spans.push(Span::raw(format!("{open}…{close}{comma}")));
+            spans.push(Span::styled(
+                format!(" {} items", children.len()),
+                Style::default().fg(Color::DarkGray),
+            ));
+            rows.push(Row {
+                path: Some(path),
+                spans,
+            });
+            return;
+        }
+        spans.push(Span::raw(open));
+        rows.push(Row {
+            path: Some(path.clone()),
+            spans,
+        });
+        let count = children.len();
+        children
+            .into_iter()
+            .enumerate()
+            .for_each(|(index, (child_key, child))| {
+                let child_path = format!(
+                    "{path}/{}",
+                    child_key.map_or(index.to_string(), String::from)
+                );
+                self.render(
+                    child,
+                    child_key,
+                    child_path,
+                    depth + 1,
+                    index + 1 == count,
+                    rows,
+                );
+            });
+        rows.push(Row {
+            path: None,
+            spans: vec![Span::raw(format!("{}{close}{comma}", INDENT.repeat(depth)))],
+        });
+    }
+}
+
+fn highlight(scalar: &Value) -> Span<'static> {
+    let color = match scalar {
+        Value::String(_) => Color::Green,
+        Value::Number(_) => Color::Yellow,
+        Value::Bool(_) => Color::Magenta,
+        _ => Color::DarkGray,
+    };
+    Span::styled(scalar.to_string(), Style::default().fg(color))
+}
+
+fn collapse_deep(value: &Value, path: String, depth: usize, collapsed: &mut HashSet<String>) {
+    let children: Vec<(String, &Value)> = match value {
+        Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i.to_string(), v))
+            .collect(),
+        _ => return,
+    };
+    if depth >= EXPANDED_DEPTH {
+        collapsed.insert(path.clone());
+    }
+    children.into_iter().for_each(|(key, child)| {
+        collapse_deep(child, format!("{path}/{key}"), depth + 1, collapsed)
+    });
+}
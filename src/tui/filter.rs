@@ -0,0 +1,44 @@
+use std::fmt::{self, Display, Formatter};
+
+/// What the rows are narrowed by, text found anywhere in the row or label selectors.
+#[derive(Clone, Default)]
+pub struct Filter {
+    pub text: String,
+    /// the text is label selectors, e.g. `com.docker.compose.project=foo`
+    pub labels: bool,
+}
+
+impl Filter {
+    pub fn by_labels(labels: bool) -> Filter {
+        Filter {
+            text: String::new(),
+            labels,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.text.push(c);
+    }
+
+    pub fn pop(&mut self) {
+        self.text.pop();
+    }
+
+    /// Back to no filter at all, by text as when first opened.
+    pub fn clear(&mut self) {
+        *self = Filter::default();
+    }
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.labels {
+            true => write!(f, "label {}", self.text),
+            false => write!(f, "/{}", self.text),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use super::input::Input;
+
+/// Fuzzy search over the rows of the listing, jumping the cursor to the pick, or over
+/// the actions of the palette.
+pub struct Finder {
+    title: &'static str,
+    input: Input,
+    /// `(index, text)` of everything that can be picked, e.g. the rows by content index
+    candidates: Vec<(usize, String)>,
+    /// indexes into `candidates`, best match first
+    matches: Vec<usize>,
+    pub list: ListState,
+}
+
+impl Finder {
+    pub fn new(title: &'static str, candidates: Vec<(usize, String)>) -> Finder {
+        let mut finder = Finder {
+            title,
+            input: Input::default(),
+            candidates,
+            matches: vec![],
+            list: ListState::default(),
+        };
+        finder.search();
+        finder
+    }
+
+    /// Edits the query, `false` when the key isn't an editing one.
+    pub fn edit(&mut self, key_event: &KeyEvent) -> bool {
+        let edited = self.input.edit(key_event);
+        if edited {
+            self.search();
+        }
+        edited
+    }
+
+    pub fn up(&mut self) {
+        let cursor = self.list.selected().unwrap_or_default().saturating_sub(1);
+        self.list.select(Some(cursor));
+    }
+
+    pub fn down(&mut self) {
+        let last = self.matches.len().saturating_sub(1);
+        let cursor = (self.list.selected().unwrap_or_default() + 1).min(last);
+        self.list.select(Some(cursor));
+    }
+
+    /// The index of the highlighted match, as it came with its candidate.
+    pub fn picked(&self) -> Option<usize> {
+        let candidate = self.matches.get(self.list.selected()?)?;
+        self.candidates.get(*candidate).map(|(index, _)| *index)
+    }
+
+    pub fn render<B: Backend>(
+        &mut self,
+        f: &mut Frame<B>,
+        area: Rect,
+        highlight: Style,
+        borders: Borders,
+    ) {
+        let popup = Rect {
+            x: area.x + area.width / 10,
+            y: area.y + area.height / 10,
+            width: area.width - area.width / 5,
+            height: area.height - area.height / 5,
+        };
+        let block = Block::default().borders(borders).title(self.title);
+        let inner = block.inner(popup);
+        f.render_widget(Clear, popup);
+        f.render_widget(block, popup);
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+            .split(inner);
+
+        let query = Spans::from(vec![Span::raw("> "), Span::raw(self.input.value())]);
+        f.render_widget(Paragraph::new(query), parts[0]);
+        let x = parts[0].x + 2 + (self.input.value().chars().count() - self.input.tail()) as u16;
+        if x < parts[0].right() {
+            f.set_cursor(x, parts[0].y);
+        }
+
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .filter_map(|candidate| self.candidates.get(*candidate))
+            .map(|(_, text)| ListItem::new(text.as_str()))
+            .collect();
+        let list = List::new(items).highlight_style(highlight);
+        f.render_stateful_widget(list, parts[1], &mut self.list);
+    }
+
+    fn search(&mut self) {
+        let query = self.input.value().to_lowercase();
+        let mut scored: Vec<(i64, usize)> = self
+            .candidates
+            .iter()
+            .enumerate()
+            .filter_map(|(candidate, (_, text))| Some((score(&query, text)?, candidate)))
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        self.matches = scored.into_iter().map(|(_, candidate)| candidate).collect();
+        self.list.select(match self.matches.is_empty() {
+            true => None,
+            false => Some(0),
+        });
+    }
+}
+
+/// Whether `query` appears in `text` in order, not necessarily together. Runs of
+/// consecutive chars and matches starting a word score higher.
+fn score(query: &str, text: &str) -> Option<i64> {
+    let mut query = query.chars().filter(|c| !c.is_whitespace()).peekable();
+    let mut score = 0;
+    let mut run = 0;
+    let mut previous = ' ';
+    for c in text.to_lowercase().chars() {
+        let Some(wanted) = query.peek() else {
+            break;
+        };
+        if c == *wanted {
+            query.next();
+            run += 1;
+            score += run;
+            if !previous.is_alphanumeric() {
+                score += 3;
+            }
+        } else {
+            run = 0;
+        }
+        previous = c;
+    }
+    match query.peek() {
+        Some(_) => None,
+        None => Some(score),
+    }
+}
@@ -0,0 +1,118 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::input::Input;
+
+const KEYS: &str = "press 'tab'/'up'/'down' = field, 'enter' = apply, 'esc' = cancel";
+const WIDTH: u16 = 64;
+
+/// One labelled input of a form, sent as `name=value` when filled in.
+pub struct Field {
+    pub name: &'static str,
+    pub label: &'static str,
+    pub hint: &'static str,
+    pub input: Input,
+}
+
+/// A few inputs in a dialog over the content, left empty they're left out.
+pub struct Form {
+    pub title: String,
+    pub fields: Vec<Field>,
+    focus: usize,
+}
+
+impl Form {
+    pub fn new(title: String, fields: Vec<Field>) -> Form {
+        Form {
+            title,
+            fields,
+            focus: 0,
+        }
+    }
+
+    /// Moves between fields or edits the focused one, `false` when the key does neither.
+    pub fn edit(&mut self, key_event: &KeyEvent) -> bool {
+        let count = self.fields.len().max(1);
+        match key_event.code {
+            KeyCode::Tab | KeyCode::Down => self.focus = (self.focus + 1) % count,
+            KeyCode::BackTab | KeyCode::Up => self.focus = (self.focus + count - 1) % count,
+            _ => {
+                return self
+                    .fields
+                    .get_mut(self.focus)
+                    .is_some_and(|field| field.input.edit(key_event))
+            }
+        }
+        true
+    }
+
+    /// The filled in fields as `name=value` words.
+    pub fn values(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .filter(|field| !field.input.value().trim().is_empty())
+            .map(|field| format!("{}={}", field.name, field.input.value().trim()))
+            .collect()
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, borders: Borders) {
+        let label_width = self
+            .fields
+            .iter()
+            .map(|field| field.label.len())
+            .max()
+            .unwrap_or_default();
+        let mut lines = vec![];
+        self.fields.iter().enumerate().for_each(|(index, field)| {
+            let style = match index == self.focus {
+                true => Style::default().fg(Color::Yellow),
+                false => Style::default(),
+            };
+            let value = match field.input.value().is_empty() {
+                true => Span::styled(field.hint, Style::default().fg(Color::DarkGray)),
+                false => Span::raw(field.input.shown()),
+            };
+            lines.push(Spans::from(vec![
+                Span::styled(format!("{:>label_width$}: ", field.label), style),
+                value,
+            ]));
+        });
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(Span::styled(
+            KEYS,
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        let width = WIDTH.min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        let block = Block::default()
+            .borders(borders)
+            .title(format!(" {} ", self.title));
+        let inner = block.inner(popup);
+        f.render_widget(Clear, popup);
+        f.render_widget(Paragraph::new(lines).block(block), popup);
+
+        // the terminal cursor sits where the focused field is typed
+        if let Some(field) = self.fields.get(self.focus) {
+            let typed = field.input.value().chars().count() - field.input.tail();
+            let x = inner.x + label_width as u16 + 2 + typed as u16;
+            f.set_cursor(
+                x.min(inner.right().saturating_sub(1)),
+                inner.y + self.focus as u16,
+            );
+        }
+    }
+}
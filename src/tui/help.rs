@@ -0,0 +1,62 @@
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+const TITLE: &str = " Help, press 'esc' to close ";
+
+/// Every keybinding in one overlay, a column per group.
+pub struct Help {
+    pub sections: Vec<(&'static str, Vec<(String, String)>)>,
+}
+
+impl Help {
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, borders: Borders) {
+        let popup = Rect {
+            x: area.x + area.width / 20,
+            y: area.y + area.height / 20,
+            width: area.width - area.width / 10,
+            height: area.height - area.height / 10,
+        };
+        let block = Block::default().borders(borders).title(TITLE);
+        let inner = block.inner(popup);
+        f.render_widget(Clear, popup);
+        f.render_widget(block, popup);
+
+        let count = self.sections.len().max(1) as u32;
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Ratio(1, count); count as usize])
+            .split(inner);
+        self.sections
+            .iter()
+            .zip(columns.iter())
+            .for_each(|((title, bindings), column)| {
+                let width = bindings
+                    .iter()
+                    .map(|(keys, _)| keys.chars().count())
+                    .max()
+                    .unwrap_or_default();
+                let lines: Vec<Spans> = bindings
+                    .iter()
+                    .map(|(keys, action)| {
+                        Spans::from(vec![
+                            Span::styled(
+                                format!("{keys:width$}  "),
+                                Style::default().fg(Color::Cyan),
+                            ),
+                            Span::raw(action.clone()),
+                        ])
+                    })
+                    .collect();
+                let section = Block::default()
+                    .borders(borders & Borders::TOP)
+                    .title(format!(" {title} "));
+                f.render_widget(Paragraph::new(lines).block(section), *column);
+            });
+    }
+}
@@ -0,0 +1,148 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::ListState,
+};
+
+use tocker_core::clock;
+
+/// How to run an entry again, the same keys, a build of the same context or the same line.
+#[derive(Clone, Copy)]
+pub enum Rerun {
+    Keys(KeyEvent, KeyEvent),
+    Build,
+    /// a command line edited before running it
+    Line,
+}
+
+enum Status {
+    Running,
+    Done,
+    Failed(String),
+}
+
+pub struct Entry {
+    pub rerun: Rerun,
+    pub target: String,
+    line: String,
+    at: SystemTime,
+    status: Status,
+    /// everything it printed, kept once it failed
+    printed: Vec<String>,
+}
+
+/// Every docker command run this session, oldest first.
+#[derive(Default)]
+pub struct History {
+    entries: Vec<Entry>,
+    pub list: ListState,
+}
+
+impl History {
+    /// Records a command as running, `finish` it with the returned index.
+    pub fn push(&mut self, rerun: Rerun, target: &str, line: String) -> usize {
+        self.entries.push(Entry {
+            rerun,
+            target: String::from(target),
+            line,
+            at: SystemTime::now(),
+            status: Status::Running,
+            printed: vec![],
+        });
+        self.entries.len() - 1
+    }
+
+    pub fn finish(&mut self, index: usize, status: Result<(), String>) {
+        if let Some(entry) = self.entries.get_mut(index) {
+            entry.status = match status {
+                Ok(()) => Status::Done,
+                Err(err) => Status::Failed(err),
+            };
+        }
+    }
+
+    /// The commands still waiting for docker with the seconds they took so far, oldest
+    /// first.
+    pub fn running(&self) -> Vec<(&str, u64)> {
+        self.entries
+            .iter()
+            .filter(|entry| matches!(entry.status, Status::Running))
+            .map(|entry| {
+                let took = entry.at.elapsed().unwrap_or_default().as_secs();
+                (entry.line.as_str(), took)
+            })
+            .collect()
+    }
+
+    pub fn keep(&mut self, index: usize, printed: Vec<String>) {
+        if let Some(entry) = self.entries.get_mut(index) {
+            entry.printed = printed;
+        }
+    }
+
+    /// The line and output of the entry under the cursor, if it failed printing any.
+    pub fn printed(&self) -> Option<(&str, &[String])> {
+        let entry = self.selected()?;
+        match entry.printed.is_empty() {
+            true => None,
+            false => Some((entry.line.as_str(), entry.printed.as_slice())),
+        }
+    }
+
+    pub fn line(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(|entry| entry.line.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Opens on the latest entry.
+    pub fn open(&mut self) {
+        self.list.select(self.entries.len().checked_sub(1));
+    }
+
+    pub fn lines(&self) -> Vec<Spans<'static>> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let (status, color) = match &entry.status {
+                    Status::Running => (String::from("running"), Color::Yellow),
+                    Status::Done => (String::from("ok"), Color::Green),
+                    Status::Failed(err) => (format!("failed: {err}"), Color::Red),
+                };
+                Spans::from(vec![
+                    Span::styled(clock(unix(entry.at)), Style::default().fg(Color::DarkGray)),
+                    Span::raw("  "),
+                    Span::raw(entry.line.clone()),
+                    Span::raw("  "),
+                    Span::styled(status, Style::default().fg(color)),
+                ])
+            })
+            .collect()
+    }
+
+    pub fn up(&mut self) {
+        let cursor = self.list.selected().unwrap_or_default().saturating_sub(1);
+        self.list.select(Some(cursor));
+    }
+
+    pub fn down(&mut self) {
+        let last = self.entries.len().saturating_sub(1);
+        let cursor = (self.list.selected().unwrap_or_default() + 1).min(last);
+        self.list.select(Some(cursor));
+    }
+
+    pub fn selected(&self) -> Option<&Entry> {
+        self.entries.get(self.list.selected()?)
+    }
+}
+
+fn unix(at: SystemTime) -> i64 {
+    at.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,140 @@
+use std::{fs, path::PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Single line text prompt, `cursor` counts chars so editing is safe with any input.
+#[derive(Default)]
+pub struct Input {
+    text: String,
+    cursor: usize,
+    masked: bool,
+}
+
+impl Input {
+    /// Shows a `*` for every char typed, e.g. for a password.
+    pub fn masked() -> Input {
+        Input {
+            masked: true,
+            ..Default::default()
+        }
+    }
+
+    /// Starts out holding `text`, the cursor at its end.
+    pub fn filled(text: String) -> Input {
+        Input {
+            cursor: text.chars().count(),
+            text,
+            masked: false,
+        }
+    }
+
+    pub fn is_masked(&self) -> bool {
+        self.masked
+    }
+
+    pub fn value(&self) -> &str {
+        &self.text
+    }
+
+    /// What's drawn for the value, masked inputs never show it.
+    pub fn shown(&self) -> String {
+        match self.masked {
+            true => "*".repeat(self.text.chars().count()),
+            false => self.text.clone(),
+        }
+    }
+
+    /// Chars after the cursor, to place the terminal cursor from the end of the line.
+    pub fn tail(&self) -> usize {
+        self.text.chars().count() - self.cursor
+    }
+
+    /// Applies an editing key, `false` when the key isn't one.
+    pub fn edit(&mut self, key_event: &KeyEvent) -> bool {
+        let len = self.text.chars().count();
+        match key_event.code {
+            KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.text.insert(self.byte(self.cursor), c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.text.remove(self.byte(self.cursor));
+            }
+            KeyCode::Delete if self.cursor < len => {
+                self.text.remove(self.byte(self.cursor));
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(len),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len,
+            KeyCode::Backspace | KeyCode::Delete => {}
+            _ => return false,
+        }
+        true
+    }
+
+    /// Completes the host path being typed at the end of the line, as far as it's unambiguous.
+    /// `false` when there's nothing to add, e.g. the word is a `container:path`.
+    pub fn complete_path(&mut self) -> bool {
+        if self.tail() > 0 {
+            return false;
+        }
+        let word = self.text.rsplit(' ').next().unwrap_or_default();
+        if word.contains(':') && !word.starts_with(['/', '.', '~']) {
+            return false;
+        }
+        let Some(completed) = complete(word) else {
+            return false;
+        };
+        let start = self.text.len() - word.len();
+        self.text.replace_range(start.., &completed);
+        self.cursor = self.text.chars().count();
+        true
+    }
+
+    fn byte(&self, cursor: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.text.len(), |(index, _)| index)
+    }
+}
+
+/// The longest prefix every entry matching `word` shares, a dir gets its `/` once it's the only one.
+fn complete(word: &str) -> Option<String> {
+    let (dir, prefix) = match word.rfind('/') {
+        Some(slash) => word.split_at(slash + 1),
+        None => ("", word),
+    };
+    let listed = match (dir, dir.strip_prefix("~/").zip(dirs::home_dir())) {
+        (_, Some((rest, home))) => home.join(rest),
+        ("", None) => PathBuf::from("."),
+        (dir, None) => PathBuf::from(dir),
+    };
+    let matches: Vec<(String, bool)> = fs::read_dir(listed)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let is_dir = entry.path().is_dir();
+            name.starts_with(prefix).then_some((name, is_dir))
+        })
+        .collect();
+    let (first, is_dir) = matches.first()?;
+    let mut common = first.clone();
+    matches.iter().skip(1).for_each(|(name, _)| {
+        let shared = common
+            .chars()
+            .zip(name.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        common.truncate(shared);
+    });
+    if matches.len() == 1 && *is_dir {
+        common.push('/');
+    }
+    let completed = format!("{dir}{common}");
+    (completed != word).then_some(completed)
+}
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use tocker_core::{human_size, Change, Layer};
+
+const INDENT: &str = "  ";
+const FILES_TITLE: &str = " Files, + added  ~ modified  - removed ";
+
+/// Browses the layers of an image next to the files the selected one adds, changes or
+/// removes, like dive does.
+pub struct Explorer {
+    image: String,
+    layers: Vec<Layer>,
+    pub list: ListState,
+    tree: Vec<Spans<'static>>,
+    scroll: u16,
+}
+
+impl Explorer {
+    pub fn new(image: String, layers: Vec<Layer>) -> Explorer {
+        let mut explorer = Explorer {
+            image,
+            layers,
+            list: ListState::default(),
+            tree: vec![],
+            scroll: 0,
+        };
+        explorer.select(0);
+        explorer
+    }
+
+    pub fn up(&mut self) {
+        self.select(self.selected().saturating_sub(1));
+    }
+
+    pub fn down(&mut self) {
+        let last = self.layers.len().saturating_sub(1);
+        self.select((self.selected() + 1).min(last));
+    }
+
+    /// Scrolls the files of the selected layer, they can be far more than fit.
+    pub fn scroll(&mut self, down: bool, by: u16) {
+        let last = self.tree.len().saturating_sub(1) as u16;
+        self.scroll = match down {
+            true => (self.scroll + by).min(last),
+            false => self.scroll.saturating_sub(by),
+        };
+    }
+
+    pub fn render<B: Backend>(
+        &mut self,
+        f: &mut Frame<B>,
+        area: Rect,
+        highlight: Style,
+        borders: Borders,
+    ) {
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(area);
+        let items: Vec<ListItem> = self
+            .layers
+            .iter()
+            .map(|layer| {
+                let step = match layer.created_by.is_empty() {
+                    true => &layer.id,
+                    false => &layer.created_by,
+                };
+                ListItem::new(format!("{:>9}  {step}", human_size(layer.size as i64)))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(borders)
+                    .title(format!(" Layers of {} ", self.image)),
+            )
+            .highlight_style(highlight);
+        f.render_stateful_widget(list, panes[0], &mut self.list);
+
+        let files = Paragraph::new(self.tree.clone())
+            .block(Block::default().borders(borders).title(FILES_TITLE))
+            .scroll((self.scroll, 0));
+        f.render_widget(files, panes[1]);
+    }
+
+    fn selected(&self) -> usize {
+        self.list.selected().unwrap_or_default()
+    }
+
+    fn select(&mut self, index: usize) {
+        self.list.select(Some(index));
+        self.tree = self.layers.get(index).map(tree).unwrap_or_default();
+        self.scroll = 0;
+    }
+}
+
+/// The changed files as a tree, every directory with the size of what changed under it.
+fn tree(layer: &Layer) -> Vec<Spans<'static>> {
+    let mut sizes: BTreeMap<&str, u64> = BTreeMap::new();
+    layer
+        .files
+        .iter()
+        .filter(|file| file.change != Change::Removed)
+        .for_each(|file| {
+            file.path
+                .match_indices('/')
+                .for_each(|(slash, _)| *sizes.entry(&file.path[..slash]).or_default() += file.size);
+        });
+
+    let mut lines = vec![];
+    let mut open: Vec<&str> = vec![];
+    for file in &layer.files {
+        let parts: Vec<&str> = file.path.split('/').collect();
+        let (name, dirs) = parts.split_last().unwrap_or((&"", &[]));
+        let shared = open
+            .iter()
+            .zip(dirs.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        open.truncate(shared);
+        for (depth, dir) in dirs.iter().enumerate().skip(shared) {
+            let path = dirs[..=depth].join("/");
+            let size = sizes.get(path.as_str()).copied().unwrap_or_default();
+            lines.push(Spans::from(vec![
+                Span::raw(format!("{:>9}  ", human_size(size as i64))),
+                Span::styled(
+                    format!("{}{dir}/", INDENT.repeat(depth)),
+                    Style::default().fg(Color::Cyan),
+                ),
+            ]));
+            open.push(dir);
+        }
+        let (marker, color) = match file.change {
+            Change::Added => ('+', Color::Green),
+            Change::Modified => ('~', Color::Yellow),
+            Change::Removed => ('-', Color::Red),
+        };
+        let size = match file.change {
+            Change::Removed => String::new(),
+            _ => human_size(file.size as i64),
+        };
+        lines.push(Spans::from(Span::styled(
+            format!("{size:>9}  {}{marker} {name}", INDENT.repeat(dirs.len())),
+            Style::default().fg(color),
+        )));
+    }
+    if lines.is_empty() {
+        lines.push(Spans::from(Span::styled(
+            "No files changed by this layer",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    lines
+}
@@ -0,0 +1,274 @@
+use std::collections::VecDeque;
+
+use ratatui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState},
+    Frame,
+};
+use tokio_util::sync::CancellationToken;
+
+use tocker_core::LogOptions;
+
+/// Kept of a container's logs, the oldest lines go first past it.
+const KEPT_LINES: usize = 10_000;
+/// The names of merged containers in front of their lines, one color each in turn like
+/// `docker compose logs` does.
+const NAME_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Yellow,
+    Color::Green,
+    Color::Magenta,
+    Color::Blue,
+    Color::Red,
+];
+
+/// What's typed at the bottom of the log viewer.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Prompt {
+    /// the pattern searched for, after a `/`
+    Search,
+    /// how many of the newest lines it starts with
+    Tail,
+    /// how far back they go
+    Since,
+    /// the file the lines held are saved to
+    Save,
+}
+
+/// The logs of one container or several merged as they come, searched with `/pattern`
+/// and the matches highlighted, optionally the only lines shown.
+pub struct LogView {
+    pub names: Vec<String>,
+    /// every line with the index of its container in `names`
+    lines: VecDeque<(usize, String)>,
+    /// the line under the cursor, by its index in `lines`
+    cursor: usize,
+    /// the cursor moves along with the newest line
+    following: bool,
+    /// searched for regardless of case, nothing searched when empty
+    pub pattern: String,
+    pub typing: Option<Prompt>,
+    /// the tail, since or file being typed, the pattern is typed in place
+    pub typed: String,
+    /// lines without the pattern are hidden
+    only_matching: bool,
+    /// how they were asked for, followed again with the same ones
+    pub options: LogOptions,
+    /// one for each container, in the order of `names`
+    pub streams: Vec<CancellationToken>,
+    list: ListState,
+}
+
+impl LogView {
+    pub fn new(
+        names: Vec<String>,
+        options: LogOptions,
+        streams: Vec<CancellationToken>,
+    ) -> LogView {
+        LogView {
+            names,
+            lines: VecDeque::new(),
+            cursor: 0,
+            following: true,
+            pattern: String::new(),
+            typing: None,
+            typed: String::new(),
+            only_matching: false,
+            options,
+            streams,
+            list: ListState::default(),
+        }
+    }
+
+    /// Stops following the logs of every container.
+    pub fn stop(&self) {
+        self.streams.iter().for_each(CancellationToken::cancel);
+    }
+
+    pub fn push(&mut self, source: usize, line: String) {
+        self.lines.push_back((source, line));
+        if self.lines.len() > KEPT_LINES {
+            self.lines.pop_front();
+            self.cursor = self.cursor.saturating_sub(1);
+        }
+        if self.following {
+            self.cursor = self.lines.len() - 1;
+        }
+    }
+
+    /// How many lines there are so far.
+    pub fn held(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Every line held, each after the name of its container when merged, e.g. for a bug
+    /// report.
+    pub fn text(&self) -> String {
+        let merged = self.names.len() > 1;
+        self.lines
+            .iter()
+            .map(|(source, line)| match merged {
+                true => format!("{} | {line}\n", self.names[*source]),
+                false => format!("{line}\n"),
+            })
+            .collect()
+    }
+
+    /// Drops every line for the logs followed again from their tail, the cursor stays on
+    /// the same line once they're back.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    pub fn up(&mut self, by: usize) {
+        let shown = self.shown();
+        let at = self.position(&shown).unwrap_or_default();
+        if let Some(line) = shown.get(at.saturating_sub(by)) {
+            self.cursor = *line;
+        }
+        self.following = false;
+    }
+
+    pub fn down(&mut self, by: usize) {
+        let shown = self.shown();
+        let Some(last) = shown.len().checked_sub(1) else {
+            return;
+        };
+        let at = self.position(&shown).unwrap_or_default();
+        self.cursor = shown[(at + by).min(last)];
+        self.following = at + by >= last;
+    }
+
+    /// Goes to the newest line and stays on it as more come.
+    pub fn follow(&mut self) {
+        self.cursor = self.lines.len().saturating_sub(1);
+        self.following = true;
+    }
+
+    /// Moves to the next line holding the pattern, or the previous one, wrapping around,
+    /// `false` when no line holds it.
+    pub fn jump(&mut self, forward: bool) -> bool {
+        let count = self.lines.len();
+        let found = (1..=count)
+            .map(|step| match forward {
+                true => (self.cursor + step) % count,
+                false => (self.cursor + count - step % count) % count,
+            })
+            .find(|line| self.matches(&self.lines[*line].1));
+        if let Some(line) = found {
+            self.cursor = line;
+            self.following = false;
+        }
+        found.is_some()
+    }
+
+    /// Shows only the lines holding the pattern, or all of them again.
+    pub fn toggle_only_matching(&mut self) {
+        self.only_matching = !self.only_matching;
+    }
+
+    fn matching(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|(_, line)| self.matches(line))
+            .count()
+    }
+
+    pub fn render<B: Backend>(
+        &mut self,
+        f: &mut Frame<B>,
+        area: Rect,
+        cursor: Style,
+        highlight: Style,
+        borders: Borders,
+    ) {
+        let shown = self.shown();
+        self.list.select(self.position(&shown));
+        let title = self.title();
+        let merged = self.names.len() > 1;
+        let width = self.names.iter().map(|name| name.len()).max();
+        let items: Vec<ListItem> = shown
+            .iter()
+            .map(|line| {
+                let (source, line) = &self.lines[*line];
+                let mut spans = spans(line, &self.pattern, highlight);
+                if let (true, Some(width)) = (merged, width) {
+                    let color = NAME_COLORS[source % NAME_COLORS.len()];
+                    spans.0.insert(
+                        0,
+                        Span::styled(
+                            format!("{:<width$} | ", self.names[*source]),
+                            Style::default().fg(color),
+                        ),
+                    );
+                }
+                ListItem::new(spans)
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(borders).title(title))
+            .highlight_style(cursor);
+        f.render_stateful_widget(list, area, &mut self.list);
+    }
+
+    fn title(&self) -> String {
+        let mut title = format!(" Logs of {}", self.names.join(", "));
+        if !self.pattern.is_empty() {
+            title.push_str(&format!(" /{} ({} lines)", self.pattern, self.matching()));
+        }
+        if self.only_matching {
+            title.push_str(", only matching");
+        }
+        if self.following {
+            title.push_str(", following");
+        }
+        title.push(' ');
+        title
+    }
+
+    fn matches(&self, line: &str) -> bool {
+        !self.pattern.is_empty()
+            && line
+                .to_ascii_lowercase()
+                .contains(&self.pattern.to_ascii_lowercase())
+    }
+
+    /// The indexes of the lines on screen, every one unless only matching ones show.
+    fn shown(&self) -> Vec<usize> {
+        (0..self.lines.len())
+            .filter(|line| {
+                !self.only_matching || self.pattern.is_empty() || self.matches(&self.lines[*line].1)
+            })
+            .collect()
+    }
+
+    /// Where the cursor is among the lines shown, on the next one when its own is hidden.
+    fn position(&self, shown: &[usize]) -> Option<usize> {
+        shown
+            .iter()
+            .position(|line| *line >= self.cursor)
+            .or(shown.len().checked_sub(1))
+    }
+}
+
+/// `line` cut around the pattern, every match in the highlight style.
+fn spans<'a>(line: &'a str, pattern: &str, highlight: Style) -> Spans<'a> {
+    if pattern.is_empty() {
+        return Spans::from(line);
+    }
+    // ASCII lowercase keeps the byte offsets of the line
+    let lower = line.to_ascii_lowercase();
+    let pattern = pattern.to_ascii_lowercase();
+    let mut spans = vec![];
+    let mut last = 0;
+    lower.match_indices(&pattern).for_each(|(start, found)| {
+        spans.push(Span::raw(&line[last..start]));
+        spans.push(Span::styled(&line[start..start + found.len()], highlight));
+        last = start + found.len();
+    });
+    spans.push(Span::raw(&line[last..]));
+    Spans::from(spans)
+}
@@ -1,35 +1,261 @@
+mod browser;
+mod confirm;
+mod contexts;
+mod desktop;
+mod detail;
+mod filter;
+mod finder;
+mod form;
+mod help;
+mod history;
+mod input;
+mod layers;
+mod logs;
+mod pane;
+mod printed;
+mod theme;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
 use crossterm::{
-    event::{read, KeyEvent},
+    cursor::Show,
+    event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers},
     execute,
+    style::Print,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
-    backend::CrosstermBackend,
-    layout::{Alignment, Constraint, Direction, Layout},
-    style::{Color, Style},
+    backend::{Backend, CrosstermBackend},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
     terminal::CompletedFrame,
-    widgets::{Block, Borders, List, ListItem, Paragraph},
-    Terminal,
+    text::{Span, Spans},
+    widgets::{
+        Block, Borders, Cell, List, ListItem, ListState, Paragraph, Row as GridRow, Table as Grid,
+        TableState, Tabs, Wrap,
+    },
+    Frame, Terminal,
 };
 use std::{
-    io::{self, stdout, Error, ErrorKind, Stdout},
-    process::{exit, Output},
+    collections::{HashMap, HashSet, VecDeque},
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, stdout, Error, ErrorKind, Stdout, Write},
+    mem, panic,
+    path::{Path, PathBuf},
+    process::{self, exit, Command},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info};
+
+use browser::Browser;
+use confirm::Confirm;
+use contexts::Contexts;
+use detail::Detail;
+use filter::Filter;
+use finder::Finder;
+use form::{Field, Form};
+use help::Help;
+use history::{History, Rerun};
+use input::Input;
+use layers::Explorer;
+use logs::{LogView, Prompt};
+use printed::Printed;
+use theme::Theme;
+
+use tocker_core::{
+    human_size, parse_since, selects, usage_kind, BuildFile, Desktop, DockerKind, LogOptions,
+    Message, Moment, Output, Row, Select, SortBy, Table, TargetType, Tocker, View, Watch,
+    PINNED_TAIL,
 };
 
-use crate::tocker::{Message, Moment, Select, TargetType, Tocker};
+const INPUT_COMMANDS: &str = ", press 'enter' = confirm, 'esc' = cancel: \n > ";
+const BUILD_COMMANDS: &str =
+    "Type the context path and optionally the tag, e.g. '. app:latest', press 'enter' = build, 'esc' = cancel: \n > ";
+const EDIT_COMMANDS: &str =
+    "Edit the command line, e.g. append flags, press 'enter' = run, 'esc' = cancel: \n > ";
+const REBUILD_COMMANDS: &str = "Edited, build again? press 'enter' = run, 'esc' = skip: \n > ";
+const NAMED_COMMANDS: &str =
+    "Type a command by name, e.g. 'container rm web' or 'rm' for the tab on screen, press 'enter' = run, 'esc' = cancel: \n > ";
+const EXPORT_COMMANDS: &str =
+    "Export the visible rows, type a path ending in '.json' or '.csv', press 'enter' = write, 'esc' = cancel: \n > ";
+const STATS_COMMANDS: &str = "Live stats, refreshed every second: \n press 'esc' = back";
+const TOP_COMMANDS: &str = ", refreshed every second: \n press 'esc' = back";
+const DETAIL_COMMANDS: &str =
+    "Inspect: \n press 'up'/'down'/'pgup'/'pgdn' = scroll, 'enter' = collapse/expand, 'esc' = back";
+const DETAIL_PAGE: usize = 10;
+const LAYERS_COMMANDS: &str =
+    "Layers: \n press 'up'/'down' = layer, 'pgup'/'pgdn' = scroll files, 'esc' = back";
+const BROWSE_COMMANDS: &str =
+    "Volume: \n press 'up'/'down' = move, 'enter' = open folder, 'backspace' = folder above, 'esc' = back";
+const LOGS_COMMANDS: &str =
+    "Logs: \n press 'up'/'down'/'pgup'/'pgdn' = scroll, '/' = search, 'n'/'N' = next/previous match, 'f' = only matching lines, 't' = timestamps, 'l' = last lines, 's' = since, 'w' = save to a file, 'end' = follow, 'esc' = back";
+const SEARCH_COMMANDS: &str =
+    "Search the logs, press 'enter' = done, 'esc' = clear the search: \n /";
+const TAIL_COMMANDS: &str =
+    "How many of the newest lines to show, press 'enter' = done, 'esc' = cancel: \n ";
+const SINCE_COMMANDS: &str =
+    "How far back to show, e.g. 10m, 2h or 1d, nothing for all, press 'enter' = done, 'esc' = cancel: \n ";
+const SAVE_COMMANDS: &str =
+    "Save the lines loaded to a file, press 'enter' = save, 'esc' = cancel: \n ";
+/// The side pane follows only the newest lines, as printed.
+const PINNED_LOGS: LogOptions = LogOptions {
+    tail: PINNED_TAIL,
+    timestamps: false,
+    since: None,
+};
+/// Spaces between the columns of a listing, like the docker CLI prints them.
+const COLUMN_SPACING: u16 = 3;
+/// Left out of a listing too wide for the screen, first to last.
+const DROPPED_FIRST: [&str; 5] = ["COMMAND", "CREATED", "RESTART", "CONTAINER ID", "IMAGE ID"];
+/// Cut down no further to fit a listing, an ellipsis and a few characters still tell it.
+const NARROWEST_COLUMN: u16 = 8;
+/// Cut to `LONG_WIDTH` unless the listing shows names and paths in full.
+const LONG_COLUMNS: [&str; 5] = ["IMAGE", "REPOSITORY", "COMMAND", "MOUNTS", "MOUNTPOINT"];
+const LONG_WIDTH: u16 = 24;
+/// The color of a cell from its text, `None` to leave it as its row.
+type CellColor = fn(&str) -> Option<Color>;
+/// The columns colored by their own value rather than the state of their row.
+const MARKED: [(&str, CellColor); 2] =
+    [("HEALTH", health_color), ("RECLAIMABLE", reclaimable_color)];
+/// Dangling images listed in the confirmation, the rest are counted.
+const CLEANUP_SHOWN: usize = 10;
+const RECLAIMABLE_TITLE: &str = " reclaimable only ";
+const USAGE_TITLE: &str = " 'enter' = list what's reclaimable ";
+const RECORDING_TITLE: &str = " recording keys ";
+const UNREACHABLE_TITLE: &str = " daemon unreachable — retrying ";
+const UNREACHABLE_HELP: [&str; 5] = [
+    "Is it running? Start it, e.g. with `sudo systemctl start docker` or by opening",
+    "Docker Desktop, or point tocker at another one with --host or DOCKER_HOST.",
+    "",
+    "It's tried again every few seconds, everything picks up once it answers.",
+    "Press 'enter' to retry now.",
+];
+const FIND_TITLE: &str = " Find, press 'enter' = jump, 'esc' = close ";
+const PALETTE_TITLE: &str = " Actions, press 'enter' = run, 'esc' = close ";
+const FILTER_COMMANDS: &str = "Filter rows, press 'enter' = keep, 'esc' = clear: \n ";
+const LABELS_COMMANDS: &str =
+    "Filter by labels, e.g. 'com.docker.compose.project=foo', press 'enter' = keep, 'esc' = clear: \n ";
+const HISTORY_COMMANDS: &str =
+    "History: \n press 'up'/'down' = scroll, 'enter' = run again, 'o' = output of a failed one, 'esc' = back";
+const CONTEXT_COMMANDS: &str =
+    "Docker contexts: \n press 'up'/'down' = scroll, 'enter' = switch, 'esc' = back";
+const SORT_COMMANDS: &str =
+    "Sort by: \n press 'n' = name, 's' = size, 'c' = created, 't' = status (again = reverse), 'esc' = back";
+const SIGNAL_COMMANDS: &str =
+    "Send the signal: \n press 't' = SIGTERM, 'k' = SIGKILL, 'h' = SIGHUP, 'i' = SIGINT, 'u' = SIGUSR1, 'c' = custom, 'esc' = cancel";
+const FORM_COMMANDS: &str =
+    "Fill in the form, empty fields are left out: \n press 'tab'/'up'/'down' = field, 'enter' = apply, 'esc' = cancel";
+const POLICY_COMMANDS: &str =
+    "Restart policy: \n press 'n' = no, 'f' = on-failure, 'a' = always, 'u' = unless-stopped, 'c' = custom, 'esc' = cancel";
+const CONFIRM_COMMANDS: &str = "Waiting for confirmation: \n press 'y' = yes, 'n'/'esc' = no";
+const CONFIRM_QUESTION: &str = "Are you sure? Space that can be reclaimed:";
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const TICK_RATE: Duration = Duration::from_millis(100);
+const EVENTS_HEIGHT: u16 = 10;
+/// Older events are dropped, only the tail of the stream is ever shown.
+const EVENTS_KEPT: usize = 200;
+/// Of the width of the content, what the pinned logs take.
+const PINNED_WIDTH: u16 = 40;
+/// In tab bar order, the number keys pick them by position.
+const TABS: [DockerKind; 11] = [
+    DockerKind::Image,
+    DockerKind::Container,
+    DockerKind::Volume,
+    DockerKind::Project,
+    DockerKind::System,
+    DockerKind::Registry,
+    DockerKind::Service,
+    DockerKind::Stack,
+    DockerKind::Secret,
+    DockerKind::Config,
+    DockerKind::Fleet,
+];
 
-const INITIAL_COMMANDS: &str =
-    "Available commands: \n press 'i' = image, 'c' = container, 'v' = volume.";
-const TARGET_COMMANDS: &str = "Available commands: \n press 'space' = select, 'enter' = confirm";
+/// Everything the main loop reacts to, coming from the input thread or from docker jobs.
+pub enum AppEvent {
+    Input(Event),
+    Output(Result<Output, Error>),
+    Listing(Result<Output, Error>, DockerKind),
+    Refresh(Result<Output, Error>),
+    /// whether the daemon answered the latest ping
+    Ping(Result<Output, Error>),
+    Update(Result<Output, Error>),
+    Progress(Vec<String>),
+    Detail(Result<Output, Error>),
+    Usage(Result<Output, Error>),
+    Search(Result<Output, Error>),
+    /// the layers of the image named
+    Layers(Result<Output, Error>, String),
+    /// the files inside the volume named
+    Files(Result<Output, Error>, String),
+    /// only what a prune of the kind would remove
+    Reclaimable(Result<Output, Error>, DockerKind),
+    /// a command recorded in the history is done, with its error, exit code and what
+    /// it printed if it failed
+    Finished(usize, Result<(), String>, i32, Vec<String>),
+    Event(Result<String, Error>),
+    /// an event of any container, told if it's a watched one
+    Alert(Result<String, Error>),
+    /// a line of the pinned container's logs
+    Pinned(Result<String, Error>),
+    /// a line of the logs in the log viewer, with the index of its container there
+    Logged(usize, Result<String, Error>),
+    Tick,
+}
 
 struct ContentItem {
     text: String,
     selected: bool,
+    /// what commands target when the row is selected, `None` for headers and plain output
+    id: Option<String>,
+    /// the cells of a listing row, or of its header, empty for plain output
+    cells: Vec<String>,
+    color: Option<Color>,
+    /// the cell colored apart from the rest by its column, e.g. the health
+    mark: Option<(usize, Color)>,
+    labels: HashMap<String, String>,
+}
+
+impl ContentItem {
+    fn plain(text: String) -> ContentItem {
+        ContentItem {
+            text,
+            selected: false,
+            id: None,
+            cells: vec![],
+            color: None,
+            mark: None,
+            labels: HashMap::new(),
+        }
+    }
+}
+
+/// A listing put aside while another tab shows, as it was left.
+struct Tab {
+    content: Vec<ContentItem>,
+    table: Option<Table>,
+    cursor: usize,
+    filter: Filter,
+    sort: Option<(SortBy, bool)>,
+    reclaimable: bool,
 }
 
 struct Scroller {
-    // offset: usize,
     cursor: usize,
+    /// keeps the viewport offset between draws, so the view only moves to follow the cursor
+    list: ListState,
+    /// the same for a listing, drawn as a table
+    table: TableState,
+    /// rows the viewport showed last time, what a page moves by
+    page: usize,
 }
 
 pub struct AppState {
@@ -37,16 +263,127 @@ pub struct AppState {
     commands: String,
     moment: Moment,
     scroll: Scroller,
+    first: Option<KeyEvent>,
+    second: Option<KeyEvent>,
+    pending: usize,
+    spinner: usize,
+    watching: Option<CancellationToken>,
+    /// the background refresh of the listing on screen
+    refreshing: Option<CancellationToken>,
+    /// pinging the daemon all along
+    heartbeat: Option<CancellationToken>,
+    /// why the daemon stopped answering, until it does again
+    unreachable: Option<String>,
+    input: Input,
+    /// what the commands bar shows in front of the input
+    prompt: String,
+    building: bool,
+    /// the input is a whole docker command line, edited before it runs
+    editing: bool,
+    exporting: bool,
+    /// the input is a command by name, e.g. `container rm web`
+    naming: bool,
+    /// the first press of a key run by pressing it twice
+    twice: Option<KeyEvent>,
+    /// the target of a command waiting for its secret, e.g. the registry and username
+    /// of a login
+    secret_for: Option<String>,
+    /// the rows picked by a command that takes typed input after them
+    picked: Option<String>,
+    form: Option<Form>,
+    detail: Option<Detail>,
+    explorer: Option<Explorer>,
+    browser: Option<Browser>,
+    logs: Option<LogView>,
+    confirm: Option<Confirm>,
+    /// rows not matching it are hidden, the header always shows
+    filter: Filter,
+    /// the listing holds only what a prune would remove, picked from the disk usage
+    reclaimable: bool,
+    /// the dangling images listing on its way is to clean them up
+    dangling: bool,
+    previous: Moment,
+    /// the last listing, kept to sort and refresh it in place
+    table: Option<Table>,
+    sort: Option<(SortBy, bool)>,
+    /// the details pane shows next to the listing
+    split: bool,
+    /// long names, commands and mount paths show whole, wrapped in their cells
+    full: bool,
+    /// the filter left from the last run, for the first listing of its kind
+    restored: Option<(DockerKind, Filter)>,
+    /// the kind whose listing is on screen, if any
+    active: Option<DockerKind>,
+    tabs: HashMap<DockerKind, Tab>,
+    history: History,
+    /// what the failed entry under the cursor printed, over the history
+    printed: Option<Printed>,
+    help: Option<Help>,
+    /// one-off feedback in the content title, gone with the next key
+    notice: Option<String>,
+    /// the command line that ran last and its exit code, kept in the status bar
+    ran: Option<(String, i32)>,
+    /// following `docker events`, shown in a pane under the content
+    events: Option<CancellationToken>,
+    event_lines: VecDeque<String>,
+    /// the containers whose exits, restarts and health flips are told, by name
+    watched: HashSet<String>,
+    /// following the events for the watched containers, apart from the pane
+    alerts: Option<CancellationToken>,
+    /// what last happened to a watched container, highlighted until a key is pressed
+    alert: Option<String>,
+    /// the container whose logs are followed in a pane at the side, by name
+    pinned: Option<(String, CancellationToken)>,
+    pinned_lines: VecDeque<String>,
+    finder: Option<Finder>,
+    /// the keys of each action while the finder is the palette
+    palette: Option<Vec<Vec<KeyEvent>>>,
+    contexts: Option<Contexts>,
+    /// the last state change, read out on the status line in plain mode
+    announcement: String,
+    /// the keys pressed since recording started
+    recording: Option<Vec<KeyEvent>>,
+    /// keys done recording, waiting for a name and key to save them as a macro
+    recorded: Option<Vec<KeyEvent>>,
+    /// what's left to press of the macro being replayed
+    replaying: VecDeque<KeyEvent>,
+}
+
+impl AppState {
+    fn cursor_row(&self) -> Option<&Row> {
+        let id = self.content.get(self.scroll.cursor)?.id.as_ref()?;
+        self.table.as_ref()?.rows.iter().find(|row| &row.id == id)
+    }
 }
 
 pub struct Tui {
     terminal: Terminal<CrosstermBackend<Stdout>>,
     tocker: Tocker,
+    theme: Theme,
     state: AppState,
+    sender: Sender<AppEvent>,
+    events: Receiver<AppEvent>,
+    input: InputGate,
+    /// for screen readers: no box drawing, labelled rows and a status line
+    plain: bool,
+}
+
+/// Lets the ui take stdin away from the input thread, e.g. while a shell runs.
+#[derive(Clone, Default)]
+struct InputGate {
+    paused: Arc<AtomicBool>,
+    polling: Arc<Mutex<()>>,
 }
 
 impl Tui {
-    pub fn new() -> Result<Tui, Error> {
+    pub fn new(plain: bool) -> Result<Tui, Error> {
+        // a panic would otherwise print into, and leave behind, the raw alternate screen
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal().ok();
+            default_hook(info);
+        }));
+
         //clear screen
         enable_raw_mode()?;
         execute!(stdout(), EnterAlternateScreen)?;
@@ -55,321 +392,3453 @@ impl Tui {
         let backend = CrosstermBackend::new(stdout());
         let terminal = Terminal::new(backend)?;
 
-        // tocker services
-        let tocker = Tocker::new();
+        // tocker services, an invalid config or an endpoint that can't be used at all is
+        // told on the restored terminal before leaving
+        let tocker = Tocker::new().unwrap_or_else(|err| {
+            restore_terminal().ok();
+            eprintln!("{err}");
+            exit(1)
+        });
+        let mut theme = Theme::new(tocker.get_theme())?;
+        if plain {
+            theme.borders = Borders::NONE;
+        }
 
         // initial state
-        let initial_commands = String::from(INITIAL_COMMANDS);
+        let initial_commands = tocker.get_initial_commands().clone();
         let initial_content: Vec<ContentItem> = vec![];
         let initial_moment = Moment::KIND;
 
         // initial scroll
         let initial_scroll = Scroller {
-            // offset: 0,
             cursor: 0,
+            list: ListState::default(),
+            table: TableState::default(),
+            page: 1,
         };
 
+        // events from the input thread and the docker jobs
+        let (sender, events) = channel();
+
         // instantiate the tui program
         Ok(Tui {
             terminal,
             tocker,
+            theme,
             state: AppState {
                 content: initial_content,
                 commands: initial_commands,
                 moment: initial_moment,
                 scroll: initial_scroll,
+                first: None,
+                second: None,
+                pending: 0,
+                spinner: 0,
+                watching: None,
+                refreshing: None,
+                heartbeat: None,
+                unreachable: None,
+                input: Input::default(),
+                prompt: String::new(),
+                building: false,
+                editing: false,
+                exporting: false,
+                naming: false,
+                twice: None,
+                secret_for: None,
+                picked: None,
+                form: None,
+                detail: None,
+                explorer: None,
+                browser: None,
+                logs: None,
+                confirm: None,
+                filter: Filter::default(),
+                reclaimable: false,
+                dangling: false,
+                previous: Moment::KIND,
+                table: None,
+                sort: None,
+                split: false,
+                full: false,
+                restored: None,
+                active: None,
+                tabs: HashMap::new(),
+                history: History::default(),
+                printed: None,
+                help: None,
+                notice: None,
+                ran: None,
+                events: None,
+                event_lines: VecDeque::new(),
+                watched: HashSet::new(),
+                alerts: None,
+                alert: None,
+                pinned: None,
+                pinned_lines: VecDeque::new(),
+                finder: None,
+                palette: None,
+                contexts: None,
+                announcement: String::new(),
+                recording: None,
+                recorded: None,
+                replaying: VecDeque::new(),
             },
+            sender,
+            events,
+            input: InputGate::default(),
+            plain,
         })
     }
 
-    pub fn draw_ui(&mut self) -> io::Result<CompletedFrame> {
+    pub fn draw_ui(&mut self) -> io::Result<CompletedFrame<'_>> {
         self.terminal.draw(|f| {
+            // in plain mode the last line announces what changed
+            let screen = match self.plain {
+                true => {
+                    let parts = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+                        .split(f.size());
+                    f.render_widget(Paragraph::new(self.state.announcement.as_str()), parts[1]);
+                    parts[0]
+                }
+                false => f.size(),
+            };
             // scaffold ui
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .margin(0)
                 .constraints([Constraint::Percentage(90), Constraint::Percentage(10)].as_ref())
-                .split(f.size());
-            // content
+                .split(screen);
+            // a watched container's alert goes under the tab bar
+            let alert = match self.state.alert.is_some() {
+                true => 1,
+                false => 0,
+            };
+            let top = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [
+                        Constraint::Length(1),
+                        Constraint::Length(alert),
+                        Constraint::Min(0),
+                    ]
+                    .as_ref(),
+                )
+                .split(chunks[0]);
+            // tab bar, one per kind
+            let titles = TABS
+                .iter()
+                .enumerate()
+                .map(|(index, kind)| {
+                    // whether the stopped containers are listed too
+                    let shown = match (kind, self.tocker.all_containers()) {
+                        (DockerKind::Container, true) => " (all)",
+                        (DockerKind::Container, false) => " (running)",
+                        _ => "",
+                    };
+                    Spans::from(format!("{} {}{shown}", index + 1, tab_title(kind)))
+                })
+                .collect();
+            let mut tabs = Tabs::new(titles);
+            if self.plain {
+                tabs = tabs.divider(",");
+            }
+            if let Some(index) = TABS
+                .iter()
+                .position(|kind| Some(*kind) == self.state.active)
+            {
+                tabs = tabs.select(index).highlight_style(self.theme.tab);
+            }
+            f.render_widget(tabs, top[0]);
+            if let Some(alert) = &self.state.alert {
+                f.render_widget(
+                    Paragraph::new(alert.as_str()).style(self.theme.highlight),
+                    top[1],
+                );
+            }
+            // the events pane takes the bottom of the content while it follows
+            let area = match self.state.events {
+                Some(_) => {
+                    let parts = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints(
+                            [Constraint::Min(0), Constraint::Length(EVENTS_HEIGHT)].as_ref(),
+                        )
+                        .split(top[2]);
+                    let shown = EVENTS_HEIGHT.saturating_sub(2) as usize;
+                    let skip = self.state.event_lines.len().saturating_sub(shown);
+                    let lines: Vec<ListItem> = self
+                        .state
+                        .event_lines
+                        .iter()
+                        .skip(skip)
+                        .map(|line| ListItem::new(line.as_str()))
+                        .collect();
+                    let pane = List::new(lines).block(
+                        Block::default()
+                            .borders(self.theme.borders)
+                            .title(" Events "),
+                    );
+                    f.render_widget(pane, parts[1]);
+                    parts[0]
+                }
+                None => top[2],
+            };
+            // the pinned logs take the right of the content, whatever it shows
+            let area = match &self.state.pinned {
+                Some((name, _)) => {
+                    let parts = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints(
+                            [Constraint::Min(0), Constraint::Percentage(PINNED_WIDTH)].as_ref(),
+                        )
+                        .split(area);
+                    let shown = parts[1].height.saturating_sub(2) as usize;
+                    let skip = self.state.pinned_lines.len().saturating_sub(shown);
+                    let lines: Vec<ListItem> = self
+                        .state
+                        .pinned_lines
+                        .iter()
+                        .skip(skip)
+                        .map(|line| ListItem::new(line.as_str()))
+                        .collect();
+                    let pane = List::new(lines).block(
+                        Block::default()
+                            .borders(self.theme.borders)
+                            .title(format!(" Logs of {name} ")),
+                    );
+                    f.render_widget(pane, parts[1]);
+                    parts[0]
+                }
+                None => area,
+            };
+            // content, the viewport follows the cursor among the visible rows
+            let position = self
+                .state
+                .content
+                .iter()
+                .enumerate()
+                .take(self.state.scroll.cursor)
+                .filter(|(index, item)| matches(&self.state.filter, *index, item))
+                .count();
+            // a listing keeps its header above the rows as they scroll, and only the
+            // columns that fit next to each other, the details pane takes the rest
+            let width = match self.state.split {
+                true => area.width * 3 / 5,
+                false => area.width,
+            };
+            let columns = fit_columns(
+                &self.state.content,
+                width.saturating_sub(2),
+                self.state.full,
+            );
+            let widths: Vec<Constraint> = columns
+                .iter()
+                .map(|(_, width)| Constraint::Length(*width))
+                .collect();
+            let grid = (!columns.is_empty()).then(|| {
+                listing_grid(
+                    &self.state.content,
+                    &self.state.filter,
+                    self.state.scroll.cursor,
+                    &self.theme,
+                    &columns,
+                    &widths,
+                    self.state.full,
+                )
+            });
+            self.state.scroll.list.select(Some(position));
+            self.state.scroll.table.select(position.checked_sub(1));
+            let chrome = match grid.is_some() {
+                true => 3,
+                false => 2,
+            };
+            self.state.scroll.page = area.height.saturating_sub(chrome).max(1) as usize;
             let items: Vec<ListItem> = self
                 .state
                 .content
                 .iter()
                 .enumerate()
-                .map(|(index, item)| {
-                    ListItem::new(item.text.as_ref()).style(
-                        match index == self.state.scroll.cursor {
-                            true => match self.state.scroll.cursor == 0 {
-                                true => Style::default(),
-                                false => Style::default().bg(Color::Cyan).fg(Color::Black),
-                            },
-                            false => match item.selected {
-                                true => Style::default().bg(Color::Gray).fg(Color::Black),
-                                false => Style::default(),
-                            },
+                .filter(|(index, item)| matches(&self.state.filter, *index, item))
+                .map(|(index, item)| match index == self.state.scroll.cursor {
+                    true => ListItem::new(item.text.as_ref()).style(
+                        match self.state.scroll.cursor == 0 {
+                            true => Style::default(),
+                            false => self.theme.cursor,
                         },
-                    )
+                    ),
+                    false => match (item.selected, item.color) {
+                        (true, _) => ListItem::new(item.text.as_ref()).style(self.theme.selected),
+                        (false, Some(color)) => {
+                            ListItem::new(item.text.as_ref()).style(Style::default().fg(color))
+                        }
+                        (false, None) => ListItem::new(item.text.as_ref()),
+                    },
                 })
                 .collect();
-            f.render_widget(
-                List::new(items).block(Block::default().borders(Borders::ALL)),
-                chunks[0],
-            );
+            // jobs still running on the worker
+            let mut block = Block::default().borders(self.theme.borders);
+            let mut title = String::new();
+            if self.state.unreachable.is_some() {
+                title.push_str(UNREACHABLE_TITLE);
+            }
+            match (self.state.pending > 0, self.plain) {
+                (true, true) => title.push_str(" working… "),
+                (true, false) => {
+                    title.push_str(&format!(" {} working… ", SPINNER[self.state.spinner]))
+                }
+                _ => {}
+            }
+            if !self.state.filter.is_empty() {
+                title.push_str(&format!(" {} ", self.state.filter));
+            }
+            if self.state.reclaimable {
+                title.push_str(RECLAIMABLE_TITLE);
+            }
+            if self.state.active == Some(DockerKind::System) && self.state.table.is_some() {
+                title.push_str(USAGE_TITLE);
+            }
+            if self.state.recording.is_some() {
+                title.push_str(RECORDING_TITLE);
+            }
+            if let Some(notice) = &self.state.notice {
+                title.push_str(&format!(" {notice} "));
+            }
+            if !title.is_empty() {
+                block = block.title(title);
+            }
+            match (&self.state.moment, &mut self.state.detail) {
+                (Moment::DETAIL, Some(detail)) => {
+                    let lines: Vec<ListItem> =
+                        detail.lines().into_iter().map(ListItem::new).collect();
+                    let list = List::new(lines)
+                        .block(block)
+                        .highlight_style(self.theme.highlight);
+                    f.render_stateful_widget(list, area, &mut detail.list);
+                }
+                (Moment::LAYERS, _) if self.state.explorer.is_some() => {
+                    if let Some(explorer) = &mut self.state.explorer {
+                        explorer.render(f, area, self.theme.highlight, self.theme.borders);
+                    }
+                }
+                (Moment::BROWSE, _) if self.state.browser.is_some() => {
+                    if let Some(browser) = &mut self.state.browser {
+                        browser.render(f, area, self.theme.highlight, self.theme.borders);
+                    }
+                }
+                (Moment::LOGS, _) if self.state.logs.is_some() => {
+                    if let Some(logs) = &mut self.state.logs {
+                        logs.render(
+                            f,
+                            area,
+                            self.theme.highlight,
+                            self.theme.selected,
+                            self.theme.borders,
+                        );
+                    }
+                }
+                // nothing listed yet, e.g. the daemon was down from the start
+                (Moment::KIND, _)
+                    if self.state.unreachable.is_some() && self.state.table.is_none() =>
+                {
+                    let why = self.state.unreachable.clone().unwrap_or_default();
+                    let mut lines = vec![
+                        Spans::from(Span::styled(
+                            format!(
+                                "Cannot reach the Docker daemon at {}",
+                                self.tocker.get_host()
+                            ),
+                            Style::default().fg(Color::Red),
+                        )),
+                        Spans::from(""),
+                        Spans::from(why),
+                        Spans::from(""),
+                    ];
+                    lines.extend(UNREACHABLE_HELP.iter().map(|line| Spans::from(*line)));
+                    let screen = Paragraph::new(lines)
+                        .block(block)
+                        .wrap(Wrap { trim: false });
+                    f.render_widget(screen, area);
+                }
+                (Moment::HISTORY, _) => {
+                    let lines: Vec<ListItem> = self
+                        .state
+                        .history
+                        .lines()
+                        .into_iter()
+                        .map(ListItem::new)
+                        .collect();
+                    let list = List::new(lines)
+                        .block(block.title(" History "))
+                        .highlight_style(self.theme.highlight);
+                    f.render_stateful_widget(list, area, &mut self.state.history.list);
+                }
+                (Moment::CONTEXT, _) if self.state.contexts.is_some() => {
+                    if let Some(contexts) = &mut self.state.contexts {
+                        let lines: Vec<ListItem> =
+                            contexts.lines().into_iter().map(ListItem::new).collect();
+                        let list = List::new(lines)
+                            .block(block.title(" Contexts "))
+                            .highlight_style(self.theme.highlight);
+                        f.render_stateful_widget(list, area, &mut contexts.list);
+                    }
+                }
+                _ if self.state.split => {
+                    let panes = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                        .split(area);
+                    render_content(f, panes[0], block, items, grid, &mut self.state.scroll);
+                    let row = self
+                        .state
+                        .content
+                        .get(self.state.scroll.cursor)
+                        .and_then(|item| item.id.as_ref())
+                        .zip(self.state.table.as_ref())
+                        .and_then(|(id, table)| table.rows.iter().find(|row| &row.id == id));
+                    pane::render(f, panes[1], row, self.theme.borders);
+                }
+                _ => render_content(f, area, block, items, grid, &mut self.state.scroll),
+            }
+            if let Some(printed) = &mut self.state.printed {
+                printed.render(f, area, self.theme.highlight, self.theme.borders);
+            }
+            if let Some(confirm) = &self.state.confirm {
+                confirm.render(f, area, self.theme.borders);
+            }
+            if let Some(form) = &self.state.form {
+                form.render(f, area, self.theme.borders);
+            }
+            if let Some(help) = &self.state.help {
+                help.render(f, area, self.theme.borders);
+            }
+            if let Some(finder) = &mut self.state.finder {
+                finder.render(f, area, self.theme.highlight, self.theme.borders);
+            }
             // display available commands
+            let host = match self.tocker.get_context() {
+                Some(context) => format!("{context} ({})", self.tocker.get_host()),
+                None => self.tocker.get_host().clone(),
+            };
+            let (separator, arrow) = match self.plain {
+                true => (",", " > "),
+                false => (" │", " ▸ "),
+            };
+            // where the keys pressed so far led, e.g. `container ▸ rm ▸ selecting (3 chosen)`
+            let mut crumbs = self
+                .tocker
+                .crumbs(self.state.first.as_ref(), self.state.second.as_ref());
+            crumbs.push(match self.state.moment {
+                Moment::TARGET => {
+                    let chosen = self
+                        .state
+                        .content
+                        .iter()
+                        .filter(|item| item.selected)
+                        .count();
+                    format!("{} ({chosen} chosen)", moment_label(&Moment::TARGET))
+                }
+                moment => String::from(moment_label(&moment)),
+            });
+            let crumbs = crumbs.join(arrow);
+            // what docker is busy with, slow ones like removing a huge image look stuck
+            // otherwise, the last one that finished once it's done
+            let running = self.state.history.running();
+            let mut title = vec![Span::raw(format!(" {crumbs}{separator} {host}"))];
+            match (running.first(), &self.state.ran) {
+                (Some((line, took)), _) => {
+                    let busy = match self.plain {
+                        true => "running",
+                        false => SPINNER[self.state.spinner],
+                    };
+                    let more = match running.len() {
+                        1 => String::new(),
+                        count => format!(" (+{} more)", count - 1),
+                    };
+                    title.push(Span::raw(format!(
+                        "{separator} {busy} {line} {took}s{more} "
+                    )));
+                }
+                (None, Some((line, code))) => {
+                    let (badge, color) = match (*code == 0, self.plain) {
+                        (true, true) => ("ok", Color::Green),
+                        (true, false) => ("✔", Color::Green),
+                        (false, true) => ("failed", Color::Red),
+                        (false, false) => ("✘", Color::Red),
+                    };
+                    title.push(Span::raw(format!("{separator} ")));
+                    title.push(Span::styled(badge, Style::default().fg(color)));
+                    title.push(Span::raw(format!(" $ {line} → exit {code} ")));
+                }
+                (None, None) => title.push(Span::raw(" ")),
+            }
+            let block = Block::default()
+                .borders(self.theme.borders)
+                .title(Spans::from(title));
+            let inner = block.inner(chunks[1]);
             let p = Paragraph::new(self.state.commands.as_ref())
-                .block(Block::default().borders(Borders::ALL))
-                .style(Style::default().fg(Color::White).bg(Color::Black))
+                .block(block)
+                .style(self.theme.commands)
                 .alignment(Alignment::Left);
             f.render_widget(p, chunks[1]);
+            // the terminal cursor marks where typing goes
+            if self.state.moment == Moment::INPUT && self.state.help.is_none() {
+                let lines: Vec<&str> = self.state.commands.split('\n').collect();
+                let column =
+                    lines.last().map_or(0, |line| line.chars().count()) - self.state.input.tail();
+                let x = inner.x + column as u16;
+                let y = inner.y + lines.len() as u16 - 1;
+                if x < inner.right() && y < inner.bottom() {
+                    f.set_cursor(x, y);
+                }
+            }
         })
     }
 
     fn extract_target_string(&mut self) -> String {
-        let mut string = String::from("");
-        let mut id_column_index: usize = 0;
         self.state
             .content
             .iter()
             .enumerate()
-            .for_each(|(index, item)| {
-                if index == 0 {
-                    item.text
-                        .split_whitespace()
-                        .enumerate()
-                        .for_each(|(index, content)| {
-                            if content.contains("ID") {
-                                id_column_index = index - 1; //"container id", "image id"
-                            }
-                        });
-                }
-                if index > 0 && item.selected {
-                    item.text
-                        .split_whitespace()
-                        .enumerate()
-                        .for_each(|(index, content)| {
-                            if index == id_column_index {
-                                string.push_str(" ");
-                                string.push_str(content.as_ref());
-                            }
-                        });
-                }
-            });
-        String::from(string.trim())
+            .filter(|(index, item)| item.selected && matches(&self.state.filter, *index, item))
+            .map(|(_, item)| item)
+            .filter_map(|item| item.id.as_deref())
+            .collect::<Vec<&str>>()
+            .join(" ")
     }
 
-    pub fn get_target(&mut self, first: &KeyEvent, second: &KeyEvent) -> Result<String, Error> {
-        self.update_moment(Moment::TARGET);
-        let target_type = self.check_combination(&first, &second)?;
-        match target_type {
-            TargetType::SELECT => loop {
-                self.update_commands_target()?;
-                let key_event = self.extract_key_event()?;
-                let select = self.check_select(key_event)?;
-                match select {
-                    Select::UP => self.add_cursor(),
-                    Select::DOWN => self.sub_cursor(),
-                    Select::SELECT => match self.state.content.get_mut(self.state.scroll.cursor) {
-                        Some(item) => item.selected = !item.selected,
-                        None => {}
-                    },
-                    Select::CANCEL => {
-                        self.go_to_first();
-                        break;
+    fn on_target(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        let select = self.check_select(key_event)?;
+        match select {
+            Select::UP => self.add_cursor(),
+            Select::DOWN => self.sub_cursor(),
+            Select::SELECT => {
+                if let Some(item) = self.state.content.get_mut(self.state.scroll.cursor) {
+                    item.selected = !item.selected
+                }
+            }
+            Select::CANCEL => self.go_to_first(),
+            Select::CONFIRM => {
+                let target_string = self.extract_target_string();
+                match (self.state.first, self.state.second) {
+                    (Some(first), Some(second)) if self.tocker.dangling_cmd(&second) => {
+                        self.go_to_cleanup(&first, target_string)?
                     }
-                    Select::CONFIRM => {
-                        break;
+                    (Some(first), Some(second))
+                        if matches!(
+                            self.check_combination(&first, &second),
+                            Ok(TargetType::SELECTINPUT)
+                        ) =>
+                    {
+                        self.state.picked = Some(target_string);
+                        if self.tocker.signal_cmd(&second) {
+                            self.go_to_signal(&second);
+                        } else if self.tocker.policy_cmd(&second) {
+                            self.go_to_policy(&second);
+                        } else if self.tocker.form_cmd(&second) {
+                            self.go_to_form(&second);
+                        } else {
+                            self.go_to_input(&second);
+                        }
                     }
+                    _ => self.execute_cmd(&target_string)?,
                 }
-            },
-            _ => {}
+            }
+            Select::FILTER => self.go_to_filter(false),
+            Select::LABELS => self.go_to_filter(true),
+            Select::NEXT => self.add_cursor(),
+            Select::PREV => self.sub_cursor(),
+            Select::SORT => self.go_to_sort(),
+            Select::COPY => self.copy(false)?,
+            Select::COPYNAME => self.copy(true)?,
+            Select::EXPORT => self.go_to_export()?,
+            Select::ALL => self.select_rows(|_| true),
+            Select::INVERT => self.select_rows(|selected| !selected),
+            Select::CLEAR => self.select_rows(|_| false),
+            Select::PAGEUP => self.page_cursor(false),
+            Select::PAGEDOWN => self.page_cursor(true),
+            Select::FIRST => self.jump_cursor(false),
+            Select::LAST => self.jump_cursor(true),
+            Select::EDIT => self.go_to_edit()?,
+            Select::PREVTAB => self.step_tab(false)?,
+            Select::NEXTTAB => self.step_tab(true)?,
+            Select::COMMAND => self.go_to_named(),
+            Select::TRUNCATE => self.toggle_full(),
+            // listing them again would drop the picked rows
+            Select::DIGESTS => {}
+            Select::FAVORITE => self.toggle_favorite()?,
+            Select::WATCH => self.toggle_watch()?,
         }
-        let target_string = self.extract_target_string();
-        Ok(target_string)
+        if self.state.moment == Moment::TARGET {
+            self.update_commands_target();
+        }
+        Ok(())
     }
 
-    fn check_select(&mut self, key_event: KeyEvent) -> Result<&Select, Error> {
-        self.tocker.check_select(key_event)
+    /// Sets the selection of every visible row from its current one.
+    fn select_rows(&mut self, select: fn(bool) -> bool) {
+        let filter = self.state.filter.clone();
+        self.state
+            .content
+            .iter_mut()
+            .enumerate()
+            .filter(|(index, item)| item.id.is_some() && matches(&filter, *index, item))
+            .for_each(|(_, item)| item.selected = select(item.selected));
     }
 
-    fn update_commands_target(&mut self) -> Result<(), Error> {
-        self.state.commands = String::from(TARGET_COMMANDS);
-        self.draw_ui().ok();
+    fn go_to_export(&mut self) -> Result<(), Error> {
+        if self.state.table.is_none() {
+            return Err(self.wrong());
+        }
+        self.go_to_first();
+        self.state.input = Input::default();
+        self.state.prompt = String::from(EXPORT_COMMANDS);
+        self.state.commands = String::from(EXPORT_COMMANDS);
+        self.state.exporting = true;
+        self.update_moment(Moment::INPUT);
         Ok(())
     }
 
-    fn update_available_commands(&mut self, first_key: &KeyEvent) -> Result<(), Error> {
-        self.state.commands = self.tocker.get_available_commands(first_key).cloned()?;
-        self.draw_ui().ok();
-        Ok(())
+    /// Writes the rows the filter leaves visible, in their current order.
+    fn export(&mut self, path: &str) -> Result<(), Error> {
+        let written = self.write_export(path);
+        self.go_to_first();
+        self.notify(match &written {
+            Ok(count) => format!("exported {count} rows to {path}"),
+            Err(err) => format!("export failed: {err}"),
+        });
+        written.map(|_| ())
     }
 
-    fn check_combination(
-        &mut self,
-        first: &KeyEvent,
-        second: &KeyEvent,
-    ) -> Result<&TargetType, Error> {
-        self.tocker.check_for_target(first, second)
+    fn write_export(&self, path: &str) -> Result<usize, Error> {
+        let Some(table) = &self.state.table else {
+            return Err(Error::new(ErrorKind::NotFound, "No listing to export"));
+        };
+        let rows: Vec<&Row> = self
+            .state
+            .content
+            .iter()
+            .enumerate()
+            .filter(|(index, item)| *index > 0 && matches(&self.state.filter, *index, item))
+            .filter_map(|(_, item)| item.id.as_ref())
+            .filter_map(|id| table.rows.iter().find(|row| &row.id == id))
+            .collect();
+        let content = match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("json") => table.json(&rows),
+            Some("csv") => table.csv(&rows),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "The path must end in .json or .csv",
+                ))
+            }
+        };
+        let path = match path.strip_prefix("~/").zip(dirs::home_dir()) {
+            Some((rest, home)) => home.join(rest),
+            None => PathBuf::from(path),
+        };
+        fs::write(path, content)?;
+        Ok(rows.len())
     }
 
-    fn execute_cmd(
-        &mut self,
-        first: &KeyEvent,
-        second: &KeyEvent,
-        target: &String,
-    ) -> Result<Output, Error> {
-        self.tocker.exec_cmd(first, second, target)
+    /// Copies the row under the cursor through OSC 52, so it reaches the local
+    /// clipboard over ssh as well.
+    fn copy(&mut self, name: bool) -> Result<(), Error> {
+        let Some(row) = self.state.cursor_row() else {
+            return Err(self.wrong());
+        };
+        let text = match name {
+            true => row.name(),
+            false => &row.id,
+        }
+        .to_string();
+        execute!(
+            self.terminal.backend_mut(),
+            Print(format!("\x1b]52;c;{}\x07", STANDARD.encode(&text)))
+        )?;
+        self.notify(format!("copied {text}"));
+        Ok(())
     }
 
-    fn update_moment(&mut self, new_moment: Moment) {
-        self.state.moment = new_moment;
+    /// Stars the row under the cursor as a favorite or unstars it, favorites are listed
+    /// first in every run.
+    fn toggle_favorite(&mut self) -> Result<(), Error> {
+        let (Some(kind), Some(row)) = (self.state.active, self.state.cursor_row()) else {
+            return Err(self.wrong());
+        };
+        let name = String::from(row.name());
+        let starred = self.tocker.toggle_favorite(&kind, &name)?;
+        self.notify(match starred {
+            true => format!("{name} is a favorite"),
+            false => format!("{name} is no favorite anymore"),
+        });
+        self.show_table();
+        Ok(())
     }
 
-    fn quit_tocker(&mut self) -> () {
-        disable_raw_mode().expect("Error in disabling raw mode");
-        execute!(self.terminal.backend_mut(), LeaveAlternateScreen,)
-            .expect("Error in leaving alternate screen");
-        self.terminal
-            .clear()
-            .expect("Error in cleaning back the terminal");
-        self.terminal
-            .show_cursor()
-            .expect("Error in showing back the cursor");
-        self.terminal
-            .set_cursor(0, 0)
-            .expect("Error in setting cursor at the top");
-        exit(0)
+    /// Opens the filter by text or by label selectors, switching over starts it afresh.
+    fn go_to_filter(&mut self, labels: bool) {
+        if self.state.filter.labels != labels {
+            self.state.filter = Filter::by_labels(labels);
+        }
+        self.state.previous = self.state.moment;
+        self.state.commands = self.filter_commands();
+        self.update_moment(Moment::FILTER);
     }
 
-    fn clean(&mut self) -> Result<(), Error> {
-        self.state.content = vec![];
-        self.draw_ui()?;
-        Ok(())
+    fn filter_commands(&self) -> String {
+        match self.state.filter.labels {
+            true => format!("{LABELS_COMMANDS}{}", self.state.filter),
+            false => format!("{FILTER_COMMANDS}{}", self.state.filter),
+        }
     }
 
-    fn help(&mut self) -> Result<(), Error> {
-        match self.state.moment {
-            Moment::KIND => {
-                self.state.commands = self.tocker.get_help_commands().clone();
-                self.draw_ui()?;
-                Ok(())
+    /// Typing narrows the rows live, the previous moment resumes once done.
+    fn on_filter(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        match self.check_key(&key_event)? {
+            Message::OK => {}
+            Message::CANCEL => {
+                self.state.filter.clear();
+                return self.resume();
+            }
+            msg_answer => return self.next_action(msg_answer),
+        }
+        match key_event.code {
+            KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.state.filter.push(c)
             }
-            _ => Ok(()),
+            KeyCode::Backspace => {
+                self.state.filter.pop();
+            }
+            KeyCode::Enter => return self.resume(),
+            _ => {}
         }
+        // keep the cursor off hidden rows
+        if !self.is_visible(self.state.scroll.cursor) {
+            self.add_cursor();
+        }
+        self.state.commands = self.filter_commands();
+        Ok(())
     }
 
-    fn cancel(&mut self) -> Error {
-        self.go_to_first();
-        Error::new(ErrorKind::Interrupted, "User canceled the action")
+    /// Back to the moment the filter or the sort was opened from.
+    fn resume(&mut self) -> Result<(), Error> {
+        match self.state.previous {
+            Moment::TARGET => self.update_commands_target(),
+            _ => {
+                self.state.commands = match self.state.first {
+                    Some(first) => self.tocker.get_available_commands(&first)?.clone(),
+                    None => self.tocker.get_initial_commands().clone(),
+                }
+            }
+        }
+        self.update_moment(self.state.previous);
+        Ok(())
     }
 
-    fn wrong(&mut self) -> Error {
-        Error::new(ErrorKind::InvalidInput, "Press only the available keys")
+    fn is_visible(&self, index: usize) -> bool {
+        self.state
+            .content
+            .get(index)
+            .is_some_and(|item| matches(&self.state.filter, index, item))
     }
 
-    fn next_action(&mut self, message: Message) -> Result<(), Error> {
-        match message {
-            Message::HELP => self.help(),
-            Message::CLEAN => self.clean(),
-            Message::CANCEL => Err(self.cancel()),
-            Message::QUIT => Ok(self.quit_tocker()),
-            Message::WRONG => Err(self.wrong()),
-            Message::OK => Ok(()), //next key or check_combination
-        }
+    fn check_select(&mut self, key_event: KeyEvent) -> Result<&Select, Error> {
+        self.tocker.check_select(key_event)
     }
 
-    fn check_key(&mut self, key_event: &KeyEvent) -> Result<Message, Error> {
-        self.tocker.check_keybinding(key_event, &self.state.moment)
+    fn update_commands_target(&mut self) {
+        let selected = self
+            .state
+            .content
+            .iter()
+            .enumerate()
+            .filter(|(index, item)| item.selected && matches(&self.state.filter, *index, item))
+            .count();
+        self.state.commands = format!(
+            "Selected: {selected}. {}",
+            self.tocker.get_target_commands()
+        );
     }
 
-    fn extract_key_event(&mut self) -> Result<KeyEvent, Error> {
-        self.tocker.extract_key_event(read()?)
+    fn update_available_commands(&mut self, first_key: &KeyEvent) -> Result<(), Error> {
+        self.state.commands = self.tocker.get_available_commands(first_key).cloned()?;
+        Ok(())
     }
 
-    fn go_to_first(&mut self) {
-        let initial_commands = String::from(INITIAL_COMMANDS);
-        self.state.commands = initial_commands;
-        self.state.scroll.cursor = 0;
-        self.draw_ui().ok();
-        self.update_moment(Moment::KIND);
+    fn check_combination(
+        &mut self,
+        first: &KeyEvent,
+        second: &KeyEvent,
+    ) -> Result<&TargetType, Error> {
+        self.tocker.check_for_target(first, second)
     }
 
-    fn go_to_second(&mut self, first: &KeyEvent) -> Result<(), Error> {
-        self.update_available_commands(first)?;
-        self.update_moment(Moment::COMMAND);
+    fn execute_cmd(&mut self, target: &str) -> Result<(), Error> {
+        if self.state.building {
+            return self.execute_build(target);
+        }
+        if self.state.editing {
+            return self.execute_line(target);
+        }
+        let (Some(first), Some(second)) = (self.state.first, self.state.second) else {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Missing key combination",
+            ));
+        };
+        if self.tocker.secret_cmd(&first, &second, target) {
+            return self.go_to_secret(target);
+        }
+        if self.tocker.edit_cmd(&second) {
+            return self.edit(target);
+        }
+        if self.tocker.pin_cmd(&second) {
+            return self.pin(target);
+        }
+        if self.tocker.logs_cmd(&first, &second) {
+            return self.view_logs(target);
+        }
+        let line = self.tocker.command_line(&first, &second, target);
+        let entry = self
+            .state
+            .history
+            .push(Rerun::Keys(first, second), target, line);
+        if let Some(command) = self.tocker.interactive_cmd(&second, target) {
+            self.go_to_first();
+            let result = self.suspend(command);
+            self.state.history.finish(entry, status(&result));
+            return result;
+        }
+        if let Some(watch) = self.tocker.watch_cmd(&second, target) {
+            let commands = match &watch {
+                Watch::Top(container) => format!("Processes in {container}{TOP_COMMANDS}"),
+                _ => String::from(STATS_COMMANDS),
+            };
+            let sender = self.sender.clone();
+            let watching = self.tocker.watch(watch, move |output| {
+                sender.send(AppEvent::Update(output)).ok();
+            });
+            self.state.history.finish(entry, status(&watching));
+            let watching = watching?;
+            self.leave_tab();
+            self.go_to_first();
+            self.state.watching = Some(watching);
+            self.state.commands = commands;
+            self.update_moment(Moment::STATS);
+            return Ok(());
+        }
+        let sender = self.sender.clone();
+        let progress_sender = self.sender.clone();
+        let detail = self.tocker.detail_cmd(&second);
+        let search = self.tocker.search_cmd(&second);
+        let layers = self
+            .tocker
+            .layers_cmd(&second)
+            .then(|| String::from(target));
+        let browse = self
+            .tocker
+            .browse_cmd(&second)
+            .then(|| String::from(target));
+        let listing = self.tocker.listing_cmd(&first, &second);
+        let submitted = self.tocker.exec_cmd(
+            &first,
+            &second,
+            target,
+            move |output| {
+                sender
+                    .send(AppEvent::Finished(
+                        entry,
+                        status(&output),
+                        exit_code(&output),
+                        printed(&output),
+                    ))
+                    .ok();
+                let event = match (detail, listing, layers, browse) {
+                    (true, ..) => AppEvent::Detail(output),
+                    (false, Some(kind), ..) => AppEvent::Listing(output, kind),
+                    (false, None, Some(image), _) => AppEvent::Layers(output, image),
+                    (false, None, None, Some(volume)) => AppEvent::Files(output, volume),
+                    (false, None, None, None) if search => AppEvent::Search(output),
+                    (false, None, None, None) => AppEvent::Output(output),
+                };
+                sender.send(event).ok();
+            },
+            move |lines| {
+                progress_sender.send(AppEvent::Progress(lines)).ok();
+            },
+        );
+        if submitted.is_err() {
+            self.state.history.finish(entry, status(&submitted));
+        }
+        submitted?;
+        self.state.pending += 1;
+        self.go_to_first();
         Ok(())
     }
 
-    fn get_second(&mut self) -> Result<KeyEvent, Error> {
-        loop {
-            let second = self.extract_key_event()?;
-            let msg_answer = self.check_key(&second)?;
-            self.next_action(msg_answer)?;
-            break Ok(second);
+    /// Only `target` goes in the history, the secret is asked again on a rerun.
+    fn execute_secret(&mut self, target: &str, secret: &str) -> Result<(), Error> {
+        let (Some(first), Some(second)) = (self.state.first, self.state.second) else {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Missing key combination",
+            ));
+        };
+        let line = self.tocker.command_line(&first, &second, target);
+        let entry = self
+            .state
+            .history
+            .push(Rerun::Keys(first, second), target, line);
+        let sender = self.sender.clone();
+        let progress_sender = self.sender.clone();
+        let listing = self.tocker.listing_cmd(&first, &second);
+        let submitted = self.tocker.secret_exec_cmd(
+            &first,
+            &second,
+            target,
+            secret,
+            move |output| {
+                sender
+                    .send(AppEvent::Finished(
+                        entry,
+                        status(&output),
+                        exit_code(&output),
+                        printed(&output),
+                    ))
+                    .ok();
+                let event = match listing {
+                    Some(kind) => AppEvent::Listing(output, kind),
+                    None => AppEvent::Output(output),
+                };
+                sender.send(event).ok();
+            },
+            move |lines| {
+                progress_sender.send(AppEvent::Progress(lines)).ok();
+            },
+        );
+        if submitted.is_err() {
+            self.state.history.finish(entry, status(&submitted));
         }
+        submitted?;
+        self.state.pending += 1;
+        self.go_to_first();
+        Ok(())
     }
 
-    fn get_first(&mut self) -> Result<KeyEvent, Error> {
-        let first = self.extract_key_event()?;
-        let msg_answer = self.check_key(&first)?;
-        self.next_action(msg_answer)?;
-        self.go_to_second(&first)?;
-        Ok(first)
+    /// Hands the terminal to the editor on the file the first picked row is built from,
+    /// the one in the current directory if none is picked, then offers to build again.
+    fn edit(&mut self, target: &str) -> Result<(), Error> {
+        let labels = target.split_whitespace().next().and_then(|id| {
+            let table = self.state.table.as_ref()?;
+            let row = table.rows.iter().find(|row| row.id == id)?;
+            Some(&row.labels)
+        });
+        let file = BuildFile::find(labels)?;
+        self.go_to_first();
+        self.suspend(file.editor())?;
+        self.state.input = Input::filled(file.rebuild());
+        self.state.prompt = String::from(REBUILD_COMMANDS);
+        self.state.commands = format!("{REBUILD_COMMANDS}{}", self.state.input.shown());
+        self.state.editing = true;
+        self.update_moment(Moment::INPUT);
+        Ok(())
     }
 
-    fn add_cursor(&mut self) {
-        self.state.scroll.cursor += 1;
-        if self.state.scroll.cursor >= self.state.content.len() {
-            self.state.scroll.cursor = 1;
-        }
+    /// Opens the command line the picked rows would run, to run it once edited.
+    fn go_to_edit(&mut self) -> Result<(), Error> {
+        let (Some(first), Some(second)) = (self.state.first, self.state.second) else {
+            return Err(self.wrong());
+        };
+        let target = self.extract_target_string();
+        let Some(line) = self.tocker.cli_line(&first, &second, &target) else {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "This command has no docker CLI equivalent to edit",
+            ));
+        };
+        self.state.input = Input::filled(line);
+        self.state.prompt = String::from(EDIT_COMMANDS);
+        self.state.commands = format!("{EDIT_COMMANDS}{}", self.state.input.shown());
+        self.state.editing = true;
+        self.update_moment(Moment::INPUT);
+        Ok(())
     }
 
-    fn sub_cursor(&mut self) {
-        self.state.scroll.cursor -= 1;
-        if self.state.scroll.cursor <= 0 {
-            self.state.scroll.cursor = self.state.content.len() - 1;
+    fn execute_line(&mut self, line: &str) -> Result<(), Error> {
+        let entry = self
+            .state
+            .history
+            .push(Rerun::Line, line, String::from(line));
+        let sender = self.sender.clone();
+        let progress_sender = self.sender.clone();
+        let submitted = self.tocker.line_cmd(
+            line,
+            move |output| {
+                sender
+                    .send(AppEvent::Finished(
+                        entry,
+                        status(&output),
+                        exit_code(&output),
+                        printed(&output),
+                    ))
+                    .ok();
+                sender.send(AppEvent::Output(output)).ok();
+            },
+            move |lines| {
+                progress_sender.send(AppEvent::Progress(lines)).ok();
+            },
+        );
+        if submitted.is_err() {
+            self.state.history.finish(entry, status(&submitted));
         }
+        submitted?;
+        self.state.pending += 1;
+        self.go_to_first();
+        Ok(())
     }
 
-    fn looping(&mut self) -> Result<(), Error> {
-        // collect key presses combo
-        let first = self.get_first()?;
-        let second = self.get_second()?;
-
-        // check target type
-        let target_string = self.get_target(&first, &second)?;
-
-        // handle output (to be finished)
-        let output = String::from_utf8(
-            self.execute_cmd(&first, &second, &target_string)
-                .unwrap()
-                .stdout,
-        )
-        .unwrap();
+    fn execute_build(&mut self, target: &str) -> Result<(), Error> {
+        let entry = self
+            .state
+            .history
+            .push(Rerun::Build, target, format!("docker build {target}"));
+        let sender = self.sender.clone();
+        let progress_sender = self.sender.clone();
+        let submitted = self.tocker.build_cmd(
+            target,
+            move |output| {
+                sender
+                    .send(AppEvent::Finished(
+                        entry,
+                        status(&output),
+                        exit_code(&output),
+                        printed(&output),
+                    ))
+                    .ok();
+                sender.send(AppEvent::Output(output)).ok();
+            },
+            move |lines| {
+                progress_sender.send(AppEvent::Progress(lines)).ok();
+            },
+        );
+        if submitted.is_err() {
+            self.state.history.finish(entry, status(&submitted));
+        }
+        submitted?;
+        self.state.pending += 1;
+        self.go_to_first();
+        Ok(())
+    }
 
-        self.state.content = output
-            .lines()
-            .map(|line| {
-                let text = String::from(line);
-                let selected = false;
-                ContentItem { text, selected }
-            })
-            .collect();
+    fn on_output(&mut self, output: Result<Output, Error>) -> Result<(), Error> {
+        self.state.pending = self.state.pending.saturating_sub(1);
+        match output? {
+            Output::Table(table) => {
+                self.stop_refresh();
+                self.state.filter.clear();
+                self.state.content.clear();
+                let rows = table.rows.len();
+                self.state.table = Some(table);
+                self.show_table();
+                match self.state.active {
+                    Some(kind) => self.announce(format!("{}, {rows} rows", tab_title(&kind))),
+                    None => self.announce(format!("{rows} rows")),
+                }
+            }
+            output @ (Output::Lines(_) | Output::Ran(_) | Output::Layers(_) | Output::Files(_)) => {
+                // the listing stays in its tab, to switch back to
+                self.leave_tab();
+                self.state.filter.clear();
+                self.state.table = None;
+                self.state.content = output.lines().into_iter().map(ContentItem::plain).collect();
+                self.announce(format!("{} lines of output", self.state.content.len()));
+            }
+        }
+        Ok(())
+    }
 
-        self.draw_ui().ok();
+    /// Search results are shown ready to pick what to pull, unless something else
+    /// was started meanwhile.
+    fn on_search(&mut self, output: Result<Output, Error>) -> Result<(), Error> {
+        // not a listing of any tab, the one on screen is put aside
+        self.leave_tab();
+        self.on_output(output)?;
+        if self.state.moment != Moment::KIND {
+            return Ok(());
+        }
+        if let Some((first, second)) = self.tocker.pull_keys() {
+            self.state.first = Some(first);
+            self.go_to_target(&second);
+        }
+        Ok(())
+    }
 
-        self.go_to_first();
+    /// A fresh listing of `kind`, it takes over that kind's tab.
+    fn on_listing(&mut self, output: Result<Output, Error>, kind: DockerKind) -> Result<(), Error> {
+        self.fill_tab(output, kind, false)
+    }
 
-        Ok(())
+    /// Like a fresh listing, but of only what a prune of `kind` would remove.
+    fn on_reclaimable(
+        &mut self,
+        output: Result<Output, Error>,
+        kind: DockerKind,
+    ) -> Result<(), Error> {
+        self.fill_tab(output, kind, true)
     }
 
-    pub fn start_loop(&mut self) -> () {
-        loop {
-            if let Err(_) = self.looping() {
-                // self.state.content.push(ContentItem {
-                //     text: String::from(err.to_string()),
-                //     selected: false,
-                // });
+    fn fill_tab(
+        &mut self,
+        output: Result<Output, Error>,
+        kind: DockerKind,
+        reclaimable: bool,
+    ) -> Result<(), Error> {
+        if self.state.active != Some(kind) {
+            self.leave_tab();
+            self.state.tabs.remove(&kind);
+            self.state.active = Some(kind);
+        }
+        self.on_output(output)?;
+        self.state.reclaimable = reclaimable;
+        if let Some((_, filter)) = self
+            .state
+            .restored
+            .take_if(|(restored, _)| *restored == kind)
+        {
+            self.state.filter = filter;
+        }
+        self.start_refresh(kind)?;
+        // listed to clean up, unless something else was started meanwhile
+        if reclaimable && mem::take(&mut self.state.dangling) && self.state.moment == Moment::KIND {
+            if let Some((first, second)) = self.tocker.dangling_keys(kind) {
+                self.state
+                    .content
+                    .iter_mut()
+                    .filter(|item| item.id.is_some())
+                    .for_each(|item| item.selected = true);
+                self.state.first = Some(first);
+                self.go_to_target(&second);
             }
         }
+        Ok(())
+    }
+
+    /// Lists the dangling images or unused volumes to remove them, they're picked once listed.
+    fn show_dangling(&mut self, kind: DockerKind) -> Result<(), Error> {
+        self.go_to_first();
+        let sender = self.sender.clone();
+        self.tocker.reclaimable_cmd(kind, move |output| {
+            sender.send(AppEvent::Reclaimable(output, kind)).ok();
+        })?;
+        self.state.dangling = true;
+        self.state.pending += 1;
+        Ok(())
+    }
+
+    /// Asks before removing the picked dangling images or unused volumes.
+    fn go_to_cleanup(&mut self, first: &KeyEvent, target: String) -> Result<(), Error> {
+        let what = match self.tocker.kind_of(first) {
+            Some(DockerKind::Volume) => "unused volume(s)",
+            _ => "dangling image(s)",
+        };
+        let picked: Vec<&str> = self
+            .state
+            .content
+            .iter()
+            .enumerate()
+            .filter(|(index, item)| item.selected && matches(&self.state.filter, *index, item))
+            .map(|(_, item)| item.text.as_str())
+            .collect();
+        if picked.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Nothing is picked to remove",
+            ));
+        }
+        let mut summary: Vec<String> = picked
+            .iter()
+            .take(CLEANUP_SHOWN)
+            .map(|line| line.to_string())
+            .collect();
+        if picked.len() > CLEANUP_SHOWN {
+            summary.push(format!("… and {} more", picked.len() - CLEANUP_SHOWN));
+        }
+        self.state.confirm = Some(Confirm {
+            question: format!("Remove {} {what}?", picked.len()),
+            summary,
+        });
+        self.state.picked = Some(target);
+        self.state.commands = String::from(CONFIRM_COMMANDS);
+        self.update_moment(Moment::CONFIRM);
+        Ok(())
+    }
+
+    /// Jumps from the disk usage row under the cursor to what could be reclaimed of it.
+    fn show_reclaimable(&mut self) -> Result<(), Error> {
+        let Some(kind) = self.state.cursor_row().and_then(|row| usage_kind(&row.id)) else {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Only images, containers and volumes have a listing to jump to",
+            ));
+        };
+        let sender = self.sender.clone();
+        self.tocker.reclaimable_cmd(kind, move |output| {
+            sender.send(AppEvent::Reclaimable(output, kind)).ok();
+        })?;
+        self.state.pending += 1;
+        Ok(())
+    }
+
+    /// Shows the tab of `kind` as it was left, listing it the first time.
+    fn switch_tab(&mut self, kind: DockerKind) -> Result<(), Error> {
+        if self.state.active == Some(kind) {
+            return Ok(());
+        }
+        self.leave_tab();
+        self.state.active = Some(kind);
+        if let Some(tab) = self.state.tabs.remove(&kind) {
+            self.state.content = tab.content;
+            self.state.table = tab.table;
+            self.state.scroll.cursor = tab.cursor;
+            self.state.filter = tab.filter;
+            self.state.sort = tab.sort;
+            self.state.reclaimable = tab.reclaimable;
+            return self.start_refresh(kind);
+        }
+        self.state.content.clear();
+        self.state.table = None;
+        self.state.scroll.cursor = 0;
+        self.state.filter.clear();
+        self.state.reclaimable = false;
+        self.relist(kind)
+    }
+
+    /// Asks for a fresh listing of `kind`, of what a prune would remove if that's what
+    /// the tab shows, it takes over the tab once it arrives.
+    fn relist(&mut self, kind: DockerKind) -> Result<(), Error> {
+        let sender = self.sender.clone();
+        match self.state.reclaimable {
+            true => self.tocker.reclaimable_cmd(kind, move |output| {
+                sender.send(AppEvent::Reclaimable(output, kind)).ok();
+            })?,
+            false => self.tocker.list_cmd(kind, move |output| {
+                sender.send(AppEvent::Listing(output, kind)).ok();
+            })?,
+        }
+        self.state.pending += 1;
+        Ok(())
+    }
+
+    fn next_tab(&mut self) -> Result<(), Error> {
+        self.step_tab(true)
+    }
+
+    /// Switches to the tab after the one on screen, or before it.
+    fn step_tab(&mut self, forward: bool) -> Result<(), Error> {
+        let step = if forward { 1 } else { TABS.len() - 1 };
+        let next = match TABS
+            .iter()
+            .position(|kind| Some(*kind) == self.state.active)
+        {
+            Some(index) => TABS[(index + step) % TABS.len()],
+            None => TABS[0],
+        };
+        self.go_to_first();
+        self.switch_tab(next)
+    }
+
+    /// Puts the listing on screen aside in its tab, if there is one.
+    fn leave_tab(&mut self) {
+        self.stop_refresh();
+        let Some(kind) = self.state.active.take() else {
+            return;
+        };
+        if self.state.table.is_none() {
+            return;
+        }
+        let tab = Tab {
+            content: mem::take(&mut self.state.content),
+            table: self.state.table.take(),
+            cursor: self.state.scroll.cursor,
+            filter: mem::take(&mut self.state.filter),
+            sort: self.state.sort.take(),
+            reclaimable: mem::take(&mut self.state.reclaimable),
+        };
+        self.state.tabs.insert(kind, tab);
+    }
+
+    fn start_refresh(&mut self, kind: DockerKind) -> Result<(), Error> {
+        if self.state.table.is_none() {
+            return Ok(());
+        }
+        let Some(watch) = self.tocker.refresh(kind, self.state.reclaimable) else {
+            return Ok(());
+        };
+        let sender = self.sender.clone();
+        let refreshing = self.tocker.watch(watch, move |output| {
+            sender.send(AppEvent::Refresh(output)).ok();
+        })?;
+        self.state.refreshing = Some(refreshing);
+        Ok(())
+    }
+
+    /// Swaps in the refreshed listing, keeping the filter, sort, selections and cursor.
+    fn on_refresh(&mut self, output: Result<Output, Error>) -> Result<(), Error> {
+        // the banner already tells why it failed
+        if self.state.refreshing.is_none() || (output.is_err() && self.state.unreachable.is_some())
+        {
+            return Ok(());
+        }
+        if let Output::Table(table) = output? {
+            let rows = table.rows.len();
+            let before = self
+                .state
+                .table
+                .replace(table)
+                .map(|table| table.rows.len());
+            self.show_table();
+            if before != Some(rows) {
+                self.announce(format!("Now {rows} rows"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens the tab left on screen last time, sorted the same, its filter narrows the
+    /// listing once it arrives.
+    fn restore_view(&mut self) -> Result<(), Error> {
+        let Some(view) = self.tocker.last_view() else {
+            return Ok(());
+        };
+        self.state.sort = view.sort;
+        self.state.split = view.split;
+        if !view.filter.is_empty() {
+            let filter = Filter {
+                text: view.filter,
+                labels: view.labels,
+            };
+            self.state.restored = Some((view.kind, filter));
+        }
+        self.switch_tab(view.kind)
+    }
+
+    /// Pings the daemon from now on, in place of any earlier heartbeat.
+    fn start_heartbeat(&mut self) -> Result<(), Error> {
+        if let Some(heartbeat) = self.state.heartbeat.take() {
+            heartbeat.cancel();
+        }
+        let sender = self.sender.clone();
+        let heartbeat = self.tocker.watch(Watch::Ping, move |output| {
+            sender.send(AppEvent::Ping(output)).ok();
+        })?;
+        self.state.heartbeat = Some(heartbeat);
+        Ok(())
+    }
+
+    /// Raises the banner once the daemon stops answering, and picks up where things
+    /// were once it's back: the listing, the events and the pinned logs follow again.
+    fn on_ping(&mut self, output: Result<Output, Error>) -> Result<(), Error> {
+        match (output, &self.state.unreachable) {
+            (Err(err), None) => {
+                info!(error = %err, "daemon unreachable");
+                self.state.unreachable = Some(err.to_string());
+                self.notify(format!("Docker daemon unreachable, retrying: {err}"));
+            }
+            (Ok(_), Some(_)) => {
+                info!("daemon reachable again");
+                self.state.unreachable = None;
+                self.notify(String::from("Docker daemon is back"));
+                if let Some(events) = self.state.events.take() {
+                    events.cancel();
+                    self.toggle_events()?;
+                }
+                if let Some(alerts) = self.state.alerts.take() {
+                    alerts.cancel();
+                    self.follow_alerts()?;
+                }
+                if let Some((name, following)) = self.state.pinned.take() {
+                    following.cancel();
+                    let sender = self.sender.clone();
+                    let following = self.tocker.logs(&name, PINNED_LOGS, move |line| {
+                        sender.send(AppEvent::Pinned(line)).ok();
+                    })?;
+                    self.state.pinned = Some((name, following));
+                }
+                self.refollow_logs()?;
+                // a listing that never came is asked again, the others refresh themselves
+                if let (Some(kind), None) = (self.state.active, &self.state.table) {
+                    self.state.active = None;
+                    self.switch_tab(kind)?;
+                }
+            }
+            // the latest reason shows, e.g. once it's refused rather than timed out
+            (Err(err), Some(_)) => self.state.unreachable = Some(err.to_string()),
+            (Ok(_), None) => {}
+        }
+        Ok(())
+    }
+
+    fn stop_refresh(&mut self) {
+        if let Some(refreshing) = self.state.refreshing.take() {
+            refreshing.cancel();
+        }
+    }
+
+    /// Renders the kept table with the active sort, selections and cursor follow their rows.
+    fn show_table(&mut self) {
+        let Some(table) = self.state.table.as_mut() else {
+            return;
+        };
+        if let Some((by, descending)) = self.state.sort {
+            table.sort(by, descending);
+        }
+        if let Some(kind) = self.state.active {
+            table.favor(self.tocker.favorites(&kind));
+        }
+        let selected: HashSet<&str> = self
+            .state
+            .content
+            .iter()
+            .filter(|item| item.selected)
+            .filter_map(|item| item.id.as_deref())
+            .collect();
+        // at most one column of a listing is colored apart from its row
+        let marked = MARKED.iter().find_map(|(column, color)| {
+            Some((table.header.iter().position(|name| name == column)?, color))
+        });
+        let rows = table.rows.iter().map(|row| {
+            let mark =
+                marked.and_then(|(index, color)| Some((index, color(row.cells.get(index)?)?)));
+            (
+                Some(row.id.clone()),
+                table.row_cells(row),
+                row.state.as_deref().and_then(state_color),
+                mark,
+                row.labels.clone(),
+            )
+        });
+        let header = (None, table.marked_header(), None, None, HashMap::new());
+        // read out line by line in plain mode, drawn as a table otherwise
+        let plain = self.plain;
+        let lines = match plain {
+            true => table.labelled(),
+            false => table.lines(),
+        };
+        let content: Vec<ContentItem> = lines
+            .into_iter()
+            .zip(std::iter::once(header).chain(rows))
+            .map(|(text, (id, cells, color, mark, labels))| ContentItem {
+                selected: id.as_deref().is_some_and(|id| selected.contains(id)),
+                text,
+                id,
+                cells: match plain {
+                    true => vec![],
+                    false => cells,
+                },
+                color,
+                mark,
+                labels,
+            })
+            .collect();
+        let cursor = self
+            .state
+            .content
+            .get(self.state.scroll.cursor)
+            .and_then(|item| item.id.as_ref())
+            .and_then(|id| content.iter().position(|item| item.id.as_ref() == Some(id)));
+        if let Some(cursor) = cursor {
+            self.state.scroll.cursor = cursor;
+        }
+        if self.state.scroll.cursor >= content.len() {
+            self.state.scroll.cursor = content.len().saturating_sub(1);
+        }
+        self.state.content = content;
+    }
+
+    fn go_to_sort(&mut self) {
+        self.state.previous = self.state.moment;
+        self.state.commands = String::from(SORT_COMMANDS);
+        self.update_moment(Moment::SORT);
+    }
+
+    /// A column key sorts ascending, pressing it again reverses.
+    fn on_sort(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        match self.check_key(&key_event)? {
+            Message::OK => {}
+            Message::CANCEL => return self.resume(),
+            msg_answer => return self.next_action(msg_answer),
+        }
+        let by = match key_event.code {
+            KeyCode::Char('n') => SortBy::Name,
+            KeyCode::Char('s') => SortBy::Size,
+            KeyCode::Char('c') => SortBy::Created,
+            KeyCode::Char('t') => SortBy::Status,
+            _ => return Err(self.wrong()),
+        };
+        let descending = self.state.sort == Some((by, false));
+        let sortable = self
+            .state
+            .table
+            .as_mut()
+            .is_some_and(|table| table.sort(by, descending));
+        if !sortable {
+            return Err(self.wrong());
+        }
+        self.state.sort = Some((by, descending));
+        self.show_table();
+        self.resume()
+    }
+
+    fn go_to_form(&mut self, second: &KeyEvent) {
+        let Some(first) = self.state.first else {
+            return;
+        };
+        let (title, fields) = self.tocker.form_fields(&first, second);
+        let count = self
+            .state
+            .picked
+            .as_deref()
+            .map(|picked| picked.split_whitespace().count());
+        let title = match count {
+            Some(count) => format!("{title}, {count} picked"),
+            None => String::from(title),
+        };
+        let fields = fields
+            .into_iter()
+            .map(|(name, label, hint)| Field {
+                name,
+                label,
+                hint,
+                input: Input::default(),
+            })
+            .collect();
+        self.state.form = Some(Form::new(title, fields));
+        self.state.second = Some(*second);
+        self.state.commands = String::from(FORM_COMMANDS);
+        self.update_moment(Moment::FORM);
+    }
+
+    /// The form takes the editing keys, the rest work as anywhere else, e.g. 'esc'.
+    fn on_form(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        let Some(form) = self.state.form.as_mut() else {
+            return Err(self.wrong());
+        };
+        if key_event.code == KeyCode::Enter {
+            let values = form.values();
+            if values.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Fill in at least one field",
+                ));
+            }
+            if self.state.recorded.is_some() {
+                return self.save_macro(&values);
+            }
+            let values = values.join(" ");
+            return match self.state.picked.take() {
+                Some(picked) => self.execute_cmd(&format!("{picked} {values}")),
+                None => self.execute_cmd(&values),
+            };
+        }
+        if form.edit(&key_event) {
+            return Ok(());
+        }
+        let msg_answer = self.check_key(&key_event)?;
+        self.next_action(msg_answer)
+    }
+
+    fn go_to_signal(&mut self, second: &KeyEvent) {
+        self.state.second = Some(*second);
+        self.state.commands = String::from(SIGNAL_COMMANDS);
+        self.update_moment(Moment::SIGNAL);
+    }
+
+    /// The common signals are a key away, anything else is typed.
+    fn on_signal(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        let msg_answer = self.check_key(&key_event)?;
+        if !matches!(msg_answer, Message::OK) {
+            return self.next_action(msg_answer);
+        }
+        let signal = match key_event.code {
+            KeyCode::Char('t') => "SIGTERM",
+            KeyCode::Char('k') => "SIGKILL",
+            KeyCode::Char('h') => "SIGHUP",
+            KeyCode::Char('i') => "SIGINT",
+            KeyCode::Char('u') => "SIGUSR1",
+            KeyCode::Char('c') => {
+                let Some(second) = self.state.second else {
+                    return Err(self.wrong());
+                };
+                self.go_to_input(&second);
+                return Ok(());
+            }
+            _ => return Err(self.wrong()),
+        };
+        let picked = self.state.picked.take().unwrap_or_default();
+        self.execute_cmd(&format!("{picked} {signal}"))
+    }
+
+    fn go_to_policy(&mut self, second: &KeyEvent) {
+        self.state.second = Some(*second);
+        self.state.commands = String::from(POLICY_COMMANDS);
+        self.update_moment(Moment::POLICY);
+    }
+
+    /// The restart policies are a key away, a retry limit for `on-failure` is typed.
+    fn on_policy(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        let msg_answer = self.check_key(&key_event)?;
+        if !matches!(msg_answer, Message::OK) {
+            return self.next_action(msg_answer);
+        }
+        let policy = match key_event.code {
+            KeyCode::Char('n') => "no",
+            KeyCode::Char('f') => "on-failure",
+            KeyCode::Char('a') => "always",
+            KeyCode::Char('u') => "unless-stopped",
+            KeyCode::Char('c') => {
+                let Some(second) = self.state.second else {
+                    return Err(self.wrong());
+                };
+                self.go_to_input(&second);
+                return Ok(());
+            }
+            _ => return Err(self.wrong()),
+        };
+        let picked = self.state.picked.take().unwrap_or_default();
+        self.execute_cmd(&format!("{picked} {policy}"))
+    }
+
+    fn update_moment(&mut self, new_moment: Moment) {
+        if new_moment != self.state.moment {
+            self.announce(moment_label(&new_moment));
+        }
+        self.state.moment = new_moment;
+    }
+
+    /// One-off feedback in the content title, announced as well.
+    /// Shows long names, commands and mount paths whole or cut again, the next draw
+    /// lays the listing out anew.
+    fn toggle_full(&mut self) {
+        self.state.full = !self.state.full;
+        self.notify(String::from(match self.state.full {
+            true => "showing names and commands in full",
+            false => "truncating long names and commands",
+        }));
+    }
+
+    /// Shows the digests of the images or hides them, the images are listed again.
+    fn toggle_digests(&mut self) -> Result<(), Error> {
+        let shown = self.tocker.toggle_digests();
+        self.notify(String::from(match shown {
+            true => "showing image digests",
+            false => "hiding image digests",
+        }));
+        self.relist(DockerKind::Image)
+    }
+
+    /// Lists the stopped containers too or only the running ones, the containers are
+    /// listed again.
+    fn toggle_all(&mut self) -> Result<(), Error> {
+        let all = self.tocker.toggle_all();
+        self.notify(String::from(match all {
+            true => "listing all containers",
+            false => "listing running containers",
+        }));
+        self.relist(DockerKind::Container)
+    }
+
+    fn notify(&mut self, notice: String) {
+        self.announce(notice.as_str());
+        self.state.notice = Some(notice);
+    }
+
+    /// Puts `message` on the status line plain mode keeps at the bottom.
+    fn announce(&mut self, message: impl Into<String>) {
+        self.state.announcement = message.into();
+    }
+
+    fn quit_tocker(&mut self) {
+        // the next run opens on the same tab
+        let view = self.state.active.map(|kind| View {
+            kind,
+            filter: self.state.filter.text.clone(),
+            labels: self.state.filter.labels,
+            sort: self.state.sort,
+            split: self.state.split,
+        });
+        if let Err(err) = self.tocker.save_view(view.as_ref()) {
+            info!(error = %err, "session not saved");
+        }
+        disable_raw_mode().expect("Error in disabling raw mode");
+        execute!(self.terminal.backend_mut(), LeaveAlternateScreen,)
+            .expect("Error in leaving alternate screen");
+        self.terminal
+            .clear()
+            .expect("Error in cleaning back the terminal");
+        self.terminal
+            .show_cursor()
+            .expect("Error in showing back the cursor");
+        self.terminal
+            .set_cursor(0, 0)
+            .expect("Error in setting cursor at the top");
+        exit(0)
+    }
+
+    /// Refreshes the content in place, keeping the cursor where it was.
+    fn on_update(&mut self, output: Result<Output, Error>) -> Result<(), Error> {
+        if self.state.watching.is_none() {
+            return Ok(());
+        }
+        self.state.content = output?
+            .lines()
+            .into_iter()
+            .map(ContentItem::plain)
+            .collect();
+        Ok(())
+    }
+
+    fn go_to_confirm(&mut self, first: &KeyEvent, second: &KeyEvent) -> Result<(), Error> {
+        let sender = self.sender.clone();
+        self.tocker.usage_cmd(first, move |output| {
+            sender.send(AppEvent::Usage(output)).ok();
+        })?;
+        self.state.confirm = Some(Confirm {
+            question: String::from(CONFIRM_QUESTION),
+            summary: vec![String::from("…")],
+        });
+        self.state.commands = String::from(CONFIRM_COMMANDS);
+        self.state.second = Some(*second);
+        self.update_moment(Moment::CONFIRM);
+        Ok(())
+    }
+
+    fn on_usage(&mut self, output: Result<Output, Error>) -> Result<(), Error> {
+        if let Some(confirm) = self.state.confirm.as_mut() {
+            confirm.summary = match output {
+                Ok(output) => output.lines(),
+                Err(err) => vec![format!("Error: {err}")],
+            };
+        }
+        Ok(())
+    }
+
+    fn on_confirm(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        let msg_answer = self.check_key(&key_event)?;
+        if !matches!(msg_answer, Message::OK) {
+            return self.next_action(msg_answer);
+        }
+        match key_event.code {
+            KeyCode::Char('y') => {
+                let target = self.state.picked.take().unwrap_or_default();
+                self.execute_cmd(&target)
+            }
+            KeyCode::Char('n') => Err(self.cancel()),
+            _ => Err(self.wrong()),
+        }
+    }
+
+    fn on_detail_output(&mut self, output: Result<Output, Error>) -> Result<(), Error> {
+        self.state.pending = self.state.pending.saturating_sub(1);
+        let json = serde_json::from_str(&output?.lines().join("\n")).map_err(Error::other)?;
+        self.go_to_first();
+        self.state.detail = Some(Detail::new(json));
+        self.state.commands = String::from(DETAIL_COMMANDS);
+        self.update_moment(Moment::DETAIL);
+        Ok(())
+    }
+
+    fn on_layers_output(
+        &mut self,
+        output: Result<Output, Error>,
+        image: String,
+    ) -> Result<(), Error> {
+        let layers = match output {
+            Ok(Output::Layers(layers)) => layers,
+            output => return self.on_output(output),
+        };
+        self.state.pending = self.state.pending.saturating_sub(1);
+        self.go_to_first();
+        self.state.explorer = Some(Explorer::new(image, layers));
+        self.state.commands = String::from(LAYERS_COMMANDS);
+        self.update_moment(Moment::LAYERS);
+        Ok(())
+    }
+
+    fn on_layers(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        let msg_answer = self.check_key(&key_event)?;
+        if !matches!(msg_answer, Message::OK) {
+            return self.next_action(msg_answer);
+        }
+        let Some(explorer) = self.state.explorer.as_mut() else {
+            return Ok(());
+        };
+        match key_event.code {
+            KeyCode::Up | KeyCode::Char('k') => explorer.up(),
+            KeyCode::Down | KeyCode::Char('j') => explorer.down(),
+            KeyCode::PageUp => explorer.scroll(false, DETAIL_PAGE as u16),
+            KeyCode::PageDown => explorer.scroll(true, DETAIL_PAGE as u16),
+            _ => return Err(self.wrong()),
+        }
+        Ok(())
+    }
+
+    fn on_files_output(
+        &mut self,
+        output: Result<Output, Error>,
+        volume: String,
+    ) -> Result<(), Error> {
+        let files = match output {
+            Ok(Output::Files(files)) => files,
+            output => return self.on_output(output),
+        };
+        self.state.pending = self.state.pending.saturating_sub(1);
+        self.go_to_first();
+        self.state.browser = Some(Browser::new(volume, files));
+        self.state.commands = String::from(BROWSE_COMMANDS);
+        self.update_moment(Moment::BROWSE);
+        Ok(())
+    }
+
+    fn on_browse(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        let msg_answer = self.check_key(&key_event)?;
+        if !matches!(msg_answer, Message::OK) {
+            return self.next_action(msg_answer);
+        }
+        let Some(browser) = self.state.browser.as_mut() else {
+            return Ok(());
+        };
+        match key_event.code {
+            KeyCode::Up | KeyCode::Char('k') => browser.up(),
+            KeyCode::Down | KeyCode::Char('j') => browser.down(),
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                if !browser.open() {
+                    return Err(self.wrong());
+                }
+            }
+            KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => browser.parent(),
+            _ => return Err(self.wrong()),
+        }
+        Ok(())
+    }
+
+    fn on_detail(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        let msg_answer = self.check_key(&key_event)?;
+        if !matches!(msg_answer, Message::OK) {
+            return self.next_action(msg_answer);
+        }
+        let Some(detail) = self.state.detail.as_mut() else {
+            return Ok(());
+        };
+        match key_event.code {
+            KeyCode::Up | KeyCode::Char('k') => detail.up(1),
+            KeyCode::Down | KeyCode::Char('j') => detail.down(1),
+            KeyCode::PageUp => detail.up(DETAIL_PAGE),
+            KeyCode::PageDown => detail.down(DETAIL_PAGE),
+            KeyCode::Enter | KeyCode::Char(' ') => detail.toggle(),
+            _ => return Err(self.wrong()),
+        }
+        Ok(())
+    }
+
+    fn go_to_find(&mut self) -> Result<(), Error> {
+        let candidates: Vec<(usize, String)> = self
+            .state
+            .content
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                let id = item.id.as_ref()?;
+                let mut text = item
+                    .text
+                    .split_whitespace()
+                    .collect::<Vec<&str>>()
+                    .join(" ");
+                // the full id, the row only shows a short one
+                if !text.contains(id.as_str()) {
+                    text = format!("{text} {id}");
+                }
+                Some((index, text))
+            })
+            .collect();
+        if candidates.is_empty() {
+            return Err(self.wrong());
+        }
+        self.state.finder = Some(Finder::new(FIND_TITLE, candidates));
+        Ok(())
+    }
+
+    /// Opens the palette, picking an action presses its keys from the first moment on.
+    fn go_to_palette(&mut self) -> Result<(), Error> {
+        let (labels, keys): (Vec<String>, Vec<Vec<KeyEvent>>) =
+            self.tocker.palette().into_iter().unzip();
+        self.state.finder = Some(Finder::new(
+            PALETTE_TITLE,
+            labels.into_iter().enumerate().collect(),
+        ));
+        self.state.palette = Some(keys);
+        Ok(())
+    }
+
+    /// Like the help, the finder takes every key until it's closed.
+    fn on_find(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        match self.check_key(&key_event)? {
+            Message::CANCEL | Message::FIND | Message::PALETTE => {
+                self.state.finder = None;
+                self.state.palette = None;
+                return Ok(());
+            }
+            Message::QUIT => self.quit_tocker(),
+            _ => {}
+        }
+        let Some(finder) = self.state.finder.as_mut() else {
+            return Ok(());
+        };
+        match key_event.code {
+            KeyCode::Up => finder.up(),
+            KeyCode::Down => finder.down(),
+            KeyCode::Enter => {
+                let picked = finder.picked();
+                self.state.finder = None;
+                if let Some(palette) = self.state.palette.take() {
+                    let keys = picked.and_then(|index| palette.into_iter().nth(index));
+                    self.go_to_first();
+                    for key in keys.unwrap_or_default() {
+                        self.on_key(key)?;
+                    }
+                    return Ok(());
+                }
+                if let Some(index) = picked {
+                    if !self.is_visible(index) {
+                        self.state.filter.clear();
+                    }
+                    self.state.scroll.cursor = index;
+                }
+            }
+            _ => {
+                finder.edit(&key_event);
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens the events pane, or closes it and stops following.
+    fn toggle_events(&mut self) -> Result<(), Error> {
+        if let Some(events) = self.state.events.take() {
+            events.cancel();
+            return Ok(());
+        }
+        let sender = self.sender.clone();
+        let events = self.tocker.events(move |line| {
+            sender.send(AppEvent::Event(line)).ok();
+        })?;
+        self.state.event_lines.clear();
+        self.state.events = Some(events);
+        Ok(())
+    }
+
+    fn on_event_line(&mut self, line: Result<String, Error>) -> Result<(), Error> {
+        if self.state.events.is_none() {
+            return Ok(());
+        }
+        let line = line.unwrap_or_else(|err| format!("events stopped: {err}"));
+        self.state.event_lines.push_back(line);
+        if self.state.event_lines.len() > EVENTS_KEPT {
+            self.state.event_lines.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Watches the container under the cursor or stops watching it: when it exits,
+    /// restarts or its health flips, a line under the tabs tells, whatever's on screen.
+    fn toggle_watch(&mut self) -> Result<(), Error> {
+        let (Some(DockerKind::Container), Some(row)) = (self.state.active, self.state.cursor_row())
+        else {
+            return Err(self.wrong());
+        };
+        let name = String::from(row.name());
+        let watched = match self.state.watched.remove(&name) {
+            true => false,
+            false => self.state.watched.insert(name.clone()),
+        };
+        // the events keep being followed for the desktop, if it's told of every exit
+        let followed = !self.state.watched.is_empty() || self.tocker.notifies(Desktop::DIED);
+        match (followed, self.state.alerts.take()) {
+            (false, Some(alerts)) => alerts.cancel(),
+            (true, None) => self.follow_alerts()?,
+            (_, alerts) => self.state.alerts = alerts,
+        }
+        self.notify(match watched {
+            true => format!("watching {name}"),
+            false => format!("stopped watching {name}"),
+        });
+        Ok(())
+    }
+
+    fn follow_alerts(&mut self) -> Result<(), Error> {
+        let sender = self.sender.clone();
+        let alerts = self.tocker.events(move |line| {
+            sender.send(AppEvent::Alert(line)).ok();
+        })?;
+        self.state.alerts = Some(alerts);
+        Ok(())
+    }
+
+    fn on_alert(&mut self, line: Result<String, Error>) -> Result<(), Error> {
+        if self.state.alerts.is_none() {
+            return Ok(());
+        }
+        // the banner already tells why they stopped, they follow again once it's back
+        let line = match (line, &self.state.unreachable) {
+            (Ok(line), _) => line,
+            (Err(_), Some(_)) => return Ok(()),
+            (Err(err), None) => return Err(err),
+        };
+        let Some((time, action, name)) = event_parts(&line) else {
+            return Ok(());
+        };
+        if action == "die" && self.tocker.notifies(Desktop::DIED) {
+            desktop::notify(format!("{name} died"), format!("at {time}"));
+        }
+        let happened = match action {
+            "die" => String::from("exited"),
+            "restart" => String::from("restarted"),
+            _ => match action.strip_prefix("health_status: ") {
+                Some(health) => format!("is {health}"),
+                None => return Ok(()),
+            },
+        };
+        if !self.state.watched.contains(name) {
+            return Ok(());
+        }
+        let alert = format!(" {time}  {name} {happened} ");
+        info!(alert = alert.trim(), "watched container");
+        self.announce(alert.trim());
+        self.state.alert = Some(alert);
+        Ok(())
+    }
+
+    /// Follows the logs of the first picked container at the side, in place of the one
+    /// pinned before, picking none unpins it.
+    fn pin(&mut self, target: &str) -> Result<(), Error> {
+        if let Some((_, following)) = self.state.pinned.take() {
+            following.cancel();
+        }
+        self.state.pinned_lines.clear();
+        self.go_to_first();
+        let Some(container) = target.split_whitespace().next() else {
+            return Ok(());
+        };
+        let name = self
+            .state
+            .table
+            .as_ref()
+            .and_then(|table| table.rows.iter().find(|row| row.id == container))
+            .map_or(container, |row| row.name())
+            .to_string();
+        let sender = self.sender.clone();
+        let following = self.tocker.logs(container, PINNED_LOGS, move |line| {
+            sender.send(AppEvent::Pinned(line)).ok();
+        })?;
+        self.state.pinned = Some((name, following));
+        Ok(())
+    }
+
+    fn on_pinned_line(&mut self, line: Result<String, Error>) -> Result<(), Error> {
+        if self.state.pinned.is_none() {
+            return Ok(());
+        }
+        let line = line.unwrap_or_else(|err| format!("logs stopped: {err}"));
+        self.state.pinned_lines.push_back(line);
+        if self.state.pinned_lines.len() > PINNED_TAIL {
+            self.state.pinned_lines.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Follows the logs of the picked containers in the log viewer, merged as they come
+    /// when there are several.
+    fn view_logs(&mut self, target: &str) -> Result<(), Error> {
+        let names: Vec<String> = target
+            .split_whitespace()
+            .map(|container| {
+                self.state
+                    .table
+                    .as_ref()
+                    .and_then(|table| table.rows.iter().find(|row| row.id == container))
+                    .map_or(container, |row| row.name())
+                    .to_string()
+            })
+            .collect();
+        if names.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "No container picked"));
+        }
+        let options = self.tocker.log_options();
+        let streams = self.follow_logs(&names, &options)?;
+        self.go_to_first();
+        self.state.logs = Some(LogView::new(names, options, streams));
+        self.state.commands = String::from(LOGS_COMMANDS);
+        self.update_moment(Moment::LOGS);
+        Ok(())
+    }
+
+    /// Follows the logs in the viewer again, at least as many lines as it holds, e.g. once
+    /// they should carry timestamps.
+    fn refollow_logs(&mut self) -> Result<(), Error> {
+        let Some(logs) = self.state.logs.as_mut() else {
+            return Ok(());
+        };
+        logs.stop();
+        let names = logs.names.clone();
+        let options = LogOptions {
+            tail: logs.held().max(logs.options.tail),
+            ..logs.options.clone()
+        };
+        logs.clear();
+        let streams = self.follow_logs(&names, &options)?;
+        if let Some(logs) = self.state.logs.as_mut() {
+            logs.streams = streams;
+        }
+        Ok(())
+    }
+
+    /// Follows the logs of each of `containers`, their lines told apart by the index of
+    /// their container.
+    fn follow_logs(
+        &self,
+        containers: &[String],
+        options: &LogOptions,
+    ) -> Result<Vec<CancellationToken>, Error> {
+        containers
+            .iter()
+            .enumerate()
+            .map(|(source, container)| {
+                let sender = self.sender.clone();
+                self.tocker.logs(container, options.clone(), move |line| {
+                    sender.send(AppEvent::Logged(source, line)).ok();
+                })
+            })
+            .collect()
+    }
+
+    fn on_logged_line(&mut self, source: usize, line: Result<String, Error>) -> Result<(), Error> {
+        let Some(logs) = self.state.logs.as_mut() else {
+            return Ok(());
+        };
+        logs.push(
+            source,
+            line.unwrap_or_else(|err| format!("logs stopped: {err}")),
+        );
+        Ok(())
+    }
+
+    fn on_logs(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        let msg_answer = self.check_key(&key_event)?;
+        let typing = self.state.logs.as_ref().and_then(|logs| logs.typing);
+        match (msg_answer, typing) {
+            (Message::OK, _) => {}
+            (Message::CANCEL, Some(prompt)) => {
+                if let Some(logs) = self.state.logs.as_mut() {
+                    if prompt == Prompt::Search {
+                        logs.pattern.clear();
+                    }
+                    logs.typing = None;
+                }
+                self.state.commands = String::from(LOGS_COMMANDS);
+                return Ok(());
+            }
+            (msg_answer, _) => return self.next_action(msg_answer),
+        }
+        if let Some(prompt) = typing {
+            return self.on_logs_prompt(prompt, key_event);
+        }
+        let Some(logs) = self.state.logs.as_mut() else {
+            return Ok(());
+        };
+        match key_event.code {
+            KeyCode::Up | KeyCode::Char('k') => logs.up(1),
+            KeyCode::Down | KeyCode::Char('j') => logs.down(1),
+            KeyCode::PageUp => logs.up(DETAIL_PAGE),
+            KeyCode::PageDown => logs.down(DETAIL_PAGE),
+            KeyCode::End | KeyCode::Char('G') => logs.follow(),
+            KeyCode::Char('/') => {
+                logs.pattern.clear();
+                logs.typing = Some(Prompt::Search);
+                self.state.commands = String::from(SEARCH_COMMANDS);
+            }
+            KeyCode::Char('l') => {
+                logs.typed = logs.options.tail.to_string();
+                logs.typing = Some(Prompt::Tail);
+                self.state.commands = format!("{TAIL_COMMANDS}{}", logs.typed);
+            }
+            KeyCode::Char('s') => {
+                logs.typed.clear();
+                logs.typing = Some(Prompt::Since);
+                self.state.commands = String::from(SINCE_COMMANDS);
+            }
+            KeyCode::Char('w') => {
+                logs.typed = format!("{}.log", logs.names.join("-"));
+                logs.typing = Some(Prompt::Save);
+                self.state.commands = format!("{SAVE_COMMANDS}{}", logs.typed);
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') if logs.pattern.is_empty() => {
+                self.notify(String::from("Nothing searched for, press '/' first"));
+            }
+            KeyCode::Char(c @ ('n' | 'N')) => {
+                if !logs.jump(c == 'n') {
+                    let missing = format!("No line holds {}", logs.pattern);
+                    self.notify(missing);
+                }
+            }
+            KeyCode::Char('f') => logs.toggle_only_matching(),
+            KeyCode::Char('t') => {
+                logs.options.timestamps = !logs.options.timestamps;
+                return self.refollow_logs();
+            }
+            _ => return Err(self.wrong()),
+        }
+        Ok(())
+    }
+
+    /// Types the search pattern, or a tail or since the logs are asked for again with.
+    fn on_logs_prompt(&mut self, prompt: Prompt, key_event: KeyEvent) -> Result<(), Error> {
+        let Some(logs) = self.state.logs.as_mut() else {
+            return Ok(());
+        };
+        let typed = match prompt {
+            Prompt::Search => &mut logs.pattern,
+            Prompt::Tail | Prompt::Since | Prompt::Save => &mut logs.typed,
+        };
+        match key_event.code {
+            KeyCode::Backspace => {
+                typed.pop();
+            }
+            KeyCode::Char(c) => typed.push(c),
+            KeyCode::Enter => {
+                logs.typing = None;
+                self.state.commands = String::from(LOGS_COMMANDS);
+                match prompt {
+                    Prompt::Search => {
+                        if !logs.pattern.is_empty() && !logs.jump(true) {
+                            let missing = format!("No line holds {}", logs.pattern);
+                            self.notify(missing);
+                        }
+                        return Ok(());
+                    }
+                    Prompt::Tail => {
+                        logs.options.tail = logs.typed.trim().parse().map_err(|_| {
+                            Error::new(ErrorKind::InvalidInput, "Tail should be a number of lines")
+                        })?;
+                    }
+                    Prompt::Since => {
+                        logs.options.since = match logs.typed.trim() {
+                            "" => None,
+                            since => Some(parse_since(since)?),
+                        };
+                    }
+                    Prompt::Save => {
+                        let typed = logs.typed.trim();
+                        let path = match typed.strip_prefix("~/").zip(dirs::home_dir()) {
+                            Some((rest, home)) => home.join(rest),
+                            None => PathBuf::from(typed),
+                        };
+                        let saved = fs::write(path, logs.text()).map(|_| logs.held());
+                        let notice = match &saved {
+                            Ok(count) => format!("saved {count} log lines to {typed}"),
+                            Err(err) => format!("saving the logs failed: {err}"),
+                        };
+                        self.notify(notice);
+                        return saved.map(|_| ());
+                    }
+                }
+                // from the newest line, the ones held may be too many or too few
+                logs.clear();
+                logs.follow();
+                return self.refollow_logs();
+            }
+            _ => return Err(self.wrong()),
+        }
+        let legend = match prompt {
+            Prompt::Search => SEARCH_COMMANDS,
+            Prompt::Tail => TAIL_COMMANDS,
+            Prompt::Since => SINCE_COMMANDS,
+            Prompt::Save => SAVE_COMMANDS,
+        };
+        self.state.commands = format!("{legend}{typed}");
+        Ok(())
+    }
+
+    fn go_to_history(&mut self) -> Result<(), Error> {
+        if self.state.history.is_empty() {
+            return Ok(());
+        }
+        self.go_to_first();
+        self.state.history.open();
+        self.state.commands = String::from(HISTORY_COMMANDS);
+        self.update_moment(Moment::HISTORY);
+        Ok(())
+    }
+
+    fn on_history(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        let msg_answer = self.check_key(&key_event)?;
+        if let Some(printed) = self.state.printed.as_mut() {
+            match (msg_answer, key_event.code) {
+                (Message::CANCEL, _) | (Message::OK, KeyCode::Char('o')) => {
+                    self.state.printed = None
+                }
+                (Message::OK, KeyCode::Up | KeyCode::Char('k')) => printed.up(1),
+                (Message::OK, KeyCode::Down | KeyCode::Char('j')) => printed.down(1),
+                (Message::OK, KeyCode::PageUp) => printed.up(DETAIL_PAGE),
+                (Message::OK, KeyCode::PageDown) => printed.down(DETAIL_PAGE),
+                (Message::OK, _) => return Err(self.wrong()),
+                (message, _) => return self.next_action(message),
+            }
+            return Ok(());
+        }
+        if !matches!(msg_answer, Message::OK) {
+            return self.next_action(msg_answer);
+        }
+        match key_event.code {
+            KeyCode::Up | KeyCode::Char('k') => self.state.history.up(),
+            KeyCode::Down | KeyCode::Char('j') => self.state.history.down(),
+            KeyCode::Enter => return self.rerun(),
+            KeyCode::Char('o') => return self.open_printed(),
+            _ => return Err(self.wrong()),
+        }
+        Ok(())
+    }
+
+    /// Shows everything the failed entry under the cursor printed.
+    fn open_printed(&mut self) -> Result<(), Error> {
+        let Some((line, printed)) = self.state.history.printed() else {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                "Only a command that failed keeps its output",
+            ));
+        };
+        self.state.printed = Some(Printed::new(line, printed.to_vec()));
+        Ok(())
+    }
+
+    fn go_to_contexts(&mut self) -> Result<(), Error> {
+        let contexts = self.tocker.contexts()?;
+        self.go_to_first();
+        self.state.contexts = Some(Contexts::new(contexts));
+        self.state.commands = String::from(CONTEXT_COMMANDS);
+        self.update_moment(Moment::CONTEXT);
+        Ok(())
+    }
+
+    fn on_contexts(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        let msg_answer = self.check_key(&key_event)?;
+        if !matches!(msg_answer, Message::OK) {
+            self.state.contexts = None;
+            return self.next_action(msg_answer);
+        }
+        let Some(contexts) = self.state.contexts.as_mut() else {
+            return Ok(());
+        };
+        match key_event.code {
+            KeyCode::Up | KeyCode::Char('k') => contexts.up(),
+            KeyCode::Down | KeyCode::Char('j') => contexts.down(),
+            KeyCode::Enter => return self.switch_context(),
+            _ => return Err(self.wrong()),
+        }
+        Ok(())
+    }
+
+    /// Moves everything over to the context under the cursor: the listings are fetched
+    /// again from its daemon, the pinned logs go and the events pane follows the new one.
+    fn switch_context(&mut self) -> Result<(), Error> {
+        let Some(context) = self
+            .state
+            .contexts
+            .take()
+            .and_then(|contexts| contexts.selected().cloned())
+        else {
+            return Ok(());
+        };
+        self.go_to_first();
+        self.tocker.use_context(&context)?;
+        info!(context = %context.name, host = %self.tocker.get_host(), "switched context");
+        // whatever the previous worker still had queued won't answer
+        self.state.pending = 0;
+        self.state.unreachable = None;
+        self.start_heartbeat()?;
+        if let Some((_, following)) = self.state.pinned.take() {
+            following.cancel();
+        }
+        self.state.pinned_lines.clear();
+        if let Some(events) = self.state.events.take() {
+            events.cancel();
+            self.toggle_events()?;
+        }
+        let active = self.state.active;
+        self.leave_tab();
+        self.state.tabs.clear();
+        self.state.content.clear();
+        self.notify(format!("Switched to {}", context.name));
+        match active {
+            Some(kind) => self.switch_tab(kind),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs the entry under the cursor again, with the same keys and target.
+    fn rerun(&mut self) -> Result<(), Error> {
+        let Some(entry) = self.state.history.selected() else {
+            return Ok(());
+        };
+        let (rerun, target) = (entry.rerun, entry.target.clone());
+        self.go_to_first();
+        match rerun {
+            Rerun::Keys(first, second) => {
+                self.state.first = Some(first);
+                self.state.second = Some(second);
+            }
+            Rerun::Build => self.state.building = true,
+            Rerun::Line => self.state.editing = true,
+        }
+        self.execute_cmd(&target)
+    }
+
+    /// Partial output of a running job, shown until it's done.
+    fn on_progress(&mut self, lines: Vec<String>) -> Result<(), Error> {
+        // a refresh of the listing would cover it, e.g. a checklist of what's being removed
+        if self.state.table.is_some() {
+            self.leave_tab();
+            self.state.filter.clear();
+            self.state.table = None;
+        }
+        self.state.content = lines.into_iter().map(ContentItem::plain).collect();
+        Ok(())
+    }
+
+    /// Hands the terminal over to `command` and takes it back once it exits.
+    fn suspend(&mut self, mut command: Command) -> Result<(), Error> {
+        self.input.paused.store(true, Ordering::SeqCst);
+        let polling = self.input.polling.lock();
+        // every step is taken whatever the ones before did, the terminal is taken back and
+        // the input resumed before any of them fails
+        let left: Result<(), Error> = [
+            disable_raw_mode(),
+            execute!(self.terminal.backend_mut(), LeaveAlternateScreen),
+            self.terminal.show_cursor(),
+        ]
+        .into_iter()
+        .collect();
+
+        let status = left.and_then(|_| {
+            info!(command = ?command, "running");
+            command.status()
+        });
+        match &status {
+            Ok(status) => info!(%status, "exited"),
+            Err(err) => info!(error = %err, "failed to run"),
+        }
+
+        let back: Result<(), Error> = [
+            enable_raw_mode(),
+            execute!(self.terminal.backend_mut(), EnterAlternateScreen),
+            self.terminal.hide_cursor(),
+            self.terminal.clear(),
+        ]
+        .into_iter()
+        .collect();
+        drop(polling);
+        self.input.paused.store(false, Ordering::SeqCst);
+        status.and(back)
+    }
+
+    /// Hands what's on screen to `$PAGER` with the TUI suspended until it's closed: the
+    /// inspected json, a snapshot of the logs, what a failed command printed or the
+    /// output of the last one.
+    fn page(&mut self) -> Result<(), Error> {
+        let state = &self.state;
+        let content = match (&state.moment, &state.detail, &state.logs, &state.printed) {
+            (Moment::DETAIL, Some(detail), ..) => detail.json(),
+            (Moment::LOGS, _, Some(logs), _) => logs.text(),
+            (Moment::HISTORY, .., Some(printed)) => printed.text(),
+            _ if !state.content.is_empty() => state
+                .content
+                .iter()
+                .map(|item| format!("{}\n", item.text))
+                .collect(),
+            _ => return Err(Error::new(ErrorKind::NotFound, "Nothing to page")),
+        };
+        let path = env::temp_dir().join(format!(
+            "tocker-{}-{}.txt",
+            process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        let written = private_file(&path)?.write_all(content.as_bytes());
+        let paged = written.and_then(|_| self.suspend(pager(&path)));
+        fs::remove_file(&path).ok();
+        paged
+    }
+
+    fn clean(&mut self) -> Result<(), Error> {
+        self.leave_tab();
+        self.state.content = vec![];
+        Ok(())
+    }
+
+    fn help(&mut self) -> Result<(), Error> {
+        self.state.help = Some(Help {
+            sections: self.tocker.help_sections(),
+        });
+        Ok(())
+    }
+
+    /// The overlay sits on top of whatever moment is going on, only closing it gets through.
+    fn on_help(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        match self.check_key(&key_event)? {
+            Message::CANCEL | Message::HELP => self.state.help = None,
+            Message::QUIT => self.quit_tocker(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), Error> {
+        self.go_to_first();
+        self.state.input = Input::default();
+        self.state.prompt = String::from(BUILD_COMMANDS);
+        self.state.commands = String::from(BUILD_COMMANDS);
+        self.state.building = true;
+        self.update_moment(Moment::INPUT);
+        Ok(())
+    }
+
+    fn cancel(&mut self) -> Error {
+        self.go_to_first();
+        // a stalled daemon would keep the spinner going forever, their replies say
+        // they were canceled
+        if self.state.pending > 0 {
+            self.tocker.cancel_jobs();
+        }
+        Error::new(ErrorKind::Interrupted, "User canceled the action")
+    }
+
+    fn wrong(&mut self) -> Error {
+        Error::new(ErrorKind::InvalidInput, "Press only the available keys")
+    }
+
+    fn next_action(&mut self, message: Message) -> Result<(), Error> {
+        match message {
+            Message::HELP => self.help(),
+            Message::CLEAN => self.clean(),
+            Message::BUILD => self.build(),
+            Message::SPLIT => {
+                self.state.split = !self.state.split;
+                Ok(())
+            }
+            Message::TAB => self.next_tab(),
+            Message::HISTORY => self.go_to_history(),
+            Message::EVENTS => self.toggle_events(),
+            Message::FIND => self.go_to_find(),
+            Message::PALETTE => self.go_to_palette(),
+            Message::CONTEXT => self.go_to_contexts(),
+            Message::RECORD => self.toggle_recording(),
+            Message::PAGER => self.page(),
+            Message::MACRO(index) => self.replay_macro(index),
+            Message::CANCEL => Err(self.cancel()),
+            Message::QUIT => {
+                self.quit_tocker();
+                Ok(())
+            }
+            Message::WRONG => Err(self.wrong()),
+            Message::OK => Ok(()), //next key or check_combination
+        }
+    }
+
+    fn check_key(&mut self, key_event: &KeyEvent) -> Result<Message, Error> {
+        self.tocker.check_keybinding(key_event, &self.state.moment)
+    }
+
+    fn go_to_first(&mut self) {
+        if let Some(watching) = self.state.watching.take() {
+            watching.cancel();
+        }
+        let initial_commands = self.tocker.get_initial_commands().clone();
+        self.state.commands = initial_commands;
+        self.state.scroll.cursor = 0;
+        self.state.first = None;
+        self.state.second = None;
+        self.state.building = false;
+        self.state.editing = false;
+        self.state.exporting = false;
+        self.state.naming = false;
+        self.state.secret_for = None;
+        self.state.picked = None;
+        self.state.form = None;
+        self.state.dangling = false;
+        self.state.detail = None;
+        self.state.explorer = None;
+        self.state.browser = None;
+        if let Some(logs) = self.state.logs.take() {
+            logs.stop();
+        }
+        self.state.confirm = None;
+        self.state.printed = None;
+        self.state.recorded = None;
+        self.update_moment(Moment::KIND);
+    }
+
+    /// Starts recording the keys pressed, or stops and asks what to save them as.
+    fn toggle_recording(&mut self) -> Result<(), Error> {
+        let Some(mut keys) = self.state.recording.take() else {
+            // from the first moment, where replays start too
+            self.go_to_first();
+            self.state.recording = Some(vec![]);
+            self.notify(String::from("Recording, press the same key to stop"));
+            return Ok(());
+        };
+        // the key that stopped the recording is the last one
+        keys.pop();
+        if keys.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "Nothing recorded"));
+        }
+        self.go_to_first();
+        let fields = vec![
+            Field {
+                name: "name",
+                label: "Name",
+                hint: "e.g. clean-exited",
+                input: Input::default(),
+            },
+            Field {
+                name: "key",
+                label: "Key",
+                hint: "to replay it, e.g. alt+1",
+                input: Input::default(),
+            },
+        ];
+        let title = format!("Save {} keys as a macro", keys.len());
+        self.state.form = Some(Form::new(title, fields));
+        self.state.recorded = Some(keys);
+        self.state.commands = String::from(FORM_COMMANDS);
+        self.update_moment(Moment::FORM);
+        Ok(())
+    }
+
+    /// Saves the keys just recorded under the name and key filled in the form.
+    fn save_macro(&mut self, values: &[String]) -> Result<(), Error> {
+        let field = |name: &str| {
+            values
+                .iter()
+                .find_map(|value| value.strip_prefix(&format!("{name}=")))
+                .unwrap_or_default()
+                .to_string()
+        };
+        let (name, key) = (field("name"), field("key"));
+        let keys = self.state.recorded.as_deref().unwrap_or_default();
+        self.tocker.save_macro(&name, &key, keys)?;
+        self.go_to_first();
+        self.notify(format!("Saved macro {name}, press {key} to replay it"));
+        Ok(())
+    }
+
+    /// Replays a macro from the first moment on, fed a key at a time by `replay`.
+    fn replay_macro(&mut self, index: usize) -> Result<(), Error> {
+        if self.state.recording.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Stop recording before replaying a macro",
+            ));
+        }
+        let keys = self.tocker.macro_keys(index)?;
+        self.go_to_first();
+        self.state.replaying = keys.into();
+        Ok(())
+    }
+
+    /// Presses the next keys of the macro being replayed, each once the jobs started by
+    /// the previous ones are back, e.g. the listing to select rows from.
+    fn replay(&mut self) -> Result<(), Error> {
+        while self.state.pending == 0 {
+            let Some(key) = self.state.replaying.pop_front() else {
+                return Ok(());
+            };
+            self.on_key(key)?;
+        }
+        Ok(())
+    }
+
+    fn go_to_second(&mut self, first: &KeyEvent) -> Result<(), Error> {
+        self.update_available_commands(first)?;
+        self.state.first = Some(*first);
+        self.update_moment(Moment::COMMAND);
+        Ok(())
+    }
+
+    fn go_to_input(&mut self, second: &KeyEvent) {
+        self.state.input = Input::default();
+        self.state.prompt = format!("{}{}", self.tocker.input_hint(second), INPUT_COMMANDS);
+        self.state.commands = self.state.prompt.clone();
+        self.state.second = Some(*second);
+        self.update_moment(Moment::INPUT);
+    }
+
+    fn on_input(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        let path_input = self
+            .state
+            .second
+            .is_some_and(|second| self.tocker.path_input(&second));
+        if key_event.code == KeyCode::Tab && path_input {
+            if self.state.input.complete_path() {
+                self.state.commands = format!("{}{}", self.state.prompt, self.state.input.shown());
+            }
+            return Ok(());
+        }
+        let msg_answer = self.check_key(&key_event)?;
+        if !matches!(msg_answer, Message::OK) {
+            return self.next_action(msg_answer);
+        }
+        if key_event.code == KeyCode::Enter {
+            let input = self.state.input.value().trim().to_string();
+            if let Some(picked) = self.state.picked.take() {
+                return self.execute_cmd(&format!("{picked} {input}"));
+            }
+            if let Some(target) = self.state.secret_for.take() {
+                return self.execute_secret(&target, &input);
+            }
+            if self.state.exporting {
+                return self.export(&input);
+            }
+            if mem::take(&mut self.state.naming) {
+                return self.execute_named(&input);
+            }
+            return self.execute_cmd(&input);
+        }
+        if self.state.input.edit(&key_event) {
+            self.state.commands = format!("{}{}", self.state.prompt, self.state.input.shown());
+        }
+        Ok(())
+    }
+
+    /// Asks for the secret of a command masked, e.g. the password of a login, with `target`
+    /// kept for when it's typed.
+    fn go_to_secret(&mut self, target: &str) -> Result<(), Error> {
+        let Some(second) = self.state.second else {
+            return Err(self.wrong());
+        };
+        let hint = self.tocker.secret_hint(&second, target)?;
+        self.state.secret_for = Some(String::from(target));
+        self.state.input = Input::masked();
+        self.state.prompt = format!("{hint}{INPUT_COMMANDS}");
+        self.state.commands = self.state.prompt.clone();
+        self.update_moment(Moment::INPUT);
+        Ok(())
+    }
+
+    /// Asks for a command by name, the cursor stays where it is for commands left
+    /// without a target.
+    fn go_to_named(&mut self) {
+        self.state.input = Input::default();
+        self.state.prompt = String::from(NAMED_COMMANDS);
+        self.state.commands = String::from(NAMED_COMMANDS);
+        self.state.naming = true;
+        self.update_moment(Moment::INPUT);
+    }
+
+    fn execute_named(&mut self, line: &str) -> Result<(), Error> {
+        let (first, second, target) = self.tocker.named(line, self.state.active)?;
+        self.run_keys(first, second, target)
+    }
+
+    /// Runs the command of `first` and `second` as if they were pressed, straight on
+    /// `target`, or on the rows picked or under the cursor for commands acting on rows.
+    fn run_keys(&mut self, first: KeyEvent, second: KeyEvent, target: String) -> Result<(), Error> {
+        self.go_to_second(&first)?;
+        let dangling = self.tocker.dangling_cmd(&second);
+        let form = self.tocker.form_cmd(&second);
+        let target = match self.check_combination(&first, &second)? {
+            TargetType::SELECT if dangling => String::new(),
+            TargetType::SELECT if target.is_empty() => self.cursor_target(),
+            TargetType::SELECT => target,
+            TargetType::INPUT if !form => target,
+            _ => String::new(),
+        };
+        if target.is_empty() {
+            return self.on_second(second);
+        }
+        self.state.second = Some(second);
+        self.execute_cmd(&target)
+    }
+
+    /// The rows picked, or the one under the cursor if none is.
+    fn cursor_target(&mut self) -> String {
+        let picked = self.extract_target_string();
+        if !picked.is_empty() {
+            return picked;
+        }
+        self.state
+            .content
+            .get(self.state.scroll.cursor)
+            .and_then(|item| item.id.clone())
+            .unwrap_or_default()
+    }
+
+    fn go_to_target(&mut self, second: &KeyEvent) {
+        self.update_commands_target();
+        self.state.second = Some(*second);
+        self.update_moment(Moment::TARGET);
+    }
+
+    fn on_first(&mut self, first: KeyEvent) -> Result<(), Error> {
+        // pinged again right away rather than at the next heartbeat
+        if self.state.unreachable.is_some() && first.code == KeyCode::Enter {
+            self.notify(String::from("Retrying…"));
+            return self.start_heartbeat();
+        }
+        let pending = self.state.twice.take();
+        if let Some((kind, command)) = self.tocker.repeat_keys(&first, self.state.active) {
+            if pending == Some(first) {
+                return self.run_keys(kind, command, String::new());
+            }
+            self.state.twice = Some(first);
+            return Ok(());
+        }
+        if let (KeyCode::Char(digit @ '1'..='9'), KeyModifiers::NONE) =
+            (first.code, first.modifiers)
+        {
+            let index = digit as usize - '1' as usize;
+            if let Some(kind) = TABS.get(index) {
+                return self.switch_tab(*kind);
+            }
+        }
+        // the listing can be filtered before picking what to do with it
+        match self.tocker.check_select(first) {
+            Ok(Select::FILTER) => {
+                self.go_to_filter(false);
+                return Ok(());
+            }
+            Ok(Select::LABELS) => {
+                self.go_to_filter(true);
+                return Ok(());
+            }
+            Ok(Select::NEXT) => {
+                self.add_cursor();
+                return Ok(());
+            }
+            Ok(Select::PREV) => {
+                self.sub_cursor();
+                return Ok(());
+            }
+            Ok(Select::SORT) => {
+                self.go_to_sort();
+                return Ok(());
+            }
+            Ok(Select::CONFIRM) if self.state.active == Some(DockerKind::System) => {
+                return self.show_reclaimable()
+            }
+            Ok(Select::FAVORITE) => return self.toggle_favorite(),
+            Ok(Select::WATCH) => return self.toggle_watch(),
+            Ok(Select::COPY) => return self.copy(false),
+            Ok(Select::COPYNAME) => return self.copy(true),
+            Ok(Select::EXPORT) => return self.go_to_export(),
+            Ok(Select::PAGEUP) => {
+                self.page_cursor(false);
+                return Ok(());
+            }
+            Ok(Select::PAGEDOWN) => {
+                self.page_cursor(true);
+                return Ok(());
+            }
+            Ok(Select::FIRST) => {
+                self.jump_cursor(false);
+                return Ok(());
+            }
+            Ok(Select::LAST) => {
+                self.jump_cursor(true);
+                return Ok(());
+            }
+            Ok(Select::PREVTAB) => return self.step_tab(false),
+            Ok(Select::NEXTTAB) => return self.step_tab(true),
+            Ok(Select::COMMAND) => {
+                self.go_to_named();
+                return Ok(());
+            }
+            Ok(Select::TRUNCATE) => {
+                self.toggle_full();
+                return Ok(());
+            }
+            Ok(Select::DIGESTS) if self.state.active == Some(DockerKind::Image) => {
+                return self.toggle_digests()
+            }
+            // on the listing it's every container, rather than every row
+            Ok(Select::ALL)
+                if self.state.active == Some(DockerKind::Container) && !self.state.reclaimable =>
+            {
+                return self.toggle_all()
+            }
+            _ => {}
+        }
+        let msg_answer = self.check_key(&first)?;
+        self.next_action(msg_answer)?;
+        self.go_to_second(&first)
+    }
+
+    fn on_second(&mut self, second: KeyEvent) -> Result<(), Error> {
+        let msg_answer = self.check_key(&second)?;
+        self.next_action(msg_answer)?;
+        let Some(first) = self.state.first else {
+            return Err(Error::new(ErrorKind::InvalidInput, "Missing first key"));
+        };
+        let form = self.tocker.form_cmd(&second);
+        let dangling = self.tocker.dangling_cmd(&second);
+        match self.check_combination(&first, &second)? {
+            TargetType::SELECT if dangling => match self.tocker.kind_of(&first) {
+                Some(kind) => self.show_dangling(kind),
+                None => Err(self.wrong()),
+            },
+            TargetType::SELECT | TargetType::SELECTINPUT => {
+                self.go_to_target(&second);
+                Ok(())
+            }
+            TargetType::INPUT if form => {
+                self.go_to_form(&second);
+                Ok(())
+            }
+            TargetType::INPUT => {
+                self.go_to_input(&second);
+                Ok(())
+            }
+            TargetType::CONFIRM => self.go_to_confirm(&first, &second),
+            _ => {
+                self.state.second = Some(second);
+                self.execute_cmd("")
+            }
+        }
+    }
+
+    fn on_key(&mut self, key_event: KeyEvent) -> Result<(), Error> {
+        // what's typed into a password prompt stays out of the log
+        if !(self.state.moment == Moment::INPUT && self.state.input.is_masked()) {
+            debug!(key = ?key_event.code, modifiers = ?key_event.modifiers, moment = ?self.state.moment, "key");
+            // passwords typed stay out of macros too
+            if let Some(keys) = self.state.recording.as_mut() {
+                keys.push(key_event);
+            }
+        }
+        self.state.notice = None;
+        self.state.alert = None;
+        if self.state.help.is_some() {
+            return self.on_help(key_event);
+        }
+        if self.state.finder.is_some() {
+            return self.on_find(key_event);
+        }
+        match self.state.moment {
+            Moment::KIND => self.on_first(key_event),
+            Moment::COMMAND => self.on_second(key_event),
+            Moment::TARGET => self.on_target(key_event),
+            Moment::INPUT => self.on_input(key_event),
+            Moment::DETAIL => self.on_detail(key_event),
+            Moment::CONFIRM => self.on_confirm(key_event),
+            Moment::FILTER => self.on_filter(key_event),
+            Moment::SORT => self.on_sort(key_event),
+            Moment::HISTORY => self.on_history(key_event),
+            Moment::LAYERS => self.on_layers(key_event),
+            Moment::BROWSE => self.on_browse(key_event),
+            Moment::LOGS => self.on_logs(key_event),
+            Moment::SIGNAL => self.on_signal(key_event),
+            Moment::POLICY => self.on_policy(key_event),
+            Moment::CONTEXT => self.on_contexts(key_event),
+            Moment::FORM => self.on_form(key_event),
+            Moment::STATS => {
+                let msg_answer = self.check_key(&key_event)?;
+                self.next_action(msg_answer)
+            }
+        }
+    }
+
+    fn on_event(&mut self, event: AppEvent) -> Result<(), Error> {
+        match event {
+            AppEvent::Input(event) => {
+                let key_event = self.tocker.extract_key_event(event)?;
+                self.on_key(key_event)
+            }
+            AppEvent::Output(output) => self.on_output(output),
+            AppEvent::Listing(output, watch) => self.on_listing(output, watch),
+            AppEvent::Reclaimable(output, kind) => self.on_reclaimable(output, kind),
+            AppEvent::Refresh(output) => self.on_refresh(output),
+            AppEvent::Ping(output) => self.on_ping(output),
+            AppEvent::Update(output) => self.on_update(output),
+            AppEvent::Progress(lines) => self.on_progress(lines),
+            AppEvent::Detail(output) => self.on_detail_output(output),
+            AppEvent::Usage(output) => self.on_usage(output),
+            AppEvent::Search(output) => self.on_search(output),
+            AppEvent::Layers(output, image) => self.on_layers_output(output, image),
+            AppEvent::Files(output, volume) => self.on_files_output(output, volume),
+            AppEvent::Event(line) => self.on_event_line(line),
+            AppEvent::Alert(line) => self.on_alert(line),
+            AppEvent::Pinned(line) => self.on_pinned_line(line),
+            AppEvent::Logged(source, line) => self.on_logged_line(source, line),
+            AppEvent::Finished(entry, status, code, printed) => {
+                if let Some(line) = self.state.history.line(entry).map(String::from) {
+                    self.announce(format!("Finished {line}, exit code {code}"));
+                    self.state.ran = Some((line, code));
+                }
+                // e.g. a `docker rm` of which some targets failed
+                let status = match (status, code) {
+                    (Ok(()), 0) => Ok(()),
+                    (Ok(()), code) => Err(format!("exit code {code}")),
+                    (failed, _) => failed,
+                };
+                if let Some(line) = self.state.history.line(entry) {
+                    notify_finished(&self.tocker, line, &status);
+                }
+                self.state.history.finish(entry, status);
+                self.state.history.keep(entry, printed);
+                Ok(())
+            }
+            AppEvent::Tick => {
+                self.state.spinner = (self.state.spinner + 1) % SPINNER.len();
+                Ok(())
+            }
+        }
+    }
+
+    fn add_cursor(&mut self) {
+        for _ in 0..self.state.content.len().max(1) {
+            self.state.scroll.cursor += 1;
+            if self.state.scroll.cursor >= self.state.content.len() {
+                self.state.scroll.cursor = 1;
+            }
+            if self.is_visible(self.state.scroll.cursor) {
+                break;
+            }
+        }
+    }
+
+    /// Indexes of the rows the cursor can land on, the header excluded.
+    fn visible_rows(&self) -> Vec<usize> {
+        self.state
+            .content
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(index, item)| matches(&self.state.filter, *index, item))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Moves a viewport worth of rows, stopping at the first and last ones.
+    fn page_cursor(&mut self, down: bool) {
+        let rows = self.visible_rows();
+        let Some(last) = rows.len().checked_sub(1) else {
+            return;
+        };
+        let current = rows
+            .iter()
+            .position(|index| *index >= self.state.scroll.cursor)
+            .unwrap_or(last);
+        let target = match down {
+            true => (current + self.state.scroll.page).min(last),
+            false => current.saturating_sub(self.state.scroll.page),
+        };
+        self.state.scroll.cursor = rows[target];
+    }
+
+    fn jump_cursor(&mut self, end: bool) {
+        let rows = self.visible_rows();
+        let target = match end {
+            true => rows.last(),
+            false => rows.first(),
+        };
+        if let Some(target) = target {
+            self.state.scroll.cursor = *target;
+        }
+    }
+
+    fn sub_cursor(&mut self) {
+        if self.state.content.len() < 2 {
+            return;
+        }
+        for _ in 0..self.state.content.len() {
+            self.state.scroll.cursor = self.state.scroll.cursor.saturating_sub(1);
+            if self.state.scroll.cursor == 0 {
+                self.state.scroll.cursor = self.state.content.len() - 1;
+            }
+            if self.is_visible(self.state.scroll.cursor) {
+                break;
+            }
+        }
+    }
+
+    fn spawn_input(&self) {
+        let sender = self.sender.clone();
+        let input = self.input.clone();
+        thread::spawn(move || loop {
+            if input.paused.load(Ordering::SeqCst) {
+                thread::sleep(TICK_RATE);
+                continue;
+            }
+            let polling = input.polling.lock();
+            let event = match poll(TICK_RATE) {
+                Ok(true) => match read() {
+                    Ok(event) => AppEvent::Input(event),
+                    Err(_) => break,
+                },
+                Ok(false) => AppEvent::Tick,
+                Err(_) => break,
+            };
+            drop(polling);
+            if sender.send(event).is_err() {
+                break;
+            }
+        });
+    }
+
+    pub fn start_loop(&mut self) {
+        self.spawn_input();
+        if let Err(err) = self.start_heartbeat() {
+            self.announce(err.to_string());
+        }
+        if let Err(err) = self.restore_view() {
+            self.announce(err.to_string());
+        }
+        if self.tocker.notifies(Desktop::DIED) {
+            if let Err(err) = self.follow_alerts() {
+                self.announce(err.to_string());
+            }
+        }
+        while let Ok(event) = self.events.recv() {
+            if let Err(err) = self.on_event(event) {
+                self.announce(err.to_string());
+            }
+            if let Err(err) = self.replay() {
+                self.state.replaying.clear();
+                self.notify(format!("Macro stopped: {err}"));
+            }
+            self.draw_ui().ok();
+        }
+    }
+}
+
+impl Drop for Tui {
+    fn drop(&mut self) {
+        restore_terminal().ok();
+    }
+}
+
+/// Tells the desktop a build or a pull is done, if the config asks for it.
+fn notify_finished(tocker: &Tocker, line: &str, status: &Result<(), String>) {
+    let verb = line
+        .strip_prefix("docker ")
+        .map(|rest| rest.strip_prefix("image ").unwrap_or(rest))
+        .and_then(|rest| rest.split_whitespace().next());
+    let (event, what) = match verb {
+        Some("build") => (Desktop::BUILT, "Build"),
+        Some("pull") => (Desktop::PULLED, "Pull"),
+        _ => return,
+    };
+    if !tocker.notifies(event) {
+        return;
+    }
+    let summary = match status {
+        Ok(()) => format!("{what} finished"),
+        Err(err) => format!("{what} failed: {err}"),
+    };
+    desktop::notify(summary, String::from(line));
+}
+
+/// The time, action and container of a line of the events, e.g.
+/// `12:03:04  die      web (nginx)`.
+fn event_parts(line: &str) -> Option<(&str, &str, &str)> {
+    let (rest, _image) = line.rsplit_once(" (")?;
+    let (rest, name) = rest.rsplit_once(' ')?;
+    let (time, action) = rest.split_once("  ")?;
+    Some((time, action.trim(), name))
+}
+
+/// Gives the terminal back to the shell: cooked mode, main screen, visible cursor.
+fn restore_terminal() -> Result<(), Error> {
+    disable_raw_mode()?;
+    execute!(stdout(), LeaveAlternateScreen, Show)
+}
+
+fn state_color(state: &str) -> Option<Color> {
+    match state {
+        "running" => Some(Color::Green),
+        "exited" | "dead" => Some(Color::Red),
+        "paused" => Some(Color::Yellow),
+        "restarting" => Some(Color::Magenta),
+        _ => None,
+    }
+}
+
+/// Anything left to reclaim stands out.
+fn reclaimable_color(reclaimable: &str) -> Option<Color> {
+    match reclaimable.starts_with(&format!("{} ", human_size(0))) {
+        true => None,
+        false => Some(Color::Yellow),
+    }
+}
+
+fn health_color(health: &str) -> Option<Color> {
+    match health {
+        "healthy" => Some(Color::Green),
+        "unhealthy" => Some(Color::Red),
+        "starting" => Some(Color::Yellow),
+        _ => None,
+    }
+}
+
+/// What the history keeps of a result, the error message if any.
+fn status<T>(result: &Result<T, Error>) -> Result<(), String> {
+    result.as_ref().map(|_| ()).map_err(|err| err.to_string())
+}
+
+fn exit_code(result: &Result<Output, Error>) -> i32 {
+    result.as_ref().map_or(1, Output::exit_code)
+}
+
+/// Everything a failed command printed, its errors among it, nothing if it succeeded.
+fn printed(result: &Result<Output, Error>) -> Vec<String> {
+    match result {
+        Ok(Output::Ran(report)) if !report.failures.is_empty() => report.lines.clone(),
+        Ok(_) => vec![],
+        Err(err) => vec![format!("Error: {err}")],
+    }
+}
+
+/// A new file at `path` only the user can read, failing rather than opening one already
+/// there, e.g. a link planted in the shared temp dir.
+fn private_file(path: &Path) -> Result<File, Error> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+/// `$PAGER` on the file at `path`, `less` if it's not set.
+fn pager(path: &Path) -> Command {
+    let pager = env::var("PAGER").unwrap_or(String::from("less"));
+    // e.g. `less -R`
+    let mut words = pager.split_whitespace();
+    let mut command = Command::new(words.next().unwrap_or("less"));
+    command.args(words);
+    command.arg(path);
+    command
+}
+
+/// What `moment` reads as at the end of the breadcrumb, and as plain mode announces it.
+fn moment_label(moment: &Moment) -> &'static str {
+    match moment {
+        Moment::KIND => "pick a kind",
+        Moment::COMMAND => "pick a command",
+        Moment::TARGET => "selecting",
+        Moment::INPUT => "typing",
+        Moment::DETAIL => "inspecting",
+        Moment::CONFIRM => "confirming",
+        Moment::FILTER => "filtering",
+        Moment::SORT => "sorting",
+        Moment::HISTORY => "history",
+        Moment::LAYERS => "exploring layers",
+        Moment::BROWSE => "browsing files",
+        Moment::LOGS => "reading logs",
+        Moment::SIGNAL => "picking a signal",
+        Moment::POLICY => "picking a restart policy",
+        Moment::CONTEXT => "picking a context",
+        Moment::FORM => "filling in the form",
+        Moment::STATS => "watching stats",
+    }
+}
+
+fn tab_title(kind: &DockerKind) -> &'static str {
+    match kind {
+        DockerKind::Image => "Images",
+        DockerKind::Container => "Containers",
+        DockerKind::Volume => "Volumes",
+        DockerKind::Project => "Projects",
+        DockerKind::System => "System",
+        DockerKind::Registry => "Registries",
+        DockerKind::Service => "Services",
+        DockerKind::Stack => "Stacks",
+        DockerKind::Secret => "Secrets",
+        DockerKind::Config => "Configs",
+        DockerKind::Fleet => "Fleet",
+    }
+}
+
+/// The columns of a listing that fit in `width` with how wide each gets, none when the
+/// content isn't a listing. Every column is as wide as its widest cell, the long ones
+/// capped unless `full`, until they don't fit, then the least needed ones go and the
+/// widest are cut down.
+fn fit_columns(content: &[ContentItem], width: u16, full: bool) -> Vec<(usize, u16)> {
+    let Some(header) = content.first().filter(|item| !item.cells.is_empty()) else {
+        return vec![];
+    };
+    let mut columns: Vec<(usize, u16)> = (0..header.cells.len())
+        .map(|column| {
+            let widest = content
+                .iter()
+                .filter_map(|item| item.cells.get(column))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or_default() as u16;
+            let name = header.cells[column].trim_end_matches([' ', '▲', '▼']);
+            match !full && LONG_COLUMNS.contains(&name) {
+                true => (column, widest.min(LONG_WIDTH)),
+                false => (column, widest),
+            }
+        })
+        .collect();
+    let total = |columns: &[(usize, u16)]| {
+        let spacing = COLUMN_SPACING * columns.len().saturating_sub(1) as u16;
+        columns.iter().map(|(_, width)| width).sum::<u16>() + spacing
+    };
+    for dropped in DROPPED_FIRST {
+        if total(&columns) <= width {
+            return columns;
+        }
+        columns.retain(|(column, _)| {
+            let name = header.cells[*column].trim_end_matches([' ', '▲', '▼']);
+            name != dropped
+        });
+    }
+    while total(&columns) > width {
+        let excess = total(&columns) - width;
+        let Some(widest) = columns
+            .iter_mut()
+            .map(|(_, width)| width)
+            .filter(|width| **width > NARROWEST_COLUMN)
+            .max()
+        else {
+            break;
+        };
+        *widest = widest.saturating_sub(excess).max(NARROWEST_COLUMN);
+    }
+    columns
+}
+
+/// The rows of a listing that pass the filter under its header, styled like the lines
+/// of any other content. Cells too long for their column end in an ellipsis, or wrap
+/// onto more lines of the row when `full`.
+fn listing_grid<'a>(
+    content: &'a [ContentItem],
+    filter: &Filter,
+    cursor: usize,
+    theme: &Theme,
+    columns: &[(usize, u16)],
+    widths: &'a [Constraint],
+    full: bool,
+) -> Grid<'a> {
+    let cell_of = |item: &ContentItem, column: usize| {
+        String::from(item.cells.get(column).map_or("", String::as_str))
+    };
+    let fitted = |item: &ContentItem, column: usize, width: u16| match full {
+        true => wrap(&cell_of(item, column), width as usize),
+        false => ellipsize(&cell_of(item, column), width as usize),
+    };
+    let header = content
+        .first()
+        .map(|item| {
+            columns
+                .iter()
+                .map(|(column, width)| {
+                    Cell::from(ellipsize(&cell_of(item, *column), *width as usize))
+                })
+                .collect::<Vec<Cell>>()
+        })
+        .unwrap_or_default();
+    let rows = content
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(|(index, item)| matches(filter, *index, item))
+        .map(|(index, item)| {
+            let (style, plain) = match (index == cursor, item.selected, item.color) {
+                (true, ..) => (theme.cursor, true),
+                (false, true, _) => (theme.selected, true),
+                (false, false, Some(color)) => (Style::default().fg(color), false),
+                (false, false, None) => (Style::default(), false),
+            };
+            let fitted: Vec<String> = columns
+                .iter()
+                .map(|(column, width)| fitted(item, *column, *width))
+                .collect();
+            let height = fitted
+                .iter()
+                .map(|cell| cell.lines().count())
+                .max()
+                .unwrap_or_default()
+                .max(1);
+            let cells: Vec<Cell> = columns
+                .iter()
+                .zip(fitted)
+                .map(|((column, _), text)| {
+                    let cell = Cell::from(text);
+                    match item.mark.filter(|(marked, _)| !plain && marked == column) {
+                        Some((_, color)) => cell.style(Style::default().fg(color)),
+                        None => cell,
+                    }
+                })
+                .collect();
+            GridRow::new(cells).style(style).height(height as u16)
+        });
+    Grid::new(rows)
+        .header(GridRow::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+        .widths(widths)
+        .column_spacing(COLUMN_SPACING)
+}
+
+/// `text` cut to `width` characters, the last of them an ellipsis if anything was cut.
+fn ellipsize(text: &str, width: usize) -> String {
+    match text.chars().count() > width {
+        true => {
+            let kept: String = text.chars().take(width.saturating_sub(1)).collect();
+            format!("{kept}…")
+        }
+        false => String::from(text),
+    }
+}
+
+/// `text` broken into lines of `width` characters, to show it whole in a narrow column.
+fn wrap(text: &str, width: usize) -> String {
+    text.chars()
+        .collect::<Vec<char>>()
+        .chunks(width.max(1))
+        .map(|line| line.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Draws a listing as its table, any other content line by line.
+fn render_content<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    block: Block,
+    items: Vec<ListItem>,
+    grid: Option<Grid>,
+    scroll: &mut Scroller,
+) {
+    match grid {
+        Some(grid) => f.render_stateful_widget(grid.block(block), area, &mut scroll.table),
+        None => f.render_stateful_widget(List::new(items).block(block), area, &mut scroll.list),
+    }
+}
+
+/// Case-insensitive substring match against the whole row, e.g. name, image or status,
+/// or the row labels against selectors, which plain output lines never match.
+fn matches(filter: &Filter, index: usize, item: &ContentItem) -> bool {
+    if index == 0 || filter.is_empty() {
+        return true;
+    }
+    match filter.labels {
+        true => item.id.is_some() && selects(&item.labels, &filter.text),
+        false => item
+            .text
+            .to_lowercase()
+            .contains(&filter.text.to_lowercase()),
     }
 }
@@ -0,0 +1,56 @@
+use ratatui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use tocker_core::Row;
+
+const INDENT: &str = "  ";
+const NO_ROW: &str = "Move the cursor on a row to see its details";
+
+/// Side pane with the details of the row under the cursor.
+pub fn render<B: Backend>(f: &mut Frame<B>, area: Rect, row: Option<&Row>, borders: Borders) {
+    let lines = match row {
+        Some(row) if !row.details.is_empty() => details(row),
+        Some(_) => vec![muted("No details for this row")],
+        None => vec![muted(NO_ROW)],
+    };
+    let block = Block::default().borders(borders).title(" Details ");
+    let pane = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(pane, area);
+}
+
+/// Single values sit next to their label, lists go one per line below it.
+fn details(row: &Row) -> Vec<Spans<'static>> {
+    let mut lines = vec![];
+    row.details.iter().for_each(|(label, values)| {
+        let label = Span::styled(format!("{label}: "), Style::default().fg(Color::Cyan));
+        match values.as_slice() {
+            [] => lines.push(Spans::from(vec![label, muted_span("none")])),
+            [value] => lines.push(Spans::from(vec![label, Span::raw(value.clone())])),
+            values => {
+                lines.push(Spans::from(label));
+                lines.extend(
+                    values
+                        .iter()
+                        .map(|value| Spans::from(format!("{INDENT}{value}"))),
+                );
+            }
+        }
+    });
+    lines
+}
+
+fn muted(text: &str) -> Spans<'static> {
+    Spans::from(muted_span(text))
+}
+
+fn muted_span(text: &str) -> Span<'static> {
+    Span::styled(String::from(text), Style::default().fg(Color::DarkGray))
+}
@@ -0,0 +1,65 @@
+use ratatui::{
+    backend::Backend,
+    layout::Rect,
+    style::Style,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+/// Everything a failed command printed, its errors included, drawn over the history.
+pub struct Printed {
+    title: String,
+    lines: Vec<String>,
+    pub list: ListState,
+}
+
+impl Printed {
+    pub fn new(line: &str, lines: Vec<String>) -> Printed {
+        let mut list = ListState::default();
+        list.select(Some(0));
+        Printed {
+            title: format!(" {line} "),
+            lines,
+            list,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.iter().map(|line| format!("{line}\n")).collect()
+    }
+
+    pub fn up(&mut self, by: usize) {
+        let cursor = self.list.selected().unwrap_or_default().saturating_sub(by);
+        self.list.select(Some(cursor));
+    }
+
+    pub fn down(&mut self, by: usize) {
+        let last = self.lines.len().saturating_sub(1);
+        let cursor = (self.list.selected().unwrap_or_default() + by).min(last);
+        self.list.select(Some(cursor));
+    }
+
+    pub fn render<B: Backend>(
+        &mut self,
+        f: &mut Frame<B>,
+        area: Rect,
+        highlight: Style,
+        borders: Borders,
+    ) {
+        let popup = Rect {
+            x: area.x + area.width / 10,
+            y: area.y + area.height / 10,
+            width: area.width - area.width / 5,
+            height: area.height - area.height / 5,
+        };
+        let items: Vec<ListItem> = self
+            .lines
+            .iter()
+            .map(|line| ListItem::new(line.as_str()))
+            .collect();
+        let block = Block::default().borders(borders).title(self.title.as_str());
+        let list = List::new(items).block(block).highlight_style(highlight);
+        f.render_widget(Clear, popup);
+        f.render_stateful_widget(list, popup, &mut self.list);
+    }
+}
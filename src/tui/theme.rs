@@ -0,0 +1,110 @@
+use std::io::{Error, ErrorKind};
+
+use ratatui::{
+    style::{Color, Style},
+    widgets::Borders,
+};
+
+use tocker_core::{Colors, ThemeConfig};
+
+/// Styles of everything `draw_ui` colors, from a built-in theme plus the config overrides.
+pub struct Theme {
+    pub cursor: Style,
+    pub selected: Style,
+    pub commands: Style,
+    pub highlight: Style,
+    pub tab: Style,
+    /// frames around the panes and dialogs, none in plain mode
+    pub borders: Borders,
+}
+
+impl Theme {
+    pub fn new(config: &ThemeConfig) -> Result<Theme, Error> {
+        let mut theme = match config.name.as_deref() {
+            None | Some("dark") => dark(),
+            Some("light") => light(),
+            Some(name) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unknown theme in config: {name}"),
+                ))
+            }
+        };
+        apply(&mut theme.cursor, &config.cursor)?;
+        apply(&mut theme.selected, &config.selected)?;
+        apply(&mut theme.commands, &config.commands)?;
+        apply(&mut theme.highlight, &config.highlight)?;
+        apply(&mut theme.tab, &config.tab)?;
+        Ok(theme)
+    }
+}
+
+fn dark() -> Theme {
+    Theme {
+        cursor: Style::default().bg(Color::Cyan).fg(Color::Black),
+        selected: Style::default().bg(Color::Gray).fg(Color::Black),
+        commands: Style::default().fg(Color::White).bg(Color::Black),
+        highlight: Style::default().bg(Color::DarkGray),
+        tab: Style::default().fg(Color::Cyan),
+        borders: Borders::ALL,
+    }
+}
+
+fn light() -> Theme {
+    Theme {
+        cursor: Style::default().bg(Color::Blue).fg(Color::White),
+        selected: Style::default().bg(Color::LightYellow).fg(Color::Black),
+        commands: Style::default().fg(Color::Black).bg(Color::White),
+        highlight: Style::default().bg(Color::Gray),
+        tab: Style::default().fg(Color::Blue),
+        borders: Borders::ALL,
+    }
+}
+
+fn apply(style: &mut Style, colors: &Option<Colors>) -> Result<(), Error> {
+    let Some(colors) = colors else {
+        return Ok(());
+    };
+    if let Some(fg) = &colors.fg {
+        *style = style.fg(parse_color(fg)?);
+    }
+    if let Some(bg) = &colors.bg {
+        *style = style.bg(parse_color(bg)?);
+    }
+    Ok(())
+}
+
+/// Parses colors like `cyan`, `lightblue`, `#1e1e2e` or a 256-color index like `236`.
+fn parse_color(color: &str) -> Result<Color, Error> {
+    let invalid = || {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid color in config: {color}"),
+        )
+    };
+    let parsed = match color.to_lowercase().as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" => Color::Gray,
+        "darkgray" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        hex if hex.starts_with('#') && hex.len() == 7 => {
+            let channel = |range| u8::from_str_radix(&hex[range], 16).map_err(|_| invalid());
+            Color::Rgb(channel(1..3)?, channel(3..5)?, channel(5..7)?)
+        }
+        index => Color::Indexed(index.parse().map_err(|_| invalid())?),
+    };
+    Ok(parsed)
+}